@@ -0,0 +1,15 @@
+-- object: public.encounter | type: TABLE --
+-- DROP TABLE IF EXISTS public.encounter CASCADE;
+CREATE TABLE public.encounter (
+	id serial NOT NULL,
+	player_id integer NOT NULL,
+	seed bytea NOT NULL,
+	created_at timestamptz NOT NULL DEFAULT now(),
+	CONSTRAINT encounter_pk PRIMARY KEY (id),
+	CONSTRAINT player_fk FOREIGN KEY (player_id)
+		REFERENCES public.player (id) ON DELETE CASCADE
+
+);
+-- ddl-end --
+COMMENT ON COLUMN public.encounter.seed IS E'Seed of the random number generator used for every roll of the encounter';
+-- ddl-end --
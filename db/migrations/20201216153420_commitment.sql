@@ -0,0 +1,31 @@
+-- object: public.encounter.commitment | type: COLUMN --
+-- ALTER TABLE public.encounter DROP COLUMN IF EXISTS commitment CASCADE;
+ALTER TABLE public.encounter ADD COLUMN commitment bytea;
+UPDATE public.encounter SET commitment = sha256(seed);
+ALTER TABLE public.encounter ALTER COLUMN commitment SET NOT NULL;
+-- ddl-end --
+COMMENT ON COLUMN public.encounter.commitment IS E'SHA-256 hash of the seed, published before the first roll';
+-- ddl-end --
+
+-- object: public.encounter.revealed_at | type: COLUMN --
+-- ALTER TABLE public.encounter DROP COLUMN IF EXISTS revealed_at CASCADE;
+ALTER TABLE public.encounter ADD COLUMN revealed_at timestamptz;
+-- ddl-end --
+COMMENT ON COLUMN public.encounter.revealed_at IS E'When the seed was made public. The seed stays secret while this is null';
+-- ddl-end --
+
+-- object: public.encounter_roll | type: TABLE --
+-- DROP TABLE IF EXISTS public.encounter_roll CASCADE;
+CREATE TABLE public.encounter_roll (
+	encounter_id integer NOT NULL,
+	position integer NOT NULL,
+	expression text NOT NULL,
+	total bigint NOT NULL,
+	CONSTRAINT encounter_roll_pk PRIMARY KEY (encounter_id,position),
+	CONSTRAINT encounter_fk FOREIGN KEY (encounter_id)
+		REFERENCES public.encounter (id) ON DELETE CASCADE
+
+);
+-- ddl-end --
+COMMENT ON TABLE public.encounter_roll IS E'Every roll of an encounter in the order they were made';
+-- ddl-end --
@@ -0,0 +1,53 @@
+-- object: public.player.name | type: COLUMN --
+-- ALTER TABLE public.player DROP COLUMN IF EXISTS name CASCADE;
+ALTER TABLE public.player ADD COLUMN name text;
+-- ddl-end --
+COMMENT ON COLUMN public.player.name IS E'Last known twitch login of the player';
+-- ddl-end --
+
+-- object: player_name_idx | type: INDEX --
+-- DROP INDEX IF EXISTS public.player_name_idx CASCADE;
+CREATE INDEX player_name_idx ON public.player
+	USING btree
+	(
+	  name
+	);
+-- ddl-end --
+
+-- object: public.roll_log | type: TABLE --
+-- DROP TABLE IF EXISTS public.roll_log CASCADE;
+CREATE TABLE public.roll_log (
+	id serial NOT NULL,
+	player_id integer NOT NULL,
+	encounter_id integer,
+	dice text NOT NULL,
+	modifiers text NOT NULL,
+	rolls smallint[] NOT NULL,
+	result bigint NOT NULL,
+	created_at timestamptz NOT NULL DEFAULT now(),
+	CONSTRAINT roll_log_pk PRIMARY KEY (id),
+	CONSTRAINT player_fk FOREIGN KEY (player_id)
+		REFERENCES public.player (id) ON DELETE CASCADE,
+	CONSTRAINT encounter_fk FOREIGN KEY (encounter_id)
+		REFERENCES public.encounter (id) ON DELETE CASCADE
+
+);
+-- ddl-end --
+COMMENT ON TABLE public.roll_log IS E'Every roll made during a game action';
+-- ddl-end --
+COMMENT ON COLUMN public.roll_log.dice IS E'Number and kind of dice, e.g. 4d6';
+-- ddl-end --
+COMMENT ON COLUMN public.roll_log.modifiers IS E'Dice modifiers in dice notation, e.g. advkh3';
+-- ddl-end --
+COMMENT ON COLUMN public.roll_log.rolls IS E'Result of every single die';
+-- ddl-end --
+
+-- object: roll_log_player_idx | type: INDEX --
+-- DROP INDEX IF EXISTS public.roll_log_player_idx CASCADE;
+CREATE INDEX roll_log_player_idx ON public.roll_log
+	USING btree
+	(
+	  player_id,
+	  created_at
+	);
+-- ddl-end --
@@ -0,0 +1,17 @@
+-- object: public.character_creation | type: TABLE --
+-- DROP TABLE IF EXISTS public.character_creation CASCADE;
+CREATE TABLE public.character_creation (
+	player_id integer NOT NULL,
+	scores smallint[] NOT NULL,
+	rerolled bool NOT NULL DEFAULT false,
+	created_at timestamptz NOT NULL DEFAULT now(),
+	CONSTRAINT character_creation_pk PRIMARY KEY (player_id),
+	CONSTRAINT player_fk FOREIGN KEY (player_id)
+		REFERENCES public.player (id) ON DELETE CASCADE
+
+);
+-- ddl-end --
+COMMENT ON TABLE public.character_creation IS E'Scores of characters that are being created but not yet assigned';
+-- ddl-end --
+COMMENT ON COLUMN public.character_creation.rerolled IS E'Whether the scores were rolled a second time';
+-- ddl-end --
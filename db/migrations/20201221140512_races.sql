@@ -0,0 +1,7 @@
+-- object: public.race | type: TYPE --
+ALTER TYPE public.race ADD VALUE IF NOT EXISTS 'dwarf';
+ALTER TYPE public.race ADD VALUE IF NOT EXISTS 'elf';
+ALTER TYPE public.race ADD VALUE IF NOT EXISTS 'halfling';
+ALTER TYPE public.race ADD VALUE IF NOT EXISTS 'orc';
+ALTER TYPE public.race ADD VALUE IF NOT EXISTS 'gnome';
+-- ddl-end --
@@ -0,0 +1,6 @@
+-- object: public.class | type: TYPE --
+ALTER TYPE public.class ADD VALUE IF NOT EXISTS 'rogue';
+ALTER TYPE public.class ADD VALUE IF NOT EXISTS 'wizard';
+ALTER TYPE public.class ADD VALUE IF NOT EXISTS 'cleric';
+ALTER TYPE public.class ADD VALUE IF NOT EXISTS 'ranger';
+-- ddl-end --
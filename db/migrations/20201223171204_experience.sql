@@ -0,0 +1,13 @@
+-- object: public.player.experience | type: COLUMN --
+-- ALTER TABLE public.player DROP COLUMN IF EXISTS experience CASCADE;
+ALTER TABLE public.player ADD COLUMN experience integer NOT NULL DEFAULT 0;
+-- ddl-end --
+COMMENT ON COLUMN public.player.experience IS E'Experience points collected in encounters';
+-- ddl-end --
+
+-- object: public.player.level | type: COLUMN --
+-- ALTER TABLE public.player DROP COLUMN IF EXISTS level CASCADE;
+ALTER TABLE public.player ADD COLUMN level smallint NOT NULL DEFAULT 1;
+-- ddl-end --
+COMMENT ON COLUMN public.player.level IS E'Level reached with the experience points';
+-- ddl-end --
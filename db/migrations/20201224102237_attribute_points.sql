@@ -0,0 +1,11 @@
+-- object: public.player.attribute_points | type: COLUMN --
+-- ALTER TABLE public.player DROP COLUMN IF EXISTS attribute_points CASCADE;
+ALTER TABLE public.player ADD COLUMN attribute_points smallint NOT NULL DEFAULT 0;
+-- ddl-end --
+COMMENT ON COLUMN public.player.attribute_points IS E'Attribute points granted on level up that were not trained yet';
+-- ddl-end --
+
+-- object: player_attribute_points_ck | type: CONSTRAINT --
+-- ALTER TABLE public.player DROP CONSTRAINT IF EXISTS player_attribute_points_ck CASCADE;
+ALTER TABLE public.player ADD CONSTRAINT player_attribute_points_ck CHECK (attribute_points >= 0);
+-- ddl-end --
@@ -0,0 +1,39 @@
+-- object: public.slot | type: TYPE --
+-- DROP TYPE IF EXISTS public.slot CASCADE;
+CREATE TYPE public.slot AS
+ ENUM ('weapon','armor','shield','accessory');
+-- ddl-end --
+
+-- object: public.item | type: TABLE --
+-- DROP TABLE IF EXISTS public.item CASCADE;
+CREATE TABLE public.item (
+	id serial NOT NULL,
+	player_id integer NOT NULL,
+	key text NOT NULL,
+	slot public.slot,
+	created_at timestamptz NOT NULL DEFAULT now(),
+	CONSTRAINT item_pk PRIMARY KEY (id),
+	CONSTRAINT player_fk FOREIGN KEY (player_id)
+		REFERENCES public.player (id) ON DELETE CASCADE
+
+);
+-- ddl-end --
+COMMENT ON TABLE public.item IS E'Items owned by players';
+-- ddl-end --
+COMMENT ON COLUMN public.item.key IS E'Key of the item in the item catalog';
+-- ddl-end --
+COMMENT ON COLUMN public.item.slot IS E'The slot the item is equipped in, if it is equipped';
+-- ddl-end --
+
+-- object: item_equipped_idx | type: INDEX --
+-- DROP INDEX IF EXISTS public.item_equipped_idx CASCADE;
+CREATE UNIQUE INDEX item_equipped_idx ON public.item
+	USING btree
+	(
+	  player_id,
+	  slot
+	)
+	WHERE (slot IS NOT NULL);
+-- ddl-end --
+COMMENT ON INDEX public.item_equipped_idx IS E'Only one item can be equipped per slot';
+-- ddl-end --
@@ -0,0 +1,21 @@
+-- object: public.item.quantity | type: COLUMN --
+-- ALTER TABLE public.item DROP COLUMN IF EXISTS quantity CASCADE;
+ALTER TABLE public.item ADD COLUMN quantity integer NOT NULL DEFAULT 1;
+-- ddl-end --
+COMMENT ON COLUMN public.item.quantity IS E'Number of items in the stack, always 1 for items that do not stack';
+-- ddl-end --
+
+-- object: item_quantity_ck | type: CONSTRAINT --
+-- ALTER TABLE public.item DROP CONSTRAINT IF EXISTS item_quantity_ck CASCADE;
+ALTER TABLE public.item ADD CONSTRAINT item_quantity_ck CHECK (quantity > 0);
+-- ddl-end --
+
+-- object: item_player_idx | type: INDEX --
+-- DROP INDEX IF EXISTS public.item_player_idx CASCADE;
+CREATE INDEX item_player_idx ON public.item
+	USING btree
+	(
+	  player_id,
+	  key
+	);
+-- ddl-end --
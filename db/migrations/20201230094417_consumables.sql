@@ -0,0 +1,49 @@
+-- object: public.ability | type: TYPE --
+-- DROP TYPE IF EXISTS public.ability CASCADE;
+CREATE TYPE public.ability AS
+ ENUM ('strength','dexterity','constitution','intelligence','wisdom','charisma','luck');
+-- ddl-end --
+
+-- object: public.buff | type: TABLE --
+-- DROP TABLE IF EXISTS public.buff CASCADE;
+CREATE TABLE public.buff (
+	id serial NOT NULL,
+	player_id integer NOT NULL,
+	ability public.ability NOT NULL,
+	bonus smallint NOT NULL,
+	encounters smallint NOT NULL,
+	created_at timestamptz NOT NULL DEFAULT now(),
+	CONSTRAINT buff_pk PRIMARY KEY (id),
+	CONSTRAINT buff_encounters_ck CHECK (encounters > 0),
+	CONSTRAINT player_fk FOREIGN KEY (player_id)
+		REFERENCES public.player (id) ON DELETE CASCADE
+
+);
+-- ddl-end --
+COMMENT ON TABLE public.buff IS E'Temporary bonuses to attributes, e.g. from potions';
+-- ddl-end --
+COMMENT ON COLUMN public.buff.encounters IS E'Number of encounters the buff lasts for';
+-- ddl-end --
+
+-- object: buff_player_idx | type: INDEX --
+-- DROP INDEX IF EXISTS public.buff_player_idx CASCADE;
+CREATE INDEX buff_player_idx ON public.buff
+	USING btree
+	(
+	  player_id
+	);
+-- ddl-end --
+
+-- object: public.player.health | type: COLUMN --
+-- ALTER TABLE public.player DROP COLUMN IF EXISTS health CASCADE;
+ALTER TABLE public.player ADD COLUMN health integer;
+-- ddl-end --
+COMMENT ON COLUMN public.player.health IS E'Health left after the last encounter, NULL for full health';
+-- ddl-end --
+
+-- object: public.player.health_updated_at | type: COLUMN --
+-- ALTER TABLE public.player DROP COLUMN IF EXISTS health_updated_at CASCADE;
+ALTER TABLE public.player ADD COLUMN health_updated_at timestamptz;
+-- ddl-end --
+COMMENT ON COLUMN public.player.health_updated_at IS E'When the health was stored, characters rest from then on';
+-- ddl-end --
@@ -0,0 +1,52 @@
+-- object: public.player.gold | type: COLUMN --
+-- ALTER TABLE public.player DROP COLUMN IF EXISTS gold CASCADE;
+ALTER TABLE public.player ADD COLUMN gold integer NOT NULL DEFAULT 0;
+-- ddl-end --
+COMMENT ON COLUMN public.player.gold IS E'Gold balance, every change is recorded in gold_ledger';
+-- ddl-end --
+
+-- object: player_gold_ck | type: CONSTRAINT --
+-- ALTER TABLE public.player DROP CONSTRAINT IF EXISTS player_gold_ck CASCADE;
+ALTER TABLE public.player ADD CONSTRAINT player_gold_ck CHECK (gold >= 0);
+-- ddl-end --
+
+-- object: public.gold_reason | type: TYPE --
+-- DROP TYPE IF EXISTS public.gold_reason CASCADE;
+CREATE TYPE public.gold_reason AS
+ ENUM ('encounter');
+-- ddl-end --
+
+-- object: public.gold_ledger | type: TABLE --
+-- DROP TABLE IF EXISTS public.gold_ledger CASCADE;
+CREATE TABLE public.gold_ledger (
+	id serial NOT NULL,
+	player_id integer NOT NULL,
+	amount integer NOT NULL,
+	balance integer NOT NULL,
+	reason public.gold_reason NOT NULL,
+	encounter_id integer,
+	created_at timestamptz NOT NULL DEFAULT now(),
+	CONSTRAINT gold_ledger_pk PRIMARY KEY (id),
+	CONSTRAINT player_fk FOREIGN KEY (player_id)
+		REFERENCES public.player (id) ON DELETE CASCADE,
+	CONSTRAINT encounter_fk FOREIGN KEY (encounter_id)
+		REFERENCES public.encounter (id) ON DELETE SET NULL
+
+);
+-- ddl-end --
+COMMENT ON TABLE public.gold_ledger IS E'Every change of the gold of a player';
+-- ddl-end --
+COMMENT ON COLUMN public.gold_ledger.amount IS E'Gold gained, negative for gold spent';
+-- ddl-end --
+COMMENT ON COLUMN public.gold_ledger.balance IS E'Gold of the player after the change';
+-- ddl-end --
+
+-- object: gold_ledger_player_idx | type: INDEX --
+-- DROP INDEX IF EXISTS public.gold_ledger_player_idx CASCADE;
+CREATE INDEX gold_ledger_player_idx ON public.gold_ledger
+	USING btree
+	(
+	  player_id,
+	  created_at
+	);
+-- ddl-end --
@@ -0,0 +1,28 @@
+-- object: public.shop_stock | type: TABLE --
+-- DROP TABLE IF EXISTS public.shop_stock CASCADE;
+CREATE TABLE public.shop_stock (
+	channel text NOT NULL,
+	day date NOT NULL,
+	key text NOT NULL,
+	quantity integer NOT NULL,
+	CONSTRAINT shop_stock_pk PRIMARY KEY (channel,day,key),
+	CONSTRAINT shop_stock_quantity_ck CHECK (quantity >= 0)
+
+);
+-- ddl-end --
+COMMENT ON TABLE public.shop_stock IS E'What is left of the daily stock of the shop in each channel';
+-- ddl-end --
+COMMENT ON COLUMN public.shop_stock.key IS E'Key of the item in the item catalog';
+-- ddl-end --
+
+-- object: public.gold_reason | type: TYPE --
+ALTER TYPE public.gold_reason ADD VALUE IF NOT EXISTS 'purchase';
+ALTER TYPE public.gold_reason ADD VALUE IF NOT EXISTS 'sale';
+-- ddl-end --
+
+-- object: public.gold_ledger.item | type: COLUMN --
+-- ALTER TABLE public.gold_ledger DROP COLUMN IF EXISTS item CASCADE;
+ALTER TABLE public.gold_ledger ADD COLUMN item text;
+-- ddl-end --
+COMMENT ON COLUMN public.gold_ledger.item IS E'Key of the item bought or sold';
+-- ddl-end --
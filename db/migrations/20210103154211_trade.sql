@@ -0,0 +1,55 @@
+-- object: public.trade | type: TABLE --
+-- DROP TABLE IF EXISTS public.trade CASCADE;
+CREATE TABLE public.trade (
+	id serial NOT NULL,
+	from_id integer NOT NULL,
+	to_id integer NOT NULL,
+	from_gold integer NOT NULL DEFAULT 0,
+	to_gold integer NOT NULL DEFAULT 0,
+	from_accepted bool NOT NULL DEFAULT false,
+	to_accepted bool NOT NULL DEFAULT false,
+	created_at timestamptz NOT NULL DEFAULT now(),
+	expires_at timestamptz NOT NULL,
+	CONSTRAINT trade_pk PRIMARY KEY (id),
+	CONSTRAINT trade_gold_ck CHECK (from_gold >= 0 AND to_gold >= 0),
+	CONSTRAINT trade_players_ck CHECK (from_id <> to_id),
+	CONSTRAINT from_fk FOREIGN KEY (from_id)
+		REFERENCES public.player (id) ON DELETE CASCADE,
+	CONSTRAINT to_fk FOREIGN KEY (to_id)
+		REFERENCES public.player (id) ON DELETE CASCADE
+
+);
+-- ddl-end --
+COMMENT ON TABLE public.trade IS E'Pending trades between two players, deleted once done';
+-- ddl-end --
+COMMENT ON COLUMN public.trade.from_id IS E'The player who proposed the trade';
+-- ddl-end --
+COMMENT ON COLUMN public.trade.from_gold IS E'Gold the proposing player gives';
+-- ddl-end --
+COMMENT ON COLUMN public.trade.to_gold IS E'Gold the other player gives';
+-- ddl-end --
+
+-- object: public.trade_item | type: TABLE --
+-- DROP TABLE IF EXISTS public.trade_item CASCADE;
+CREATE TABLE public.trade_item (
+	trade_id integer NOT NULL,
+	player_id integer NOT NULL,
+	key text NOT NULL,
+	quantity integer NOT NULL,
+	CONSTRAINT trade_item_pk PRIMARY KEY (trade_id,player_id,key),
+	CONSTRAINT trade_item_quantity_ck CHECK (quantity > 0),
+	CONSTRAINT trade_fk FOREIGN KEY (trade_id)
+		REFERENCES public.trade (id) ON DELETE CASCADE,
+	CONSTRAINT player_fk FOREIGN KEY (player_id)
+		REFERENCES public.player (id) ON DELETE CASCADE
+
+);
+-- ddl-end --
+COMMENT ON TABLE public.trade_item IS E'Items given in a pending trade';
+-- ddl-end --
+COMMENT ON COLUMN public.trade_item.player_id IS E'The player who gives the item';
+-- ddl-end --
+
+-- object: public.gold_reason | type: TYPE --
+ALTER TYPE public.gold_reason ADD VALUE IF NOT EXISTS 'trade';
+-- ddl-end --
@@ -0,0 +1,24 @@
+-- object: public.status | type: TYPE --
+-- DROP TYPE IF EXISTS public.status CASCADE;
+CREATE TYPE public.status AS
+ ENUM ('poison','bleed','stun','regeneration','blessed','cursed');
+-- ddl-end --
+
+-- object: public.status_effect | type: TABLE --
+-- DROP TABLE IF EXISTS public.status_effect CASCADE;
+CREATE TABLE public.status_effect (
+	player_id integer NOT NULL,
+	kind public.status NOT NULL,
+	encounters smallint NOT NULL,
+	created_at timestamptz NOT NULL DEFAULT now(),
+	CONSTRAINT status_effect_pk PRIMARY KEY (player_id,kind),
+	CONSTRAINT status_effect_encounters_ck CHECK (encounters > 0),
+	CONSTRAINT player_fk FOREIGN KEY (player_id)
+		REFERENCES public.player (id) ON DELETE CASCADE
+
+);
+-- ddl-end --
+COMMENT ON TABLE public.status_effect IS E'Status effects that last across encounters';
+-- ddl-end --
+COMMENT ON COLUMN public.status_effect.encounters IS E'Number of encounters the effect lasts for';
+-- ddl-end --
@@ -1,8 +1,9 @@
+use crate::reload::Reloader;
 use anyhow::{Context, Result};
 use log::{debug, error, info, trace};
 use pest::{iterators::Pairs, Parser};
 use pest_derive::Parser;
-use std::{collections::HashMap, convert::TryInto};
+use std::{collections::HashMap, convert::TryInto, sync::Arc};
 use twitchchat::{
     messages::Commands, messages::Privmsg, runner::NotifyHandle, AsyncRunner, Status, UserConfig,
 };
@@ -17,12 +18,22 @@ pub struct Args<'a, 'b: 'a> {
 }
 
 impl Args<'_, '_> {
+    /// The Twitch id of the user who sent the message
+    ///
+    /// # Errors
+    ///
+    /// Fails if the message has no valid user id
     pub fn user_id(&self) -> Result<i32> {
         Ok(self.raw.user_id().context("missing user id")?.try_into()?)
     }
 }
 
 pub trait Command: Send + Sync {
+    /// Reply to a message that called the command
+    ///
+    /// # Errors
+    ///
+    /// Fails if the command cannot be executed, the error is logged
     fn handle(&mut self, args: Args<'_, '_>) -> Result<()>;
 }
 
@@ -38,25 +49,30 @@ where
 
 pub struct Bot {
     prefix: char,
+    // named after the `bot` command
+    #[allow(clippy::struct_field_names)]
     bot_command: Box<dyn Command>,
     commands: HashMap<String, Box<dyn Command>>,
     aliases: HashMap<String, String>,
+    reloader: Option<Arc<Reloader>>,
 }
 
 impl Bot {
+    #[must_use]
     pub fn new(prefix: char) -> Self {
         Self {
             prefix,
             bot_command: Box::new(|_: Args| unimplemented!()),
             commands: HashMap::new(),
             aliases: HashMap::new(),
+            reloader: None,
         }
     }
 
     // add this command to the bot
-    pub fn with_command<S, C>(mut self, name: S, aliases: Vec<S>, cmd: C) -> Self
+    #[must_use]
+    pub fn with_command<C>(mut self, name: &str, aliases: &[&str], cmd: C) -> Self
     where
-        S: ToString,
         C: Command + 'static,
     {
         self.commands.insert(name.to_string(), Box::new(cmd));
@@ -69,6 +85,7 @@ impl Bot {
         self
     }
 
+    #[must_use]
     pub fn with_bot_command<C>(mut self, cmd: C) -> Self
     where
         C: Command + 'static,
@@ -77,7 +94,18 @@ impl Bot {
         self
     }
 
-    // run the bot until its done
+    // watch the config file once the bot is connected
+    #[must_use]
+    pub fn with_reloader(mut self, reloader: Arc<Reloader>) -> Self {
+        self.reloader = Some(reloader);
+        self
+    }
+
+    /// Run the bot until its done
+    ///
+    /// # Errors
+    ///
+    /// Fails if the bot cannot connect to Twitch or loses the connection
     pub async fn run(
         &mut self,
         user_config: &UserConfig,
@@ -96,6 +124,12 @@ impl Bot {
             }
         }
 
+        if let Some(reloader) = &self.reloader {
+            if let Err(err) = reloader.clone().watch(runner.writer()) {
+                error!("Could not watch config file: {}", err);
+            }
+        }
+
         debug!("starting main loop");
         self.main_loop(&mut runner).await
     }
@@ -117,7 +151,7 @@ impl Bot {
                     trace!("got privmsg: {}", pm.data());
 
                     // see if its a command and do stuff with it
-                    if let Some(msg) = self.parse_command(pm.data()) {
+                    if let Some(msg) = Self::parse_command(pm.data()) {
                         if let Some(command) = self.get_command(&msg) {
                             debug!("dispatching to: {}", msg.command.escape_debug());
 
@@ -137,7 +171,7 @@ impl Bot {
                 // stop if we're stopping
                 Status::Quit | Status::Eof => break,
                 // ignore the rest
-                Status::Message(..) => continue,
+                Status::Message(..) => {}
             }
         }
 
@@ -145,16 +179,14 @@ impl Bot {
         Ok(())
     }
 
-    fn parse_command<'a>(&self, input: &'a str) -> Option<Message<'a>> {
+    fn parse_command(input: &str) -> Option<Message<'_>> {
         Message::parse(input).ok()
     }
 
     fn get_command(&mut self, message: &Message<'_>) -> Option<&mut Box<dyn Command>> {
         let command = message.command.to_ascii_lowercase();
-        if message.prefix == self.prefix || message.prefix == GLOBAL_PREFIX {
-            if command == "bot" {
-                return Some(&mut self.bot_command);
-            }
+        if (message.prefix == self.prefix || message.prefix == GLOBAL_PREFIX) && command == "bot" {
+            return Some(&mut self.bot_command);
         }
 
         if message.prefix != self.prefix {
@@ -179,8 +211,13 @@ pub struct Message<'a> {
 struct MessageParser;
 
 impl Message<'_> {
+    /// Parse a chat message as a command
+    ///
+    /// # Errors
+    ///
+    /// Fails if the message is not a command
     pub fn parse(text: &str) -> Result<Message<'_>> {
-        Self::process(MessageParser::parse(Rule::message, text.as_ref())?)
+        Self::process(MessageParser::parse(Rule::message, text)?)
     }
 
     fn process(pairs: Pairs<'_, Rule>) -> Result<Message<'_>> {
@@ -228,7 +265,7 @@ mod test_message_parser {
                 command: "bot",
                 arguments: Vec::new()
             }
-        )
+        );
     }
 
     #[test]
@@ -254,7 +291,7 @@ mod test_message_parser {
                     "@@@@@@@@@@q"
                 ]
             }
-        )
+        );
     }
 
     #[test]
@@ -0,0 +1,34 @@
+use super::Ability;
+
+/// Temporary bonuses to attributes, e.g. from potions
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq)]
+pub struct Buffs([i16; 7]);
+
+impl Buffs {
+    #[must_use]
+    pub fn get(&self, ability: Ability) -> i16 {
+        self.0[ability as usize]
+    }
+
+    /// Add `bonus` to `ability`. Bonuses of the same ability stack.
+    pub fn add(&mut self, ability: Ability, bonus: i16) {
+        self.0[ability as usize] = self.0[ability as usize].saturating_add(bonus);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn stack() {
+        let mut buffs = Buffs::default();
+        buffs.add(Ability::Strength, 2);
+        buffs.add(Ability::Strength, 1);
+        buffs.add(Ability::Luck, -1);
+
+        assert_eq!(buffs.get(Ability::Strength), 3);
+        assert_eq!(buffs.get(Ability::Luck), -1);
+        assert_eq!(buffs.get(Ability::Dexterity), 0);
+    }
+}
@@ -1,12 +1,168 @@
-#[derive(sqlx::Type, Debug)]
+use super::Ability;
+use crate::{item::Item, Die};
+use anyhow::{bail, Result};
+use std::{fmt, str::FromStr};
+
+#[derive(sqlx::Type, Debug, Clone, Copy, PartialEq, Eq, Default)]
 #[sqlx(rename_all = "lowercase")]
 #[sqlx(rename = "class")]
 pub enum Class {
+    #[default]
     Fighter,
+    Rogue,
+    Wizard,
+    Cleric,
+    Ranger,
+}
+
+/// A passive ability of a class that changes how fights are resolved
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ClassFeature {
+    /// Attack twice in the first round
+    ActionSurge,
+    /// Extra damage on the first hit of a fight
+    SneakAttack,
+    /// A ward that absorbs the first points of damage
+    ArcaneWard,
+    /// Heal once per fight when badly hurt
+    HealingPrayer,
+    /// Extra damage on every hit
+    HuntersMark,
 }
 
-impl Default for Class {
-    fn default() -> Self {
-        Self::Fighter
+impl Class {
+    pub const ALL: [Class; 5] = [
+        Self::Fighter,
+        Self::Rogue,
+        Self::Wizard,
+        Self::Cleric,
+        Self::Ranger,
+    ];
+
+    /// The attribute used for attacks and damage
+    #[must_use]
+    pub fn primary_ability(self) -> Ability {
+        match self {
+            Self::Fighter => Ability::Strength,
+            Self::Rogue | Self::Ranger => Ability::Dexterity,
+            Self::Wizard => Ability::Intelligence,
+            Self::Cleric => Ability::Wisdom,
+        }
+    }
+
+    /// The die that decides the health of the class
+    #[must_use]
+    pub fn hit_die(self) -> Die {
+        match self {
+            Self::Fighter | Self::Ranger => Die::D10,
+            Self::Rogue | Self::Cleric => Die::D8,
+            Self::Wizard => Die::D6,
+        }
+    }
+
+    /// The items a new character of this class starts with, equipped
+    #[must_use]
+    pub fn starting_equipment(self) -> Vec<&'static Item> {
+        let keys: &[&str] = match self {
+            Self::Fighter => &["longsword", "chain_mail", "shield"],
+            Self::Rogue => &["dagger", "leather_armor"],
+            Self::Wizard => &["quarterstaff", "spellbook"],
+            Self::Cleric => &["mace", "scale_mail", "shield"],
+            Self::Ranger => &["longbow", "leather_armor"],
+        };
+
+        keys.iter().filter_map(|key| Item::by_key(key)).collect()
+    }
+
+    #[must_use]
+    pub fn feature(self) -> ClassFeature {
+        match self {
+            Self::Fighter => ClassFeature::ActionSurge,
+            Self::Rogue => ClassFeature::SneakAttack,
+            Self::Wizard => ClassFeature::ArcaneWard,
+            Self::Cleric => ClassFeature::HealingPrayer,
+            Self::Ranger => ClassFeature::HuntersMark,
+        }
+    }
+}
+
+impl FromStr for Class {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match Self::ALL
+            .iter()
+            .find(|class| class.to_string().eq_ignore_ascii_case(s))
+        {
+            Some(class) => Ok(*class),
+            None => bail!("`{}` is not a class", s),
+        }
+    }
+}
+
+impl fmt::Display for Class {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Fighter => "fighter",
+            Self::Rogue => "rogue",
+            Self::Wizard => "wizard",
+            Self::Cleric => "cleric",
+            Self::Ranger => "ranger",
+        })
+    }
+}
+
+impl ClassFeature {
+    #[must_use]
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::ActionSurge => "Action Surge",
+            Self::SneakAttack => "Sneak Attack",
+            Self::ArcaneWard => "Arcane Ward",
+            Self::HealingPrayer => "Healing Prayer",
+            Self::HuntersMark => "Hunter's Mark",
+        }
+    }
+
+    /// A short explanation for chat
+    #[must_use]
+    pub fn description(self) -> &'static str {
+        match self {
+            Self::ActionSurge => "attack twice in the first round",
+            Self::SneakAttack => "+2d6 damage on the first hit",
+            Self::ArcaneWard => "absorb the first 5 damage",
+            Self::HealingPrayer => "heal 1d8 once when below half health",
+            Self::HuntersMark => "+1d4 damage on every hit",
+        }
+    }
+}
+
+impl fmt::Display for ClassFeature {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} ({})", self.name(), self.description())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse() {
+        for class in &Class::ALL {
+            assert_eq!(class.to_string().parse::<Class>().unwrap(), *class);
+        }
+
+        assert_eq!("Wizard".parse::<Class>().unwrap(), Class::Wizard);
+        assert!("bard".parse::<Class>().is_err());
+    }
+
+    #[test]
+    fn starting_equipment() {
+        assert_eq!(Class::Fighter.starting_equipment().len(), 3);
+        assert_eq!(Class::Rogue.starting_equipment().len(), 2);
+        assert_eq!(Class::Wizard.starting_equipment().len(), 2);
+        assert_eq!(Class::Cleric.starting_equipment().len(), 3);
+        assert_eq!(Class::Ranger.starting_equipment().len(), 2);
     }
 }
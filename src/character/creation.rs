@@ -0,0 +1,225 @@
+use super::{Ability, Attribute, CharacterStats};
+use crate::{DicePool, Die, PoolRoll};
+use anyhow::{bail, ensure, Result};
+use rand::Rng;
+use std::{convert::TryFrom, fmt, str::FromStr};
+
+/// The scores handed out by [`CreationMethod::StandardArray`]
+pub const STANDARD_ARRAY: [i16; 6] = [15, 14, 13, 12, 10, 8];
+
+/// The points to spend with [`CreationMethod::PointBuy`]
+pub const POINT_BUY_BUDGET: u16 = 27;
+
+/// The lowest and highest score that can be bought
+pub const POINT_BUY_RANGE: (i16, i16) = (8, 15);
+
+/// The abilities chosen on creation in the order scores are assigned. Luck
+/// always starts at `0`.
+pub const CREATION_ABILITIES: [Ability; 6] = [
+    Ability::Strength,
+    Ability::Dexterity,
+    Ability::Constitution,
+    Ability::Intelligence,
+    Ability::Wisdom,
+    Ability::Charisma,
+];
+
+/// How the attribute scores of a new character are chosen
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum CreationMethod {
+    /// Roll 4d6 and drop the lowest die for every score
+    #[default]
+    Rolled,
+    /// Assign the scores of [`STANDARD_ARRAY`]
+    StandardArray,
+    /// Buy every score with a budget of [`POINT_BUY_BUDGET`] points
+    PointBuy,
+}
+
+impl FromStr for CreationMethod {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        Ok(match s {
+            "rolled" => Self::Rolled,
+            "standard_array" => Self::StandardArray,
+            "point_buy" => Self::PointBuy,
+            _ => bail!("`{}` is not a creation method", s),
+        })
+    }
+}
+
+impl fmt::Display for CreationMethod {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Rolled => "rolled",
+            Self::StandardArray => "standard_array",
+            Self::PointBuy => "point_buy",
+        })
+    }
+}
+
+/// Roll a score for every ability with 4d6, dropping the lowest die
+pub fn roll_scores<R>(rng: &mut R) -> Vec<PoolRoll>
+where
+    R: Rng + ?Sized,
+{
+    let pool = DicePool::new(4, Die::D6).keep_highest(3);
+
+    CREATION_ABILITIES.iter().map(|_| pool.roll(rng)).collect()
+}
+
+/// The points needed to buy a score
+///
+/// # Errors
+///
+/// Fails if `score` is outside of [`POINT_BUY_RANGE`]
+pub fn point_buy_cost(score: i16) -> Result<u16> {
+    let (min, max) = POINT_BUY_RANGE;
+    ensure!(
+        (min..=max).contains(&score),
+        "Scores must be between {} and {}",
+        min,
+        max
+    );
+
+    // every point above 13 costs double
+    Ok(match u16::try_from(score - min)? {
+        n @ 0..=5 => n,
+        n => 5 + 2 * (n - 5),
+    })
+}
+
+/// The stats of a new character with `scores` in the order of
+/// [`CREATION_ABILITIES`], which must be a permutation of `available`
+///
+/// # Errors
+///
+/// Fails if `scores` does not use every available score exactly once
+pub fn assign(available: &[i16], scores: &[i16]) -> Result<CharacterStats> {
+    let mut sorted_available = available.to_vec();
+    let mut sorted_scores = scores.to_vec();
+    sorted_available.sort_unstable();
+    sorted_scores.sort_unstable();
+
+    ensure!(
+        sorted_available == sorted_scores,
+        "You have to use each of {} exactly once",
+        join(available)
+    );
+
+    from_scores(scores)
+}
+
+/// The stats of a new character with bought `scores` in the order of
+/// [`CREATION_ABILITIES`]
+///
+/// # Errors
+///
+/// Fails if a score cannot be bought or they cost more than
+/// [`POINT_BUY_BUDGET`]
+pub fn point_buy(scores: &[i16]) -> Result<CharacterStats> {
+    let mut cost = 0;
+    for &score in scores {
+        cost += point_buy_cost(score)?;
+    }
+
+    ensure!(
+        cost <= POINT_BUY_BUDGET,
+        "These scores cost {} points but you only have {}",
+        cost,
+        POINT_BUY_BUDGET
+    );
+
+    from_scores(scores)
+}
+
+fn from_scores(scores: &[i16]) -> Result<CharacterStats> {
+    ensure!(
+        scores.len() == CREATION_ABILITIES.len(),
+        "You need {} scores, one for each of strength, dexterity, constitution, intelligence, wisdom and charisma",
+        CREATION_ABILITIES.len()
+    );
+
+    let mut stats = CharacterStats::default();
+    for (&ability, &score) in CREATION_ABILITIES.iter().zip(scores) {
+        *stats.attribute_mut(ability) = Attribute::from_score(score);
+    }
+
+    Ok(stats)
+}
+
+/// Scores separated by commas
+pub fn join(scores: &[i16]) -> String {
+    scores
+        .iter()
+        .map(ToString::to_string)
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dice::rng::test::rng;
+
+    #[test]
+    fn rolled() {
+        let rolls = roll_scores(&mut rng());
+
+        let totals: Vec<_> = rolls.iter().map(|roll| roll.total).collect();
+        assert_eq!(totals, [14, 15, 14, 9, 11, 10]);
+        for roll in rolls {
+            assert_eq!(roll.kept.iter().filter(|&&kept| kept).count(), 3);
+        }
+    }
+
+    #[test]
+    fn standard_array() {
+        let stats = assign(&STANDARD_ARRAY, &[8, 10, 12, 13, 14, 15]).unwrap();
+
+        assert_eq!(stats.strength.score(), 8);
+        assert_eq!(*stats.strength, -2);
+        assert_eq!(stats.charisma.score(), 15);
+        assert_eq!(*stats.luck, 0);
+
+        assert!(assign(&STANDARD_ARRAY, &[15, 15, 13, 12, 10, 8]).is_err());
+        assert!(assign(&STANDARD_ARRAY, &[15, 14, 13, 12, 10]).is_err());
+    }
+
+    #[test]
+    fn cost() {
+        assert_eq!(point_buy_cost(8).unwrap(), 0);
+        assert_eq!(point_buy_cost(13).unwrap(), 5);
+        assert_eq!(point_buy_cost(14).unwrap(), 7);
+        assert_eq!(point_buy_cost(15).unwrap(), 9);
+        assert!(point_buy_cost(7).is_err());
+        assert!(point_buy_cost(16).is_err());
+    }
+
+    #[test]
+    fn buy() {
+        // the standard array costs exactly the budget
+        assert!(point_buy(&STANDARD_ARRAY).is_ok());
+        assert!(point_buy(&[13, 13, 13, 12, 12, 12]).is_ok());
+        assert!(point_buy(&[15, 15, 15, 8, 8, 8]).is_ok());
+        assert!(point_buy(&[15, 15, 15, 9, 8, 8]).is_err());
+        assert!(point_buy(&[8, 8, 8, 8, 8]).is_err());
+    }
+
+    #[test]
+    fn method() {
+        for method in &[
+            CreationMethod::Rolled,
+            CreationMethod::StandardArray,
+            CreationMethod::PointBuy,
+        ] {
+            assert_eq!(
+                method.to_string().parse::<CreationMethod>().unwrap(),
+                *method
+            );
+        }
+
+        assert!("4d6".parse::<CreationMethod>().is_err());
+    }
+}
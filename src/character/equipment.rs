@@ -0,0 +1,79 @@
+use super::Ability;
+use crate::item::{Item, Slot};
+
+/// The items a character wears, at most one per slot
+#[derive(Debug, Copy, Clone, Default)]
+pub struct Equipment {
+    pub weapon: Option<&'static Item>,
+    pub armor: Option<&'static Item>,
+    pub shield: Option<&'static Item>,
+    pub accessory: Option<&'static Item>,
+}
+
+impl Equipment {
+    #[must_use]
+    pub fn get(&self, slot: Slot) -> Option<&'static Item> {
+        match slot {
+            Slot::Weapon => self.weapon,
+            Slot::Armor => self.armor,
+            Slot::Shield => self.shield,
+            Slot::Accessory => self.accessory,
+        }
+    }
+
+    /// Put `item` into its slot. Items without a slot are ignored.
+    pub fn equip(&mut self, item: &'static Item) {
+        match item.slot {
+            Some(Slot::Weapon) => self.weapon = Some(item),
+            Some(Slot::Armor) => self.armor = Some(item),
+            Some(Slot::Shield) => self.shield = Some(item),
+            Some(Slot::Accessory) => self.accessory = Some(item),
+            None => {}
+        }
+    }
+
+    /// Every equipped item
+    pub fn items(&self) -> impl Iterator<Item = &'static Item> {
+        vec![self.weapon, self.armor, self.shield, self.accessory]
+            .into_iter()
+            .flatten()
+    }
+
+    #[must_use]
+    pub fn attack_bonus(&self) -> i16 {
+        self.items().map(|item| item.attack_bonus).sum()
+    }
+
+    #[must_use]
+    pub fn armor_class(&self) -> i16 {
+        self.items().map(|item| item.armor_class).sum()
+    }
+
+    #[must_use]
+    pub fn attribute_bonus(&self, ability: Ability) -> i16 {
+        self.items().map(|item| item.attribute_bonus(ability)).sum()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bonuses() {
+        let mut equipment = Equipment::default();
+        for key in &["fine_rapier", "chain_mail", "shield", "amulet_of_health"] {
+            equipment.equip(Item::by_key(key).unwrap());
+        }
+
+        assert_eq!(equipment.items().count(), 4);
+        assert_eq!(equipment.attack_bonus(), 1);
+        assert_eq!(equipment.armor_class(), 6);
+        assert_eq!(equipment.attribute_bonus(Ability::Constitution), 2);
+        assert_eq!(equipment.attribute_bonus(Ability::Strength), 0);
+
+        equipment.equip(Item::by_key("dagger").unwrap());
+        assert_eq!(equipment.get(Slot::Weapon).unwrap().key, "dagger");
+        assert_eq!(equipment.attack_bonus(), 0);
+    }
+}
@@ -0,0 +1,169 @@
+use super::{Ability, CharacterStats};
+use anyhow::{ensure, Result};
+use std::{convert::TryFrom, fmt};
+
+/// The highest level a character can reach
+pub const MAX_LEVEL: u8 = 20;
+
+/// The attribute points granted for every level gained
+pub const ATTRIBUTE_POINTS_PER_LEVEL: u16 = 1;
+
+/// Training cannot raise a score above this
+pub const MAX_TRAINED_SCORE: i16 = 20;
+
+/// The experience needed to reach each level, starting with level 1
+const EXPERIENCE_CURVE: [u32; MAX_LEVEL as usize] = [
+    0, 300, 900, 2_700, 6_500, 14_000, 23_000, 34_000, 48_000, 64_000, 85_000, 100_000, 120_000,
+    140_000, 165_000, 195_000, 225_000, 265_000, 305_000, 355_000,
+];
+
+/// The level of a character, from 1 to [`MAX_LEVEL`]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Level(u8);
+
+impl Level {
+    /// The level reached with `experience` points
+    #[must_use]
+    pub fn from_experience(experience: u32) -> Self {
+        let reached = EXPERIENCE_CURVE
+            .iter()
+            .take_while(|&&needed| needed <= experience)
+            .count();
+
+        Self(u8::try_from(reached).unwrap_or(MAX_LEVEL))
+    }
+
+    #[must_use]
+    pub fn get(self) -> u8 {
+        self.0
+    }
+
+    /// The experience needed to reach this level
+    #[must_use]
+    pub fn experience(self) -> u32 {
+        EXPERIENCE_CURVE[usize::from(self.0 - 1)]
+    }
+
+    /// The experience needed for the next level, if there is one
+    #[must_use]
+    pub fn next_experience(self) -> Option<u32> {
+        EXPERIENCE_CURVE.get(usize::from(self.0)).copied()
+    }
+
+    /// The levels gained since level 1
+    #[must_use]
+    pub fn gained(self) -> u8 {
+        self.0 - 1
+    }
+
+    /// The bonus to attack rolls and saving throws: +1 every four levels
+    /// after the first
+    #[must_use]
+    pub fn bonus(self) -> i16 {
+        i16::from(self.gained() / 4)
+    }
+}
+
+/// Spend `points` of the `available` attribute points to raise `ability`
+///
+/// # Errors
+///
+/// Fails if `ability` is luck, the points are not available or the score
+/// would exceed [`MAX_TRAINED_SCORE`]
+pub fn train(
+    stats: &mut CharacterStats,
+    ability: Ability,
+    points: u16,
+    available: u16,
+) -> Result<()> {
+    ensure!(ability != Ability::Luck, "Luck cannot be trained");
+    ensure!(points > 0, "Train at least one point");
+    ensure!(
+        points <= available,
+        "You only have {} attribute points",
+        available
+    );
+
+    let attribute = stats.attribute_mut(ability);
+    let score = i32::from(points) + attribute.score();
+    ensure!(
+        score <= MAX_TRAINED_SCORE.into(),
+        "Your {} can only be trained up to {}",
+        ability,
+        MAX_TRAINED_SCORE
+    );
+
+    **attribute += i16::try_from(points)?;
+
+    Ok(())
+}
+
+impl Default for Level {
+    fn default() -> Self {
+        Self(1)
+    }
+}
+
+impl From<i16> for Level {
+    fn from(level: i16) -> Self {
+        let level = level.clamp(1, i16::from(MAX_LEVEL));
+
+        Self(u8::try_from(level).unwrap_or(MAX_LEVEL))
+    }
+}
+
+impl fmt::Display for Level {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn curve() {
+        assert_eq!(Level::from_experience(0).get(), 1);
+        assert_eq!(Level::from_experience(299).get(), 1);
+        assert_eq!(Level::from_experience(300).get(), 2);
+        assert_eq!(Level::from_experience(6_500).get(), 5);
+        assert_eq!(Level::from_experience(u32::MAX).get(), MAX_LEVEL);
+
+        assert_eq!(Level::from(2).experience(), 300);
+        assert_eq!(Level::from(2).next_experience(), Some(900));
+        assert_eq!(Level::from(20).next_experience(), None);
+    }
+
+    #[test]
+    fn clamp() {
+        assert_eq!(Level::from(0).get(), 1);
+        assert_eq!(Level::from(-3).get(), 1);
+        assert_eq!(Level::from(99).get(), MAX_LEVEL);
+    }
+
+    #[test]
+    fn training() {
+        let mut stats = CharacterStats::default();
+
+        train(&mut stats, Ability::Strength, 2, 3).unwrap();
+        assert_eq!(stats.strength.score(), 12);
+
+        assert!(train(&mut stats, Ability::Strength, 4, 3).is_err());
+        assert!(train(&mut stats, Ability::Strength, 0, 3).is_err());
+        assert!(train(&mut stats, Ability::Luck, 1, 3).is_err());
+
+        *stats.dexterity = 9;
+        assert!(train(&mut stats, Ability::Dexterity, 2, 3).is_err());
+        train(&mut stats, Ability::Dexterity, 1, 3).unwrap();
+        assert_eq!(stats.dexterity.score(), MAX_TRAINED_SCORE.into());
+    }
+
+    #[test]
+    fn bonus() {
+        assert_eq!(Level::from(1).bonus(), 0);
+        assert_eq!(Level::from(4).bonus(), 0);
+        assert_eq!(Level::from(5).bonus(), 1);
+        assert_eq!(Level::from(20).bonus(), 4);
+    }
+}
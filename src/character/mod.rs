@@ -1,5 +1,21 @@
+mod buffs;
 mod class;
+pub mod creation;
+mod equipment;
+pub mod level;
+mod power;
 mod race;
+mod spell;
 mod stats;
 
-pub use self::{class::Class, race::Race, stats::CharacterStats};
+pub use self::{
+    buffs::Buffs,
+    class::{Class, ClassFeature},
+    creation::CreationMethod,
+    equipment::Equipment,
+    level::{Level, MAX_LEVEL},
+    power::{Power, PowerEffect, Strike, POWERS},
+    race::{Race, RacialTrait},
+    spell::{Spell, SPELLS},
+    stats::{Ability, Attribute, CharacterStats},
+};
@@ -0,0 +1,198 @@
+use super::{Class, Level};
+use crate::{fight::StatusKind, DicePool, Die};
+use std::fmt;
+
+/// An active ability of a class, used in fights whenever it is ready
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Power {
+    pub name: &'static str,
+    pub class: Class,
+    /// The level that unlocks the power
+    pub level: u8,
+    /// The turns until the power can be used again
+    pub cooldown: u32,
+    pub effect: PowerEffect,
+    /// A short explanation for chat
+    pub description: &'static str,
+}
+
+/// What a power does when it is used
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PowerEffect {
+    /// Attack with modified rolls
+    Strike(Strike),
+    /// Damage the opponent without an attack roll
+    Blast(DicePool, i16),
+    /// Heal the user when below half health. The primary attribute modifier
+    /// is added.
+    Heal(DicePool),
+    /// Start a status effect on the user for some turns
+    Status(StatusKind, u32),
+}
+
+/// Changes to the regular attacks of a combatant
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Strike {
+    pub to_hit: i16,
+    pub advantage: bool,
+    pub extra_damage: Option<DicePool>,
+    /// A status every hit inflicts, with the turns it lasts
+    pub inflicts: Option<(StatusKind, u32)>,
+    /// The number of attacks
+    pub attacks: u8,
+}
+
+const fn strike() -> Strike {
+    Strike {
+        to_hit: 0,
+        advantage: false,
+        extra_damage: None,
+        inflicts: None,
+        attacks: 1,
+    }
+}
+
+/// Every power of every class, by level
+pub const POWERS: [Power; 10] = [
+    Power {
+        name: "Power Attack",
+        class: Class::Fighter,
+        level: 1,
+        cooldown: 3,
+        effect: PowerEffect::Strike(Strike {
+            to_hit: -2,
+            extra_damage: Some(DicePool::new(1, Die::D8)),
+            ..strike()
+        }),
+        description: "attack with -2 to hit and +1d8 damage",
+    },
+    Power {
+        name: "Second Wind",
+        class: Class::Fighter,
+        level: 3,
+        cooldown: 10,
+        effect: PowerEffect::Heal(DicePool::new(1, Die::D10)),
+        description: "heal 1d10 when below half health",
+    },
+    Power {
+        name: "Poisoned Blade",
+        class: Class::Rogue,
+        level: 1,
+        cooldown: 4,
+        effect: PowerEffect::Strike(Strike {
+            inflicts: Some((StatusKind::Poison, 3)),
+            ..strike()
+        }),
+        description: "attack that poisons for 3 turns",
+    },
+    Power {
+        name: "Shadow Strike",
+        class: Class::Rogue,
+        level: 3,
+        cooldown: 3,
+        effect: PowerEffect::Strike(Strike {
+            advantage: true,
+            extra_damage: Some(DicePool::new(1, Die::D6)),
+            ..strike()
+        }),
+        description: "attack with advantage and +1d6 damage",
+    },
+    Power {
+        name: "Magic Missile",
+        class: Class::Wizard,
+        level: 1,
+        cooldown: 3,
+        effect: PowerEffect::Blast(DicePool::new(2, Die::D4), 2),
+        description: "2d4+2 damage that always hits",
+    },
+    Power {
+        name: "Fireball",
+        class: Class::Wizard,
+        level: 3,
+        cooldown: 5,
+        effect: PowerEffect::Blast(DicePool::new(4, Die::D6), 0),
+        description: "4d6 damage that always hits",
+    },
+    Power {
+        name: "Bless",
+        class: Class::Cleric,
+        level: 1,
+        cooldown: 6,
+        effect: PowerEffect::Status(StatusKind::Blessed, 3),
+        description: "blessed for 3 turns",
+    },
+    Power {
+        name: "Cure Wounds",
+        class: Class::Cleric,
+        level: 3,
+        cooldown: 5,
+        effect: PowerEffect::Heal(DicePool::new(2, Die::D8)),
+        description: "heal 2d8 when below half health",
+    },
+    Power {
+        name: "Ensnaring Strike",
+        class: Class::Ranger,
+        level: 1,
+        cooldown: 4,
+        effect: PowerEffect::Strike(Strike {
+            inflicts: Some((StatusKind::Stun, 1)),
+            ..strike()
+        }),
+        description: "attack that stuns for 1 turn",
+    },
+    Power {
+        name: "Volley",
+        class: Class::Ranger,
+        level: 3,
+        cooldown: 4,
+        effect: PowerEffect::Strike(Strike {
+            attacks: 2,
+            ..strike()
+        }),
+        description: "attack twice",
+    },
+];
+
+impl Class {
+    /// Every power of the class, by level
+    pub fn powers(self) -> impl Iterator<Item = &'static Power> {
+        POWERS.iter().filter(move |power| power.class == self)
+    }
+
+    /// The powers unlocked at `level`
+    #[must_use]
+    pub fn unlocked_powers(self, level: Level) -> Vec<&'static Power> {
+        self.powers()
+            .filter(|power| power.level <= level.get())
+            .collect()
+    }
+}
+
+impl fmt::Display for Power {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} ({}, every {} turns)",
+            self.name, self.description, self.cooldown
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unlocked() {
+        for class in &Class::ALL {
+            assert_eq!(class.unlocked_powers(Level::default()).len(), 1);
+            assert_eq!(class.unlocked_powers(Level::from_experience(900)).len(), 2);
+            assert!(class.powers().all(|power| power.cooldown > 0));
+        }
+
+        assert_eq!(
+            Class::Wizard.unlocked_powers(Level::default())[0].to_string(),
+            "Magic Missile (2d4+2 damage that always hits, every 3 turns)"
+        );
+    }
+}
@@ -1,12 +1,200 @@
-#[derive(sqlx::Type, Debug)]
+use super::{Ability, CharacterStats};
+use anyhow::{bail, Result};
+use std::{fmt, str::FromStr};
+
+#[derive(sqlx::Type, Debug, Clone, Copy, PartialEq, Eq, Default)]
 #[sqlx(rename_all = "lowercase")]
 #[sqlx(rename = "race")]
 pub enum Race {
+    #[default]
     Human,
+    Dwarf,
+    Elf,
+    Halfling,
+    Orc,
+    Gnome,
+}
+
+/// A passive ability every member of a race has
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RacialTrait {
+    /// +1 to every saving throw
+    Resourceful,
+    /// Extra maximum health
+    DwarvenToughness,
+    /// Advantage on initiative rolls
+    KeenSenses,
+    /// Natural 1s on attack rolls are rolled again
+    Lucky,
+    /// Critical hits roll one more damage die
+    SavageAttacks,
+    /// Advantage on intelligence, wisdom and charisma saving throws
+    GnomeCunning,
+}
+
+impl Race {
+    pub const ALL: [Race; 6] = [
+        Self::Human,
+        Self::Dwarf,
+        Self::Elf,
+        Self::Halfling,
+        Self::Orc,
+        Self::Gnome,
+    ];
+
+    /// The attribute bonuses a new character of this race gets
+    #[must_use]
+    pub fn bonuses(self) -> &'static [(Ability, i16)] {
+        match self {
+            Self::Human => &[
+                (Ability::Strength, 1),
+                (Ability::Dexterity, 1),
+                (Ability::Constitution, 1),
+                (Ability::Intelligence, 1),
+                (Ability::Wisdom, 1),
+                (Ability::Charisma, 1),
+            ],
+            Self::Dwarf => &[(Ability::Constitution, 2), (Ability::Wisdom, 1)],
+            Self::Elf => &[(Ability::Dexterity, 2), (Ability::Intelligence, 1)],
+            Self::Halfling => &[(Ability::Dexterity, 2), (Ability::Charisma, 1)],
+            Self::Orc => &[(Ability::Strength, 2), (Ability::Constitution, 1)],
+            Self::Gnome => &[(Ability::Intelligence, 2), (Ability::Constitution, 1)],
+        }
+    }
+
+    /// Make `stats` a member of this race and add the racial bonuses
+    pub fn apply(self, stats: &mut CharacterStats) {
+        stats.race = self;
+        for &(ability, bonus) in self.bonuses() {
+            let attribute = stats.attribute_mut(ability);
+            **attribute = attribute.saturating_add(bonus);
+        }
+    }
+
+    /// The indefinite article for the name of the race, `a` or `an`
+    #[must_use]
+    pub fn article(self) -> &'static str {
+        match self.to_string().chars().next() {
+            Some('a' | 'e' | 'i' | 'o' | 'u') => "an",
+            _ => "a",
+        }
+    }
+
+    #[must_use]
+    pub fn racial_trait(self) -> RacialTrait {
+        match self {
+            Self::Human => RacialTrait::Resourceful,
+            Self::Dwarf => RacialTrait::DwarvenToughness,
+            Self::Elf => RacialTrait::KeenSenses,
+            Self::Halfling => RacialTrait::Lucky,
+            Self::Orc => RacialTrait::SavageAttacks,
+            Self::Gnome => RacialTrait::GnomeCunning,
+        }
+    }
+}
+
+impl FromStr for Race {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match Self::ALL
+            .iter()
+            .find(|race| race.to_string().eq_ignore_ascii_case(s))
+        {
+            Some(race) => Ok(*race),
+            None => bail!("`{}` is not a race", s),
+        }
+    }
+}
+
+impl fmt::Display for Race {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Human => "human",
+            Self::Dwarf => "dwarf",
+            Self::Elf => "elf",
+            Self::Halfling => "halfling",
+            Self::Orc => "orc",
+            Self::Gnome => "gnome",
+        })
+    }
+}
+
+impl RacialTrait {
+    #[must_use]
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Resourceful => "Resourceful",
+            Self::DwarvenToughness => "Dwarven Toughness",
+            Self::KeenSenses => "Keen Senses",
+            Self::Lucky => "Lucky",
+            Self::SavageAttacks => "Savage Attacks",
+            Self::GnomeCunning => "Gnome Cunning",
+        }
+    }
+
+    /// A short explanation for chat
+    #[must_use]
+    pub fn description(self) -> &'static str {
+        match self {
+            Self::Resourceful => "+1 to every saving throw",
+            Self::DwarvenToughness => "+2 maximum health",
+            Self::KeenSenses => "advantage on initiative",
+            Self::Lucky => "reroll natural 1s on attacks",
+            Self::SavageAttacks => "one more damage die on critical hits",
+            Self::GnomeCunning => "advantage on intelligence, wisdom and charisma saves",
+        }
+    }
 }
 
-impl Default for Race {
-    fn default() -> Self {
-        Self::Human
+impl fmt::Display for RacialTrait {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} ({})", self.name(), self.description())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse() {
+        for race in &Race::ALL {
+            assert_eq!(race.to_string().parse::<Race>().unwrap(), *race);
+        }
+
+        assert_eq!("Elf".parse::<Race>().unwrap(), Race::Elf);
+        assert!("dragon".parse::<Race>().is_err());
+    }
+
+    #[test]
+    fn article() {
+        let articles: Vec<_> = Race::ALL.iter().map(|race| race.article()).collect();
+
+        assert_eq!(articles, ["a", "a", "an", "a", "an", "a"]);
+    }
+
+    #[test]
+    fn bonuses() {
+        // humans get +1 on everything, every other race gets three points
+        for race in &Race::ALL {
+            let total: i16 = race.bonuses().iter().map(|(_, bonus)| bonus).sum();
+            let expected = match race {
+                Race::Human => 6,
+                _ => 3,
+            };
+            assert_eq!(total, expected, "{}", race);
+        }
+    }
+
+    #[test]
+    fn apply() {
+        let mut stats = CharacterStats::default();
+        Race::Orc.apply(&mut stats);
+
+        assert_eq!(stats.race, Race::Orc);
+        assert_eq!(*stats.strength, 2);
+        assert_eq!(*stats.constitution, 1);
+        assert_eq!(*stats.dexterity, 0);
     }
 }
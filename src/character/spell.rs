@@ -0,0 +1,127 @@
+use super::{Ability, Class, Level};
+use crate::{fight::StatusKind, DicePool, Die};
+use std::fmt;
+
+/// A spell of a caster class, paid for with mana. The target rolls a saving
+/// throw against the spell save DC of the caster.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Spell {
+    pub name: &'static str,
+    pub class: Class,
+    /// The level that unlocks the spell
+    pub level: u8,
+    /// The mana it costs to cast the spell
+    pub mana: u32,
+    /// The saving throw of the target
+    pub save: Ability,
+    pub damage: Option<DicePool>,
+    /// Whether a successful save halves the damage instead of preventing it
+    pub half_on_save: bool,
+    /// A status a failed save inflicts, with the turns it lasts
+    pub inflicts: Option<(StatusKind, u32)>,
+    /// A short explanation for chat
+    pub description: &'static str,
+}
+
+/// Every spell of every class, by level
+pub const SPELLS: [Spell; 4] = [
+    Spell {
+        name: "Burning Hands",
+        class: Class::Wizard,
+        level: 1,
+        mana: 2,
+        save: Ability::Dexterity,
+        damage: Some(DicePool::new(3, Die::D6)),
+        half_on_save: true,
+        inflicts: None,
+        description: "3d6 damage, half on a dexterity save",
+    },
+    Spell {
+        name: "Hold Person",
+        class: Class::Wizard,
+        level: 3,
+        mana: 3,
+        save: Ability::Wisdom,
+        damage: None,
+        half_on_save: false,
+        inflicts: Some((StatusKind::Stun, 1)),
+        description: "stunned for 1 turn unless it makes a wisdom save",
+    },
+    Spell {
+        name: "Sacred Flame",
+        class: Class::Cleric,
+        level: 1,
+        mana: 1,
+        save: Ability::Dexterity,
+        damage: Some(DicePool::new(1, Die::D8)),
+        half_on_save: false,
+        inflicts: None,
+        description: "1d8 damage unless it makes a dexterity save",
+    },
+    Spell {
+        name: "Bane",
+        class: Class::Cleric,
+        level: 3,
+        mana: 2,
+        save: Ability::Charisma,
+        damage: None,
+        half_on_save: false,
+        inflicts: Some((StatusKind::Cursed, 3)),
+        description: "cursed for 3 turns unless it makes a charisma save",
+    },
+];
+
+impl Class {
+    /// The attribute that decides the mana and spell save DC, `None` if the
+    /// class cannot cast spells
+    #[must_use]
+    pub fn spellcasting_ability(self) -> Option<Ability> {
+        match self {
+            Self::Wizard => Some(Ability::Intelligence),
+            Self::Cleric => Some(Ability::Wisdom),
+            _ => None,
+        }
+    }
+
+    /// Every spell of the class, by level
+    pub fn spells(self) -> impl Iterator<Item = &'static Spell> {
+        SPELLS.iter().filter(move |spell| spell.class == self)
+    }
+
+    /// The spells unlocked at `level`
+    #[must_use]
+    pub fn unlocked_spells(self, level: Level) -> Vec<&'static Spell> {
+        self.spells()
+            .filter(|spell| spell.level <= level.get())
+            .collect()
+    }
+}
+
+impl fmt::Display for Spell {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} ({}, {} mana)",
+            self.name, self.description, self.mana
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn casters() {
+        for class in &Class::ALL {
+            let spells = class.unlocked_spells(Level::from_experience(900));
+            assert_eq!(class.spellcasting_ability().is_some(), !spells.is_empty());
+            assert!(spells.iter().all(|spell| spell.mana > 0));
+        }
+
+        assert_eq!(
+            Class::Wizard.unlocked_spells(Level::default())[0].to_string(),
+            "Burning Hands (3d6 damage, half on a dexterity save, 2 mana)"
+        );
+    }
+}
@@ -1,12 +1,43 @@
-use std::ops::{Deref, DerefMut};
+use std::{
+    cmp,
+    convert::TryFrom,
+    fmt,
+    ops::{Deref, DerefMut},
+    str::FromStr,
+};
 
-use crate::{Dice, D20, D6};
+use super::{race::RacialTrait, Buffs, Class, Equipment, Level, Race};
+use crate::{Dice, DicePool, Die, Expression, D20, D6};
+use rand::Rng;
 
 const BASE_DAMAGE: f32 = 0.0;
-const BASE_HEALTH: f32 = 10.0;
 const BASE_SPEED: f32 = 1.0;
 const DEXTERITY_MODIFIER: f32 = 0.01;
 const HEALTH_MODIFIER: f32 = 2.0;
+const BASE_ARMOR_CLASS: i16 = 10;
+// the natural roll needed for a critical hit without luck
+const BASE_CRIT_THRESHOLD: i16 = 20;
+// luck can lower the crit threshold down to this
+const MIN_CRIT_THRESHOLD: i16 = 15;
+// percent chance to reroll a failed check per point of luck modifier
+const LUCK_REROLL_CHANCE: i64 = 5;
+const MAX_REROLL_CHANCE: i64 = 25;
+// percent loot chance per point of luck modifier
+const LUCK_LOOT_CHANCE: i64 = 5;
+// percent chance to find a treasure after a won fight without luck
+const BASE_TREASURE_CHANCE: i64 = 2;
+// percent treasure chance per point of luck modifier
+const LUCK_TREASURE_CHANCE: i64 = 2;
+// pounds per point of strength score
+const CARRYING_CAPACITY_MODIFIER: u32 = 15;
+// extra health from dwarven toughness
+const DWARVEN_TOUGHNESS_HEALTH: f32 = 2.0;
+// the damage without a weapon
+const UNARMED_DAMAGE: DicePool = DicePool::new(1, Die::D4);
+// mana per level and per point of the spellcasting modifier
+const MANA_MODIFIER: i64 = 2;
+// the spell save DC before the spellcasting modifier and level bonus
+const BASE_SPELL_SAVE_DC: i16 = 10;
 
 // 0 is equivalent to DnD's 10
 #[derive(Debug, Copy, Clone, Default)]
@@ -32,22 +63,96 @@ impl From<i16> for Attribute {
     }
 }
 
-impl Into<i16> for Attribute {
-    fn into(self) -> i16 {
-        self.0
+impl From<Attribute> for i16 {
+    fn from(attribute: Attribute) -> Self {
+        attribute.0
     }
 }
 
 impl Attribute {
+    /// The attribute for a score on `DnD`'s scale
+    #[must_use]
+    pub fn from_score(score: i16) -> Self {
+        Self(score.saturating_sub(10))
+    }
+
+    /// The attribute score on `DnD`'s scale
+    #[must_use]
+    pub fn score(&self) -> i32 {
+        10 + i32::from(self.0)
+    }
+
+    /// The `DnD` modifier, rounded down: `0` and `1` are `+0`, `2` and `3` are
+    /// `+1` and `-1` and `-2` are `-1`
+    #[must_use]
     pub fn modifier(&self) -> i16 {
-        todo!()
+        self.0.div_euclid(2)
     }
 
+    #[must_use]
     pub fn as_f32(&self) -> f32 {
         self.0.into()
     }
 }
 
+/// The attributes of a character
+#[derive(sqlx::Type, Debug, Copy, Clone, PartialEq, Eq)]
+#[sqlx(rename_all = "lowercase")]
+#[sqlx(rename = "ability")]
+pub enum Ability {
+    Strength,
+    Dexterity,
+    Constitution,
+    Intelligence,
+    Wisdom,
+    Charisma,
+    Luck,
+}
+
+impl Ability {
+    pub const ALL: [Self; 7] = [
+        Self::Strength,
+        Self::Dexterity,
+        Self::Constitution,
+        Self::Intelligence,
+        Self::Wisdom,
+        Self::Charisma,
+        Self::Luck,
+    ];
+}
+
+impl FromStr for Ability {
+    type Err = anyhow::Error;
+
+    /// Parse the name of an ability or its first three letters
+    fn from_str(s: &str) -> anyhow::Result<Self> {
+        Ok(match s.to_ascii_lowercase().as_str() {
+            "str" | "strength" => Self::Strength,
+            "dex" | "dexterity" => Self::Dexterity,
+            "con" | "constitution" => Self::Constitution,
+            "int" | "intelligence" => Self::Intelligence,
+            "wis" | "wisdom" => Self::Wisdom,
+            "cha" | "charisma" => Self::Charisma,
+            "luc" | "luck" => Self::Luck,
+            _ => anyhow::bail!("`{}` is not an attribute", s),
+        })
+    }
+}
+
+impl fmt::Display for Ability {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Strength => "strength",
+            Self::Dexterity => "dexterity",
+            Self::Constitution => "constitution",
+            Self::Intelligence => "intelligence",
+            Self::Wisdom => "wisdom",
+            Self::Charisma => "charisma",
+            Self::Luck => "luck",
+        })
+    }
+}
+
 #[derive(Debug, Copy, Clone, Default)]
 pub struct CharacterStats {
     pub strength: Attribute,
@@ -57,16 +162,163 @@ pub struct CharacterStats {
     pub wisdom: Attribute,
     pub charisma: Attribute,
     pub luck: Attribute,
+    pub race: Race,
+    pub class: Class,
+    pub level: Level,
+    pub equipment: Equipment,
+    pub buffs: Buffs,
 }
 
 impl CharacterStats {
+    /// The attribute the class uses for attacks and damage
+    #[must_use]
     pub fn primary_attribute(&self) -> Attribute {
-        self.strength
+        self.attribute(self.class.primary_ability())
+    }
+
+    /// The attribute including the bonuses of the equipment and buffs
+    #[must_use]
+    pub fn attribute(&self, ability: Ability) -> Attribute {
+        let trained = match ability {
+            Ability::Strength => self.strength,
+            Ability::Dexterity => self.dexterity,
+            Ability::Constitution => self.constitution,
+            Ability::Intelligence => self.intelligence,
+            Ability::Wisdom => self.wisdom,
+            Ability::Charisma => self.charisma,
+            Ability::Luck => self.luck,
+        };
+
+        trained
+            .saturating_add(self.equipment.attribute_bonus(ability))
+            .saturating_add(self.buffs.get(ability))
+            .into()
+    }
+
+    /// The attribute without the bonuses of the equipment and buffs
+    pub fn attribute_mut(&mut self, ability: Ability) -> &mut Attribute {
+        match ability {
+            Ability::Strength => &mut self.strength,
+            Ability::Dexterity => &mut self.dexterity,
+            Ability::Constitution => &mut self.constitution,
+            Ability::Intelligence => &mut self.intelligence,
+            Ability::Wisdom => &mut self.wisdom,
+            Ability::Charisma => &mut self.charisma,
+            Ability::Luck => &mut self.luck,
+        }
+    }
+
+    /// Attacks need to roll more than this to hit
+    #[must_use]
+    pub fn armor_class(&self) -> i16 {
+        // base + dex modifier + armor, shield and accessory
+        BASE_ARMOR_CLASS
+            + self.attribute(Ability::Dexterity).modifier()
+            + self.equipment.armor_class()
+    }
+
+    #[must_use]
+    pub fn initiative_bonus(&self) -> i16 {
+        self.attribute(Ability::Dexterity).modifier()
+    }
+
+    /// The roll deciding who acts first in a fight
+    #[must_use]
+    pub fn initiative_roll(&self) -> Expression {
+        let mut d20 = DicePool::new(1, Die::D20);
+        if self.race.racial_trait() == RacialTrait::KeenSenses {
+            d20 = d20.advantage();
+        }
+
+        Expression::with_bonus(d20, self.initiative_bonus().into())
+    }
+
+    /// The lowest natural d20 roll that is a critical hit
+    #[must_use]
+    pub fn crit_threshold(&self) -> i16 {
+        // every point of luck modifier widens the crit range by one
+        cmp::max(
+            BASE_CRIT_THRESHOLD - cmp::max(self.attribute(Ability::Luck).modifier(), 0),
+            MIN_CRIT_THRESHOLD,
+        )
     }
 
+    /// The positive part of the luck modifier. Bad luck never makes things
+    /// worse.
+    fn luck(&self) -> i64 {
+        cmp::max(self.attribute(Ability::Luck).modifier(), 0).into()
+    }
+
+    /// The chance in percent to reroll a failed attack or saving throw
+    #[must_use]
+    pub fn reroll_chance(&self) -> i64 {
+        cmp::min(self.luck() * LUCK_REROLL_CHANCE, MAX_REROLL_CHANCE)
+    }
+
+    /// The percent added to the chance to find an item after a fight
+    #[must_use]
+    pub fn loot_chance_bonus(&self) -> i64 {
+        self.luck() * LUCK_LOOT_CHANCE
+    }
+
+    /// Whether the item found after a fight is drawn twice, keeping the rarer
+    #[must_use]
+    pub fn lucky_loot(&self) -> bool {
+        self.luck() > 0
+    }
+
+    /// The chance in percent to find a treasure after a won fight
+    #[must_use]
+    pub fn treasure_chance(&self) -> i64 {
+        BASE_TREASURE_CHANCE + self.luck() * LUCK_TREASURE_CHANCE
+    }
+
+    /// The chance that an attack is a critical hit
+    #[must_use]
+    pub fn crit_chance(&self) -> f32 {
+        f32::from(D20::max() - self.crit_threshold() + 1) / f32::from(D20::max())
+    }
+
+    /// How much the character can carry, in pounds
+    #[must_use]
+    pub fn carrying_capacity(&self) -> u32 {
+        // strength score * capacity modifier
+        u32::try_from(self.attribute(Ability::Strength).score()).unwrap_or(0)
+            * CARRYING_CAPACITY_MODIFIER
+    }
+
+    /// The bonus to a saving throw against `ability`
+    #[must_use]
+    pub fn saving_throw(&self, ability: Ability) -> i16 {
+        let bonus = match self.race.racial_trait() {
+            RacialTrait::Resourceful => 1,
+            _ => 0,
+        };
+
+        self.attribute(ability).modifier() + self.level.bonus() + bonus
+    }
+
+    /// The roll for a saving throw against `ability`
+    #[must_use]
+    pub fn saving_throw_roll(&self, ability: Ability) -> Expression {
+        let mut d20 = DicePool::new(1, Die::D20);
+        if self.race.racial_trait() == RacialTrait::GnomeCunning
+            && matches!(
+                ability,
+                Ability::Intelligence | Ability::Wisdom | Ability::Charisma
+            )
+        {
+            d20 = d20.advantage();
+        }
+
+        Expression::with_bonus(d20, self.saving_throw(ability).into())
+    }
+
+    #[must_use]
     pub fn dps(&self) -> f32 {
         // base + dex * dex mod
-        let attacks_per_second = BASE_SPEED + self.dexterity.as_f32() * DEXTERITY_MODIFIER;
+        let attacks_per_second =
+            BASE_SPEED + self.attribute(Ability::Dexterity).as_f32() * DEXTERITY_MODIFIER;
 
         // base + 1D6 + primary attribute modifier
         let damage_per_attack =
@@ -75,35 +327,165 @@ impl CharacterStats {
         damage_per_attack * attacks_per_second
     }
 
+    #[must_use]
     pub fn max_health(&self) -> f32 {
-        // ( base + constitution modifier ) * health modifier
-        (BASE_HEALTH + f32::from(self.constitution.modifier())) * HEALTH_MODIFIER
+        let constitution = self.attribute(Ability::Constitution).modifier();
+        // ( max hit die + constitution modifier ) * health modifier + racial bonus
+        let first_level = self.class.hit_die().max() + constitution;
+        // every further level adds the average hit die, rounded up, but at least 1
+        let per_level = cmp::max(self.class.hit_die().max() / 2 + 1 + constitution, 1);
+        let health =
+            f32::from(first_level + per_level * i16::from(self.level.gained())) * HEALTH_MODIFIER;
+
+        match self.race.racial_trait() {
+            RacialTrait::DwarvenToughness => health + DWARVEN_TOUGHNESS_HEALTH,
+            _ => health,
+        }
+    }
+
+    /// The mana for spells, `0` if the class cannot cast spells
+    #[must_use]
+    pub fn max_mana(&self) -> i64 {
+        match self.class.spellcasting_ability() {
+            // (level + spellcasting modifier) * mana modifier
+            Some(ability) => cmp::max(
+                (i64::from(self.level.get()) + i64::from(self.attribute(ability).modifier()))
+                    * MANA_MODIFIER,
+                0,
+            ),
+            None => 0,
+        }
+    }
+
+    /// The saving throws against the spells of the character need to reach
+    /// this, `None` if the class cannot cast spells
+    #[must_use]
+    pub fn spell_save_dc(&self) -> Option<i16> {
+        self.class.spellcasting_ability().map(|ability| {
+            BASE_SPELL_SAVE_DC + self.attribute(ability).modifier() + self.level.bonus()
+        })
+    }
+
+    /// The die rolled for attacks, without any bonus
+    #[must_use]
+    pub fn attack_die(&self) -> DicePool {
+        let d20 = DicePool::new(1, Die::D20);
+
+        match self.race.racial_trait() {
+            RacialTrait::Lucky => d20.reroll_below(2),
+            _ => d20,
+        }
     }
 
-    pub fn roll_attack(&self) -> i16 {
-        // 1D20 + primary attribute modifier + weapon level
-        D20::roll_once() + self.primary_attribute().modifier()
+    /// The primary attribute modifier plus the level and equipment bonus
+    #[must_use]
+    pub fn attack_bonus(&self) -> i16 {
+        self.primary_attribute().modifier() + self.level.bonus() + self.equipment.attack_bonus()
+    }
+
+    /// The attack roll: the attack die plus the attack bonus
+    #[must_use]
+    pub fn attack_roll(&self) -> Expression {
+        Expression::with_bonus(self.attack_die(), self.attack_bonus().into())
+    }
+
+    /// The damage dice of a hit with the equipped weapon
+    #[must_use]
+    pub fn damage_dice(&self) -> DicePool {
+        self.equipment
+            .weapon
+            .and_then(|weapon| weapon.damage)
+            .unwrap_or(UNARMED_DAMAGE)
+    }
+
+    /// The damage dice of a critical hit
+    #[must_use]
+    pub fn crit_damage_dice(&self) -> DicePool {
+        let mut dice = self.damage_dice();
+        // twice the normal damage dice
+        dice.count *= 2;
+        if self.race.racial_trait() == RacialTrait::SavageAttacks {
+            dice.count += 1;
+        }
+
+        dice
+    }
+
+    pub fn roll_attack<R>(&self, rng: &mut R) -> i16
+    where
+        R: Rng + ?Sized,
+    {
+        // 1D20 + primary attribute modifier + level bonus + equipment bonus
+        // the attack die is a single d20
+        #[allow(clippy::cast_possible_truncation)]
+        let natural = self.attack_die().roll(rng).total as i16;
+
+        natural + self.attack_bonus()
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::item::Item;
+
+    fn assert_close(a: f32, b: f32) {
+        assert!((a - b).abs() < 1e-5, "{} is not {}", a, b);
+    }
+
+    #[test]
+    fn modifier() {
+        for &(value, modifier) in &[
+            (0, 0),
+            (1, 0),
+            (2, 1),
+            (3, 1),
+            (-1, -1),
+            (-2, -1),
+            (-3, -2),
+            (10, 5),
+            (-10, -5),
+            (i16::MAX, 16383),
+            (i16::MIN, -16384),
+        ] {
+            assert_eq!(Attribute::from(value).modifier(), modifier, "{}", value);
+        }
+    }
+
+    #[test]
+    fn score() {
+        assert_eq!(Attribute::from(0).score(), 10);
+        assert_eq!(Attribute::from(-10).score(), 0);
+        assert_eq!(Attribute::from(i16::MAX).score(), 32777);
+        assert_eq!(*Attribute::from_score(15), 5);
+        assert_eq!(*Attribute::from_score(3), -7);
+    }
 
     #[test]
     fn dps() {
-        assert_eq!(
+        // (1 + 1 * 0.01) attacks per second, 1D6 + 0 damage
+        assert_close(
             CharacterStats {
                 strength: 1.into(),
                 dexterity: 1.into(),
                 ..CharacterStats::default()
             }
             .dps(),
-            0.5
+            3.535,
+        );
+        assert_close(
+            CharacterStats {
+                strength: 4.into(),
+                ..CharacterStats::default()
+            }
+            .dps(),
+            5.5,
         );
     }
 
     #[test]
+    // the health of these characters is a whole number
+    #[allow(clippy::float_cmp)]
     fn health() {
         assert_eq!(
             CharacterStats {
@@ -113,5 +495,249 @@ mod tests {
             .max_health(),
             20.0
         );
+        assert_eq!(
+            CharacterStats {
+                constitution: (-3).into(),
+                ..CharacterStats::default()
+            }
+            .max_health(),
+            16.0
+        );
+    }
+
+    #[test]
+    fn armor_class_and_initiative() {
+        let stats = CharacterStats {
+            dexterity: 4.into(),
+            ..CharacterStats::default()
+        };
+        assert_eq!(stats.armor_class(), 12);
+        assert_eq!(stats.initiative_bonus(), 2);
+
+        let stats = CharacterStats {
+            dexterity: (-1).into(),
+            ..CharacterStats::default()
+        };
+        assert_eq!(stats.armor_class(), 9);
+        assert_eq!(stats.initiative_bonus(), -1);
+    }
+
+    #[test]
+    fn crit() {
+        let crit = |luck: i16| {
+            let stats = CharacterStats {
+                luck: luck.into(),
+                ..CharacterStats::default()
+            };
+            (stats.crit_threshold(), stats.crit_chance())
+        };
+
+        let (threshold, chance) = crit(0);
+        assert_eq!(threshold, 20);
+        assert_close(chance, 0.05);
+
+        // bad luck does not make crits rarer
+        assert_eq!(crit(-10).0, 20);
+        assert_eq!(crit(4).0, 18);
+
+        let (threshold, chance) = crit(i16::MAX);
+        assert_eq!(threshold, 15);
+        assert_close(chance, 0.3);
+    }
+
+    #[test]
+    fn luck() {
+        let stats = |luck: i16| CharacterStats {
+            luck: luck.into(),
+            ..CharacterStats::default()
+        };
+
+        assert_eq!(stats(0).reroll_chance(), 0);
+        assert_eq!(stats(0).loot_chance_bonus(), 0);
+        assert!(!stats(0).lucky_loot());
+        assert_eq!(stats(0).treasure_chance(), 2);
+
+        assert_eq!(stats(4).reroll_chance(), 10);
+        assert_eq!(stats(4).loot_chance_bonus(), 10);
+        assert!(stats(4).lucky_loot());
+        assert_eq!(stats(4).treasure_chance(), 6);
+
+        assert_eq!(stats(-6).treasure_chance(), 2);
+        assert_eq!(stats(i16::MAX).reroll_chance(), 25);
+    }
+
+    #[test]
+    fn carrying_capacity() {
+        let capacity = |strength: i16| {
+            CharacterStats {
+                strength: strength.into(),
+                ..CharacterStats::default()
+            }
+            .carrying_capacity()
+        };
+
+        assert_eq!(capacity(0), 150);
+        assert_eq!(capacity(5), 225);
+        assert_eq!(capacity(-10), 0);
+        assert_eq!(capacity(i16::MIN), 0);
+    }
+
+    #[test]
+    fn saving_throws() {
+        let mut stats = CharacterStats {
+            wisdom: 3.into(),
+            charisma: (-4).into(),
+            // dwarves get no bonus on saving throws
+            race: Race::Dwarf,
+            ..CharacterStats::default()
+        };
+
+        assert_eq!(stats.saving_throw(Ability::Wisdom), 1);
+        assert_eq!(stats.saving_throw(Ability::Charisma), -2);
+        assert_eq!(stats.saving_throw(Ability::Strength), 0);
+
+        // humans get +1 on every saving throw
+        stats.race = Race::Human;
+        assert_eq!(stats.saving_throw(Ability::Wisdom), 2);
+        assert_eq!(stats.saving_throw(Ability::Charisma), -1);
+        assert_eq!(stats.saving_throw(Ability::Strength), 1);
+    }
+
+    #[test]
+    fn classes() {
+        let stats = |class| CharacterStats {
+            strength: 4.into(),
+            dexterity: 6.into(),
+            intelligence: (-2).into(),
+            class,
+            race: Race::Dwarf,
+            ..CharacterStats::default()
+        };
+
+        assert_eq!(*stats(Class::Fighter).primary_attribute(), 4);
+        assert_eq!(*stats(Class::Rogue).primary_attribute(), 6);
+        assert_eq!(stats(Class::Ranger).attack_roll().to_string(), "d20+3");
+        assert_eq!(stats(Class::Wizard).attack_roll().to_string(), "d20-1");
+        assert_close(stats(Class::Wizard).max_health(), 14.0);
+        assert_close(stats(Class::Cleric).max_health(), 18.0);
+    }
+
+    #[test]
+    fn spellcasting() {
+        let stats = |class, level: i16| CharacterStats {
+            intelligence: 4.into(),
+            wisdom: (-6).into(),
+            class,
+            level: level.into(),
+            ..CharacterStats::default()
+        };
+
+        assert_eq!(stats(Class::Wizard, 1).max_mana(), 6);
+        assert_eq!(stats(Class::Wizard, 5).max_mana(), 14);
+        assert_eq!(stats(Class::Wizard, 5).spell_save_dc(), Some(13));
+        assert_eq!(stats(Class::Cleric, 1).max_mana(), 0);
+        assert_eq!(stats(Class::Cleric, 1).spell_save_dc(), Some(7));
+        assert_eq!(stats(Class::Fighter, 20).max_mana(), 0);
+        assert_eq!(stats(Class::Fighter, 20).spell_save_dc(), None);
+    }
+
+    #[test]
+    fn parse_ability() {
+        assert_eq!("str".parse::<Ability>().unwrap(), Ability::Strength);
+        assert_eq!("Wisdom".parse::<Ability>().unwrap(), Ability::Wisdom);
+        assert_eq!(Ability::Charisma.to_string(), "charisma");
+        assert!("might".parse::<Ability>().is_err());
+    }
+
+    #[test]
+    fn levels() {
+        let stats = |level: i16| CharacterStats {
+            strength: 2.into(),
+            constitution: 2.into(),
+            level: level.into(),
+            race: Race::Dwarf,
+            ..CharacterStats::default()
+        };
+
+        // (10 + 1) * 2 + 2, then (6 + 1) * 2 per level
+        assert_close(stats(1).max_health(), 24.0);
+        assert_close(stats(3).max_health(), 52.0);
+        assert_eq!(stats(4).attack_roll().to_string(), "d20+1");
+        assert_eq!(stats(5).attack_roll().to_string(), "d20+2");
+        assert_eq!(stats(9).saving_throw(Ability::Constitution), 3);
+    }
+
+    #[test]
+    fn equipment() {
+        let mut stats = CharacterStats {
+            strength: 1.into(),
+            race: Race::Orc,
+            ..CharacterStats::default()
+        };
+        assert_eq!(stats.damage_dice().to_string(), "d4");
+
+        for key in &[
+            "greataxe",
+            "chain_mail",
+            "shield",
+            "gauntlets_of_ogre_power",
+        ] {
+            stats.equipment.equip(Item::by_key(key).unwrap());
+        }
+
+        assert_eq!(stats.damage_dice().to_string(), "d12");
+        assert_eq!(stats.crit_damage_dice().to_string(), "3d12");
+        assert_eq!(stats.armor_class(), 16);
+        assert_eq!(stats.attribute(Ability::Strength).score(), 13);
+        assert_eq!(stats.strength.score(), 11);
+        assert_eq!(stats.attack_roll().to_string(), "d20+1");
+    }
+
+    #[test]
+    fn buffs() {
+        let mut stats = CharacterStats::default();
+        stats
+            .equipment
+            .equip(Item::by_key("gauntlets_of_ogre_power").unwrap());
+        stats.buffs.add(Ability::Strength, 2);
+
+        assert_eq!(stats.attribute(Ability::Strength).score(), 14);
+        assert_eq!(stats.strength.score(), 10);
+        assert_eq!(stats.carrying_capacity(), 210);
+    }
+
+    #[test]
+    fn racial_traits() {
+        let stats = |race| CharacterStats {
+            race,
+            ..CharacterStats::default()
+        };
+
+        assert_eq!(stats(Race::Human).saving_throw(Ability::Wisdom), 1);
+        assert_eq!(stats(Race::Dwarf).saving_throw(Ability::Wisdom), 0);
+        assert_close(stats(Race::Dwarf).max_health(), 22.0);
+        assert_eq!(stats(Race::Elf).initiative_roll().to_string(), "d20adv");
+        assert_eq!(stats(Race::Orc).initiative_roll().to_string(), "d20");
+        assert_eq!(stats(Race::Halfling).attack_roll().to_string(), "d20r2");
+        assert_eq!(stats(Race::Orc).crit_damage_dice().count, 3);
+        assert_eq!(stats(Race::Elf).crit_damage_dice().count, 2);
+        assert_eq!(
+            stats(Race::Gnome)
+                .saving_throw_roll(Ability::Charisma)
+                .to_string(),
+            "d20adv"
+        );
+        assert_eq!(
+            stats(Race::Gnome)
+                .saving_throw_roll(Ability::Strength)
+                .to_string(),
+            "d20"
+        );
+        assert_eq!(
+            stats(Race::Human)
+                .saving_throw_roll(Ability::Strength)
+                .to_string(),
+            "d20+1"
+        );
     }
 }
@@ -1,27 +1,240 @@
-use anyhow::Result;
-use serde::{Deserialize, Serialize};
-use std::{borrow::Cow, fs::File, path::Path};
+use crate::character::CreationMethod;
+use anyhow::{Context, Result};
+use log::LevelFilter;
+use serde::{Deserialize, Deserializer};
+use std::{
+    borrow::Cow,
+    env, fmt,
+    fs::{self, File},
+    path::{Path, PathBuf},
+    str::FromStr,
+};
 use twitchchat::UserConfig;
 
-#[derive(Debug, Clone, Deserialize, Serialize)]
+/// Prefix of all environment variables that override the configuration file
+pub const ENV_PREFIX: &str = "DUNGEON_BOT_";
+
+/// Characters drink a healing potion in fights below this percentage of health
+const DEFAULT_AUTO_HEAL_BELOW: u8 = 30;
+
+#[derive(Debug, Clone)]
 pub struct Config<'a> {
     username: Cow<'a, str>,
     token: Cow<'a, str>,
     channels: Vec<String>,
     database_url: Cow<'a, str>,
+    log_level: Option<LevelFilter>,
+    owner: Option<String>,
+    provably_fair: bool,
+    creation_method: CreationMethod,
+    auto_heal_below: u8,
+}
+
+/// The configuration as it is written in the file. Every field is optional so
+/// that missing values can be supplied by the environment.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+struct PartialConfig {
+    #[serde(deserialize_with = "some")]
+    username: Option<String>,
+    #[serde(deserialize_with = "some")]
+    token: Option<String>,
+    #[serde(deserialize_with = "some")]
+    token_file: Option<PathBuf>,
+    #[serde(deserialize_with = "some")]
+    channels: Option<Vec<String>>,
+    #[serde(deserialize_with = "some")]
+    database_url: Option<String>,
+    #[serde(deserialize_with = "some")]
+    database_url_file: Option<PathBuf>,
+    #[serde(deserialize_with = "some")]
+    log_level: Option<String>,
+    #[serde(deserialize_with = "some")]
+    owner: Option<String>,
+    #[serde(deserialize_with = "some")]
+    provably_fair: Option<bool>,
+    #[serde(deserialize_with = "some")]
+    creation_method: Option<String>,
+    #[serde(deserialize_with = "some")]
+    auto_heal_below: Option<u8>,
+}
+
+/// Allow writing `token: "..."` instead of `token: Some("...")`
+fn some<'de, D, T>(deserializer: D) -> Result<Option<T>, D::Error>
+where
+    D: Deserializer<'de>,
+    T: Deserialize<'de>,
+{
+    T::deserialize(deserializer).map(Some)
+}
+
+/// Every problem found while validating a configuration
+#[derive(Debug)]
+struct ValidationError(Vec<String>);
+
+impl fmt::Display for ValidationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid configuration:")?;
+
+        for problem in &self.0 {
+            write!(f, "\n  - {}", problem)?;
+        }
+
+        Ok(())
+    }
 }
 
+impl std::error::Error for ValidationError {}
+
 impl Config<'_> {
+    /// Load the configuration from `path` and apply overrides from the
+    /// environment.
+    ///
+    /// Every field can be overridden with an environment variable named after
+    /// the field and prefixed with [`ENV_PREFIX`], e.g. `DUNGEON_BOT_TOKEN`.
+    /// Secrets can also be read from a file with `token_file` and
+    /// `database_url_file` (or `DUNGEON_BOT_TOKEN_FILE` and
+    /// `DUNGEON_BOT_DATABASE_URL_FILE`).
+    ///
+    /// # Errors
+    ///
+    /// Fails if the file cannot be read or parsed, or with a
+    /// [`ValidationError`] listing every invalid field
     pub fn load<P>(path: P) -> Result<Self>
     where
         P: AsRef<Path>,
     {
-        let file = File::open(path)?;
-        let config = ron::de::from_reader(file)?;
+        let path = path.as_ref();
+        let file = File::open(path)
+            .with_context(|| format!("Could not open config file {}", path.display()))?;
+        let partial = ron::de::from_reader(file)
+            .with_context(|| format!("Could not parse config file {}", path.display()))?;
 
-        Ok(config)
+        Self::resolve(partial, |key| env::var(key).ok())
     }
 
+    fn resolve<F>(file: PartialConfig, env: F) -> Result<Self>
+    where
+        F: Fn(&str) -> Option<String>,
+    {
+        let var = |field: &str| env(&format!("{}{}", ENV_PREFIX, field.to_ascii_uppercase()));
+        let mut problems = Vec::new();
+
+        let username = var("username").or(file.username);
+        let token = resolve_secret(
+            "token",
+            (var("token"), var("token_file").map(PathBuf::from)),
+            (file.token, file.token_file),
+        );
+        let channels = var("channels")
+            .map(|channels| {
+                channels
+                    .split(',')
+                    .map(|channel| channel.trim().to_string())
+                    .collect()
+            })
+            .or(file.channels)
+            .unwrap_or_default();
+        let database_url = resolve_secret(
+            "database_url",
+            (
+                var("database_url"),
+                var("database_url_file").map(PathBuf::from),
+            ),
+            (file.database_url, file.database_url_file),
+        );
+        let log_level = var("log_level").or(file.log_level);
+        let owner = var("owner").or(file.owner);
+        // invalid values fail the whole config, so falling back to the file
+        // after a parse error does not matter
+        let provably_fair = parse_field::<bool>(
+            var("provably_fair"),
+            "`provably_fair` must be true or false",
+            &mut problems,
+        )
+        .or(file.provably_fair);
+
+        match &username {
+            None => problems.push(String::from("`username` is missing")),
+            Some(username) if username.trim().is_empty() => {
+                problems.push(String::from("`username` must not be empty"));
+            }
+            Some(_) => {}
+        }
+
+        match &token {
+            Err(problem) => problems.push(problem.clone()),
+            Ok(None) => problems.push(String::from("`token` is missing")),
+            Ok(Some(token)) if !token.starts_with("oauth:") || token.len() != 36 => problems.push(
+                String::from("`token` must start with `oauth:` and be 36 characters long"),
+            ),
+            Ok(Some(_)) => {}
+        }
+
+        for channel in &channels {
+            if channel.is_empty() || channel.contains(char::is_whitespace) {
+                problems.push(format!("channel {:?} is not a valid channel name", channel));
+            }
+        }
+
+        match &database_url {
+            Err(problem) => problems.push(problem.clone()),
+            Ok(None) => problems.push(String::from("`database_url` is missing")),
+            Ok(Some(url))
+                if !url.starts_with("postgres://") && !url.starts_with("postgresql://") =>
+            {
+                problems.push(String::from(
+                    "`database_url` must start with `postgres://` or `postgresql://`",
+                ));
+            }
+            Ok(Some(_)) => {}
+        }
+
+        let creation_method = parse_field::<CreationMethod>(
+            var("creation_method").or(file.creation_method),
+            "`creation_method` must be one of rolled, standard_array or point_buy",
+            &mut problems,
+        )
+        .unwrap_or_default();
+
+        let auto_heal_below = parse_field::<u8>(
+            var("auto_heal_below"),
+            "`auto_heal_below` must be a percentage",
+            &mut problems,
+        )
+        .or(file.auto_heal_below);
+        if matches!(auto_heal_below, Some(percent) if percent > 100) {
+            problems.push(String::from("`auto_heal_below` must be between 0 and 100"));
+        }
+
+        let log_level = parse_field::<LevelFilter>(
+            log_level,
+            "`log_level` must be one of off, error, warn, info, debug or trace",
+            &mut problems,
+        );
+
+        if !problems.is_empty() {
+            return Err(ValidationError(problems).into());
+        }
+
+        Ok(Self {
+            username: username.unwrap_or_default().into(),
+            token: token.ok().flatten().unwrap_or_default().into(),
+            channels,
+            database_url: database_url.ok().flatten().unwrap_or_default().into(),
+            log_level,
+            owner: owner.map(|owner| owner.to_ascii_lowercase()),
+            provably_fair: provably_fair.unwrap_or_default(),
+            creation_method,
+            auto_heal_below: auto_heal_below.unwrap_or(DEFAULT_AUTO_HEAL_BELOW),
+        })
+    }
+
+    /// The credentials of the bot for Twitch
+    ///
+    /// # Errors
+    ///
+    /// Fails if twitchchat rejects the username or token
     pub fn user_config(&self) -> Result<UserConfig> {
         let user_config = UserConfig::builder()
             .name(self.username.as_ref())
@@ -32,18 +245,307 @@ impl Config<'_> {
         Ok(user_config)
     }
 
+    #[must_use]
     pub fn channels(&self) -> &[String] {
         self.channels.as_slice()
     }
 
-    pub fn add_channel<S>(&mut self, channel: S)
-    where
-        S: ToString,
-    {
-        self.channels.push(channel.to_string())
+    pub fn add_channel(&mut self, channel: &str) {
+        self.channels.push(channel.to_string());
     }
 
+    #[must_use]
     pub fn database_url(&self) -> &str {
         self.database_url.as_ref()
     }
+
+    #[must_use]
+    pub fn log_level(&self) -> Option<LevelFilter> {
+        self.log_level
+    }
+
+    /// The login of the user allowed to run owner-only commands
+    #[must_use]
+    pub fn owner(&self) -> Option<&str> {
+        self.owner.as_deref()
+    }
+
+    /// Whether the seed of every encounter is committed to before rolling and
+    /// revealed afterwards
+    #[must_use]
+    pub fn provably_fair(&self) -> bool {
+        self.provably_fair
+    }
+
+    /// How new characters get their attribute scores
+    #[must_use]
+    pub fn creation_method(&self) -> CreationMethod {
+        self.creation_method
+    }
+
+    /// Characters use a healing potion during fights when their health drops
+    /// below this percentage of their max health. `0` turns it off.
+    #[must_use]
+    pub fn auto_heal_below(&self) -> u8 {
+        self.auto_heal_below
+    }
+
+    pub(crate) fn same_credentials(&self, other: &Self) -> bool {
+        self.username == other.username && self.token == other.token
+    }
+
+    /// Keep the credentials and database URL of `running`, which only take
+    /// effect after a restart
+    pub(crate) fn keep_restart_fields(&mut self, running: &Self) {
+        self.username.clone_from(&running.username);
+        self.token.clone_from(&running.token);
+        self.database_url.clone_from(&running.database_url);
+    }
+}
+
+/// Parse `value` if it is set, adding `problem` to `problems` if it is invalid
+fn parse_field<T>(value: Option<String>, problem: &str, problems: &mut Vec<String>) -> Option<T>
+where
+    T: FromStr,
+{
+    match value.map(|value| value.parse::<T>()) {
+        Some(Ok(value)) => Some(value),
+        Some(Err(_)) => {
+            problems.push(String::from(problem));
+            None
+        }
+        None => None,
+    }
+}
+
+/// Pick a secret from the environment or the file, in this order. A secret
+/// can be given directly or as a path to a file containing it, but not both in
+/// the same place.
+fn resolve_secret(
+    name: &str,
+    env: (Option<String>, Option<PathBuf>),
+    file: (Option<String>, Option<PathBuf>),
+) -> Result<Option<String>, String> {
+    for source in [env, file] {
+        match source {
+            (Some(_), Some(_)) => {
+                return Err(format!("set either `{0}` or `{0}_file`, not both", name))
+            }
+            (Some(value), None) => return Ok(Some(value)),
+            (None, Some(path)) => {
+                return fs::read_to_string(&path)
+                    .map(|value| Some(value.trim_end().to_string()))
+                    .map_err(|err| {
+                        format!("could not read `{}_file` {}: {}", name, path.display(), err)
+                    })
+            }
+            (None, None) => {}
+        }
+    }
+
+    Ok(None)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    const TOKEN: &str = "oauth:abcdefghijklmnopqrstuvwxyz0123";
+
+    fn complete() -> PartialConfig {
+        PartialConfig {
+            username: Some(String::from("dungeon_bot")),
+            token: Some(String::from(TOKEN)),
+            channels: Some(vec![String::from("chronophylos")]),
+            database_url: Some(String::from("postgres://localhost/dungeon")),
+            ..PartialConfig::default()
+        }
+    }
+
+    fn resolve(file: PartialConfig, env: &[(&str, &str)]) -> Result<Config<'static>> {
+        let env: HashMap<String, String> = env
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect();
+
+        Config::resolve(file, |key| env.get(key).cloned())
+    }
+
+    #[test]
+    fn parse() {
+        let file: PartialConfig = ron::de::from_str(
+            r#"(
+                username: "dungeon_bot",
+                token_file: "/run/secrets/token",
+                channels: ["chronophylos"],
+            )"#,
+        )
+        .unwrap();
+
+        assert_eq!(file.username.as_deref(), Some("dungeon_bot"));
+        assert_eq!(file.token, None);
+        assert_eq!(file.token_file, Some(PathBuf::from("/run/secrets/token")));
+        assert_eq!(file.channels, Some(vec![String::from("chronophylos")]));
+        assert_eq!(file.database_url, None);
+    }
+
+    #[test]
+    fn from_file() {
+        let config = resolve(complete(), &[]).unwrap();
+
+        assert_eq!(config.username, "dungeon_bot");
+        assert_eq!(config.token, TOKEN);
+        assert_eq!(config.channels(), ["chronophylos"]);
+        assert_eq!(config.database_url(), "postgres://localhost/dungeon");
+        assert_eq!(config.log_level(), None);
+        assert!(!config.provably_fair());
+        assert_eq!(config.creation_method(), CreationMethod::Rolled);
+        assert_eq!(config.auto_heal_below(), DEFAULT_AUTO_HEAL_BELOW);
+    }
+
+    #[test]
+    fn env_overrides_file() {
+        let config = resolve(
+            complete(),
+            &[
+                ("DUNGEON_BOT_USERNAME", "other_bot"),
+                ("DUNGEON_BOT_CHANNELS", "a, b,c"),
+                ("DUNGEON_BOT_DATABASE_URL", "postgresql://db/dungeon"),
+                ("DUNGEON_BOT_LOG_LEVEL", "warn"),
+                ("DUNGEON_BOT_PROVABLY_FAIR", "true"),
+                ("DUNGEON_BOT_CREATION_METHOD", "point_buy"),
+                ("DUNGEON_BOT_AUTO_HEAL_BELOW", "0"),
+            ],
+        )
+        .unwrap();
+
+        assert_eq!(config.username, "other_bot");
+        assert_eq!(config.channels(), ["a", "b", "c"]);
+        assert_eq!(config.database_url(), "postgresql://db/dungeon");
+        assert_eq!(config.log_level(), Some(LevelFilter::Warn));
+        assert!(config.provably_fair());
+        assert_eq!(config.creation_method(), CreationMethod::PointBuy);
+        assert_eq!(config.auto_heal_below(), 0);
+    }
+
+    #[test]
+    fn env_only() {
+        let config = resolve(
+            PartialConfig::default(),
+            &[
+                ("DUNGEON_BOT_USERNAME", "dungeon_bot"),
+                ("DUNGEON_BOT_TOKEN", TOKEN),
+                ("DUNGEON_BOT_DATABASE_URL", "postgres://localhost/dungeon"),
+            ],
+        )
+        .unwrap();
+
+        assert!(config.channels().is_empty());
+    }
+
+    #[test]
+    fn token_from_file() {
+        let path = env::temp_dir().join(format!("dungeon-bot-token-{}", std::process::id()));
+        fs::write(&path, format!("{}\n", TOKEN)).unwrap();
+
+        let config = resolve(
+            PartialConfig {
+                token: None,
+                token_file: Some(path.clone()),
+                ..complete()
+            },
+            &[],
+        );
+        fs::remove_file(&path).unwrap();
+
+        assert_eq!(config.unwrap().token, TOKEN);
+    }
+
+    #[test]
+    fn env_secret_overrides_file_secret() {
+        let config = resolve(
+            PartialConfig {
+                token: None,
+                token_file: Some(PathBuf::from("/does/not/exist")),
+                ..complete()
+            },
+            &[("DUNGEON_BOT_TOKEN", TOKEN)],
+        )
+        .unwrap();
+
+        assert_eq!(config.token, TOKEN);
+    }
+
+    #[test]
+    fn lists_every_problem() {
+        let err = resolve(
+            PartialConfig {
+                token: Some(String::from("hunter2")),
+                token_file: Some(PathBuf::from("/does/not/exist")),
+                channels: Some(vec![String::new()]),
+                log_level: Some(String::from("loud")),
+                ..PartialConfig::default()
+            },
+            &[],
+        )
+        .unwrap_err()
+        .to_string();
+
+        assert_eq!(
+            err,
+            "invalid configuration:\n  \
+             - `username` is missing\n  \
+             - set either `token` or `token_file`, not both\n  \
+             - channel \"\" is not a valid channel name\n  \
+             - `database_url` is missing\n  \
+             - `log_level` must be one of off, error, warn, info, debug or trace"
+        );
+    }
+
+    #[test]
+    fn invalid_values() {
+        let err = resolve(
+            PartialConfig {
+                username: Some(String::from(" ")),
+                token: Some(String::from("hunter2")),
+                database_url: Some(String::from("mysql://localhost")),
+                ..complete()
+            },
+            &[],
+        )
+        .unwrap_err()
+        .to_string();
+
+        assert!(err.contains("`username` must not be empty"));
+        assert!(err.contains("`token` must start with `oauth:`"));
+        assert!(err.contains("`database_url` must start with `postgres://`"));
+
+        let err = resolve(complete(), &[("DUNGEON_BOT_PROVABLY_FAIR", "yes")])
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("`provably_fair` must be true or false"));
+
+        let err = resolve(
+            PartialConfig {
+                creation_method: Some(String::from("4d6")),
+                ..complete()
+            },
+            &[],
+        )
+        .unwrap_err()
+        .to_string();
+        assert!(err.contains("`creation_method` must be one of"));
+
+        let err = resolve(
+            PartialConfig {
+                auto_heal_below: Some(120),
+                ..complete()
+            },
+            &[],
+        )
+        .unwrap_err()
+        .to_string();
+        assert!(err.contains("`auto_heal_below` must be between 0 and 100"));
+    }
 }
@@ -0,0 +1,56 @@
+use anyhow::Result;
+use sqlx::PgPool;
+
+/// Attribute scores a player can assign to their new character
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PendingCreation {
+    pub scores: Vec<i16>,
+    pub rerolled: bool,
+}
+
+impl PendingCreation {
+    /// The scores `player_id` rolled or was given, if they did not create
+    /// their character yet
+    ///
+    /// # Errors
+    ///
+    /// Fails if the database cannot be queried
+    pub async fn get(pool: &PgPool, player_id: i32) -> Result<Option<Self>> {
+        let creation = sqlx::query_as!(
+            Self,
+            r#"
+SELECT scores, rerolled
+FROM character_creation
+WHERE player_id = $1
+            "#,
+            player_id
+        )
+        .fetch_optional(pool)
+        .await?;
+
+        Ok(creation)
+    }
+
+    /// Store the scores of `player_id`, replacing earlier ones
+    ///
+    /// # Errors
+    ///
+    /// Fails if the player does not exist
+    pub async fn save(&self, pool: &PgPool, player_id: i32) -> Result<()> {
+        sqlx::query!(
+            r#"
+INSERT INTO character_creation (player_id, scores, rerolled)
+VALUES ($1, $2, $3)
+ON CONFLICT (player_id) DO UPDATE
+SET scores = excluded.scores, rerolled = excluded.rerolled
+            "#,
+            player_id,
+            &self.scores[..],
+            self.rerolled,
+        )
+        .execute(pool)
+        .await?;
+
+        Ok(())
+    }
+}
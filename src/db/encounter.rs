@@ -0,0 +1,206 @@
+use crate::{encounter::RecordedRoll, Commitment, Seed};
+use anyhow::{Context, Result};
+use sqlx::PgPool;
+use std::convert::TryFrom;
+
+pub struct Encounter<'a> {
+    id: i32,
+
+    pool: &'a PgPool,
+}
+
+impl<'a> Encounter<'a> {
+    #[must_use]
+    pub fn new(pool: &'a PgPool, id: i32) -> Self {
+        Self { id, pool }
+    }
+
+    /// The encounter with `id` if it exists
+    ///
+    /// # Errors
+    ///
+    /// Fails if the database cannot be queried
+    pub async fn find(pool: &'a PgPool, id: i32) -> Result<Option<Encounter<'a>>> {
+        let rec = sqlx::query!(
+            r#"
+SELECT id
+FROM encounter
+WHERE id = $1
+            "#,
+            id
+        )
+        .fetch_optional(pool)
+        .await?;
+
+        Ok(rec.map(|rec| Self::new(pool, rec.id)))
+    }
+
+    /// Record a new encounter of `player_id` with the seed of its rolls and
+    /// the commitment to it
+    ///
+    /// # Errors
+    ///
+    /// Fails if the player does not exist
+    pub async fn insert(pool: &'a PgPool, player_id: i32, seed: Seed) -> Result<Encounter<'a>> {
+        let commitment = seed.commitment();
+        let rec = sqlx::query!(
+            r#"
+INSERT INTO encounter (player_id, seed, commitment)
+VALUES ($1, $2, $3)
+RETURNING id
+            "#,
+            player_id,
+            &seed.as_bytes()[..],
+            &commitment.as_bytes()[..],
+        )
+        .fetch_one(pool)
+        .await?;
+
+        Ok(Self::new(pool, rec.id))
+    }
+
+    #[must_use]
+    pub fn id(&self) -> i32 {
+        self.id
+    }
+
+    /// The seed of the rolls, secret until it is revealed
+    ///
+    /// # Errors
+    ///
+    /// Fails if the stored seed is malformed
+    pub async fn seed(&self) -> Result<Seed> {
+        let rec = sqlx::query!(
+            r#"
+SELECT seed
+FROM encounter
+WHERE id = $1
+            "#,
+            self.id
+        )
+        .fetch_one(self.pool)
+        .await?;
+
+        Seed::from_slice(&rec.seed)
+    }
+
+    /// The commitment to the seed, public from the start
+    ///
+    /// # Errors
+    ///
+    /// Fails if the stored commitment is malformed
+    pub async fn commitment(&self) -> Result<Commitment> {
+        let rec = sqlx::query!(
+            r#"
+SELECT commitment
+FROM encounter
+WHERE id = $1
+            "#,
+            self.id
+        )
+        .fetch_one(self.pool)
+        .await?;
+
+        Commitment::from_slice(&rec.commitment)
+    }
+
+    /// Make the seed public
+    ///
+    /// # Errors
+    ///
+    /// Fails if the encounter does not exist or its seed is malformed
+    pub async fn reveal(&self) -> Result<Seed> {
+        let rec = sqlx::query!(
+            r#"
+UPDATE encounter
+SET revealed_at = coalesce(revealed_at, now())
+WHERE id = $1
+RETURNING seed
+            "#,
+            self.id
+        )
+        .fetch_one(self.pool)
+        .await?;
+
+        Seed::from_slice(&rec.seed)
+    }
+
+    /// The seed if it was revealed
+    ///
+    /// # Errors
+    ///
+    /// Fails if the encounter does not exist or its seed is malformed
+    pub async fn revealed_seed(&self) -> Result<Option<Seed>> {
+        let rec = sqlx::query!(
+            r#"
+SELECT seed, revealed_at
+FROM encounter
+WHERE id = $1
+            "#,
+            self.id
+        )
+        .fetch_one(self.pool)
+        .await?;
+
+        match rec.revealed_at {
+            Some(_) => Ok(Some(Seed::from_slice(&rec.seed)?)),
+            None => Ok(None),
+        }
+    }
+
+    /// Store the rolls of the encounter in the order they were made
+    ///
+    /// # Errors
+    ///
+    /// Fails if the rolls were already recorded
+    pub async fn record_rolls(&self, rolls: &[RecordedRoll]) -> Result<()> {
+        let mut tx = self.pool.begin().await?;
+
+        for (position, roll) in rolls.iter().enumerate() {
+            sqlx::query!(
+                r#"
+INSERT INTO encounter_roll (encounter_id, position, expression, total)
+VALUES ($1, $2, $3, $4)
+                "#,
+                self.id,
+                i32::try_from(position).context("Too many rolls")?,
+                roll.expression.to_string(),
+                roll.total,
+            )
+            .execute(&mut tx)
+            .await?;
+        }
+
+        tx.commit().await?;
+
+        Ok(())
+    }
+
+    /// Every recorded roll in the order they were made
+    ///
+    /// # Errors
+    ///
+    /// Fails if a recorded expression cannot be parsed
+    pub async fn rolls(&self) -> Result<Vec<RecordedRoll>> {
+        let recs = sqlx::query!(
+            r#"
+SELECT expression, total
+FROM encounter_roll
+WHERE encounter_id = $1
+ORDER BY position
+            "#,
+            self.id
+        )
+        .fetch_all(self.pool)
+        .await?;
+
+        recs.into_iter()
+            .map(|rec| {
+                Ok(RecordedRoll {
+                    expression: rec.expression.parse()?,
+                    total: rec.total,
+                })
+            })
+            .collect()
+    }
+}
@@ -0,0 +1,422 @@
+use crate::{
+    character::Equipment,
+    item::{Item, Slot},
+};
+use anyhow::{bail, ensure, Context, Result};
+use log::warn;
+use sqlx::{PgPool, Postgres, Transaction};
+use std::convert::TryFrom;
+
+/// An item a player owns. Stackable items are owned once with a quantity,
+/// every other item once per instance.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct OwnedItem {
+    pub id: i32,
+    pub item: &'static Item,
+    /// The slot the item is equipped in
+    pub slot: Option<Slot>,
+    pub quantity: u32,
+}
+
+impl OwnedItem {
+    /// The weight of the whole stack in pounds, `None` if it overflows
+    #[must_use]
+    pub fn weight(&self) -> Option<u32> {
+        self.item.weight.checked_mul(self.quantity)
+    }
+
+    /// Every item `player_id` owns, equipped items first
+    ///
+    /// # Errors
+    ///
+    /// Fails if the database cannot be queried
+    pub async fn of_player(pool: &PgPool, player_id: i32) -> Result<Vec<Self>> {
+        let recs = sqlx::query!(
+            r#"
+SELECT id, key, slot as "slot: Slot", quantity
+FROM item
+WHERE player_id = $1
+ORDER BY slot NULLS LAST, id
+            "#,
+            player_id
+        )
+        .fetch_all(pool)
+        .await?;
+
+        Ok(recs
+            .into_iter()
+            .filter_map(|rec| {
+                Some(Self {
+                    id: rec.id,
+                    item: known(&rec.key)?,
+                    slot: rec.slot,
+                    quantity: u32::try_from(rec.quantity).ok()?,
+                })
+            })
+            .collect())
+    }
+
+    /// The equipped items of `player_id`
+    ///
+    /// # Errors
+    ///
+    /// Fails if the database cannot be queried
+    pub async fn equipment(pool: &PgPool, player_id: i32) -> Result<Equipment> {
+        let recs = sqlx::query!(
+            r#"
+SELECT key
+FROM item
+WHERE player_id = $1 AND slot IS NOT NULL
+            "#,
+            player_id
+        )
+        .fetch_all(pool)
+        .await?;
+
+        let mut equipment = Equipment::default();
+        for item in recs.iter().filter_map(|rec| known(&rec.key)) {
+            equipment.equip(item);
+        }
+
+        Ok(equipment)
+    }
+
+    /// Give `item` to `player_id`, equipped if `equip` is true
+    ///
+    /// # Errors
+    ///
+    /// Fails if the player does not exist
+    pub async fn grant(
+        tx: &mut Transaction<'_, Postgres>,
+        player_id: i32,
+        item: &'static Item,
+        equip: bool,
+    ) -> Result<()> {
+        sqlx::query!(
+            r#"
+INSERT INTO item (player_id, key, slot)
+VALUES ($1, $2, $3)
+            "#,
+            player_id,
+            item.key,
+            item.slot.filter(|_| equip) as Option<Slot>,
+        )
+        .execute(tx)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Give `quantity` of `item` to `player_id` unless they would carry more
+    /// than `capacity` pounds
+    ///
+    /// # Errors
+    ///
+    /// Fails if the items would be too heavy to carry
+    pub async fn add(
+        pool: &PgPool,
+        player_id: i32,
+        item: &'static Item,
+        quantity: u32,
+        capacity: u32,
+    ) -> Result<()> {
+        let mut tx = pool.begin().await?;
+        Self::give(&mut tx, player_id, item, quantity, capacity).await?;
+        tx.commit().await?;
+
+        Ok(())
+    }
+
+    /// Give `quantity` of `item` to `player_id` in a transaction unless they
+    /// would carry more than `capacity` pounds
+    ///
+    /// # Errors
+    ///
+    /// Fails if the items would be too heavy to carry
+    pub async fn give(
+        tx: &mut Transaction<'_, Postgres>,
+        player_id: i32,
+        item: &'static Item,
+        quantity: u32,
+        capacity: u32,
+    ) -> Result<()> {
+        // lock the player so that concurrent additions see each other
+        sqlx::query!(
+            r#"
+SELECT id
+FROM player
+WHERE id = $1
+FOR UPDATE
+            "#,
+            player_id
+        )
+        .fetch_one(&mut *tx)
+        .await?;
+
+        let recs = sqlx::query!(
+            r#"
+SELECT key, quantity
+FROM item
+WHERE player_id = $1
+            "#,
+            player_id
+        )
+        .fetch_all(&mut *tx)
+        .await?;
+
+        // saturating keeps an overflowing inventory above every capacity
+        let carried = recs
+            .iter()
+            .filter_map(|rec| {
+                Some(
+                    known(&rec.key)?
+                        .weight
+                        .saturating_mul(u32::try_from(rec.quantity).ok()?),
+                )
+            })
+            .fold(0, u32::saturating_add);
+        let added = item
+            .weight
+            .checked_mul(quantity)
+            .with_context(|| format!("You cannot carry {}x {}", quantity, item.name))?;
+        ensure!(
+            carried.saturating_add(added) <= capacity,
+            "You cannot carry {} more pounds, you already carry {}/{} pounds",
+            added,
+            carried,
+            capacity
+        );
+
+        if item.stackable {
+            let stacked = sqlx::query!(
+                r#"
+UPDATE item
+SET quantity = quantity + $3
+WHERE player_id = $1 AND key = $2
+RETURNING id
+                "#,
+                player_id,
+                item.key,
+                i32::try_from(quantity)?,
+            )
+            .fetch_optional(&mut *tx)
+            .await?;
+
+            if stacked.is_none() {
+                sqlx::query!(
+                    r#"
+INSERT INTO item (player_id, key, quantity)
+VALUES ($1, $2, $3)
+                    "#,
+                    player_id,
+                    item.key,
+                    i32::try_from(quantity)?,
+                )
+                .execute(&mut *tx)
+                .await?;
+            }
+        } else {
+            for _ in 0..quantity {
+                Self::grant(tx, player_id, item, false).await?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Throw away up to `quantity` unequipped `item`s of `player_id`. Returns
+    /// how many were dropped.
+    ///
+    /// # Errors
+    ///
+    /// Fails if the player has no unequipped `item`
+    pub async fn drop(
+        pool: &PgPool,
+        player_id: i32,
+        item: &'static Item,
+        quantity: u32,
+    ) -> Result<u32> {
+        let mut tx = pool.begin().await?;
+        let dropped = Self::take(&mut tx, player_id, item, quantity).await?;
+        tx.commit().await?;
+
+        Ok(dropped)
+    }
+
+    /// Remove up to `quantity` unequipped `item`s of `player_id` in a
+    /// transaction. Returns how many were removed.
+    ///
+    /// # Errors
+    ///
+    /// Fails if the player has no unequipped `item`
+    pub async fn take(
+        tx: &mut Transaction<'_, Postgres>,
+        player_id: i32,
+        item: &'static Item,
+        quantity: u32,
+    ) -> Result<u32> {
+        let recs = sqlx::query!(
+            r#"
+SELECT id, quantity, slot IS NOT NULL as "equipped!"
+FROM item
+WHERE player_id = $1 AND key = $2
+ORDER BY id
+FOR UPDATE
+            "#,
+            player_id,
+            item.key
+        )
+        .fetch_all(&mut *tx)
+        .await?;
+
+        ensure!(!recs.is_empty(), "You don't have a {}", item.name);
+
+        let mut dropped = 0;
+        for rec in recs.iter().filter(|rec| !rec.equipped) {
+            if dropped == quantity {
+                break;
+            }
+
+            let owned = u32::try_from(rec.quantity)?;
+            let taken = std::cmp::min(owned, quantity - dropped);
+            if taken == owned {
+                sqlx::query!(
+                    r#"
+DELETE FROM item
+WHERE id = $1
+                    "#,
+                    rec.id
+                )
+                .execute(&mut *tx)
+                .await?;
+            } else {
+                sqlx::query!(
+                    r#"
+UPDATE item
+SET quantity = quantity - $2
+WHERE id = $1
+                    "#,
+                    rec.id,
+                    i32::try_from(taken)?,
+                )
+                .execute(&mut *tx)
+                .await?;
+            }
+            dropped += taken;
+        }
+
+        ensure!(
+            dropped > 0,
+            "Your {} is equipped, unequip it first",
+            item.name
+        );
+
+        Ok(dropped)
+    }
+
+    /// Equip an unequipped `item` of `player_id`. Returns the item that was
+    /// in the slot before.
+    ///
+    /// # Errors
+    ///
+    /// Fails if `item` cannot be equipped, the player does not have it or
+    /// it is already equipped
+    pub async fn equip(
+        pool: &PgPool,
+        player_id: i32,
+        item: &'static Item,
+    ) -> Result<Option<&'static Item>> {
+        let Some(slot) = item.slot else {
+            bail!("You cannot equip {}", item.name)
+        };
+
+        let mut tx = pool.begin().await?;
+
+        let owned = sqlx::query!(
+            r#"
+SELECT id, slot IS NOT NULL as "equipped!"
+FROM item
+WHERE player_id = $1 AND key = $2
+ORDER BY slot NULLS FIRST
+LIMIT 1
+FOR UPDATE
+            "#,
+            player_id,
+            item.key
+        )
+        .fetch_optional(&mut tx)
+        .await?;
+
+        let id = match owned {
+            None => bail!("You don't have a {}", item.name),
+            Some(owned) if owned.equipped => bail!("Your {} is already equipped", item.name),
+            Some(owned) => owned.id,
+        };
+
+        let replaced = sqlx::query!(
+            r#"
+UPDATE item
+SET slot = NULL
+WHERE player_id = $1 AND slot = $2
+RETURNING key
+            "#,
+            player_id,
+            slot as Slot,
+        )
+        .fetch_optional(&mut tx)
+        .await?;
+
+        sqlx::query!(
+            r#"
+UPDATE item
+SET slot = $2
+WHERE id = $1
+            "#,
+            id,
+            slot as Slot,
+        )
+        .execute(&mut tx)
+        .await?;
+
+        tx.commit().await?;
+
+        Ok(replaced.and_then(|rec| known(&rec.key)))
+    }
+
+    /// Take off the item in `slot` of `player_id` and return it
+    ///
+    /// # Errors
+    ///
+    /// Fails if the database cannot be queried
+    pub async fn unequip(
+        pool: &PgPool,
+        player_id: i32,
+        slot: Slot,
+    ) -> Result<Option<&'static Item>> {
+        let rec = sqlx::query!(
+            r#"
+UPDATE item
+SET slot = NULL
+WHERE player_id = $1 AND slot = $2
+RETURNING key
+            "#,
+            player_id,
+            slot as Slot,
+        )
+        .fetch_optional(pool)
+        .await?;
+
+        Ok(rec.and_then(|rec| known(&rec.key)))
+    }
+}
+
+/// The item for a key from the database. Items removed from the catalog are
+/// skipped.
+pub(super) fn known(key: &str) -> Option<&'static Item> {
+    let item = Item::by_key(key);
+    if item.is_none() {
+        warn!("unknown item in the database: {}", key);
+    }
+
+    item
+}
@@ -0,0 +1,151 @@
+use crate::item::Item;
+use anyhow::{bail, Result};
+use chrono::{DateTime, Utc};
+use sqlx::{PgPool, Postgres, Transaction};
+use std::fmt;
+
+/// Why the gold of a player changed
+#[derive(sqlx::Type, Debug, Clone, Copy, PartialEq, Eq)]
+#[sqlx(rename_all = "lowercase")]
+#[sqlx(rename = "gold_reason")]
+pub enum GoldReason {
+    /// Found during an encounter
+    Encounter,
+    /// Spent in a shop
+    Purchase,
+    /// Sold an item to a shop
+    Sale,
+    /// Given to or received from another player
+    Trade,
+}
+
+/// A change of the gold of a player
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LedgerEntry {
+    pub id: i32,
+    pub player_id: i32,
+    /// Negative for gold spent
+    pub amount: i32,
+    /// The gold after the change
+    pub balance: i32,
+    pub reason: GoldReason,
+    pub encounter_id: Option<i32>,
+    /// The key of the item bought or sold
+    pub item: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl LedgerEntry {
+    /// Change the gold of `player_id` by `amount` and record why. Returns the
+    /// new balance.
+    ///
+    /// # Errors
+    ///
+    /// Fails without a change if the player cannot afford it
+    pub async fn record(
+        tx: &mut Transaction<'_, Postgres>,
+        player_id: i32,
+        amount: i32,
+        reason: GoldReason,
+        encounter_id: Option<i32>,
+        item: Option<&Item>,
+    ) -> Result<i32> {
+        let rec = sqlx::query!(
+            r#"
+UPDATE player
+SET gold = gold + $2
+WHERE id = $1 AND gold + $2 >= 0
+RETURNING gold
+            "#,
+            player_id,
+            amount
+        )
+        .fetch_optional(&mut *tx)
+        .await?;
+
+        let balance = match rec {
+            Some(rec) => rec.gold,
+            None => bail!("You don't have {} gold", -amount),
+        };
+
+        sqlx::query!(
+            r#"
+INSERT INTO gold_ledger (player_id, amount, balance, reason, encounter_id, item)
+VALUES ($1, $2, $3, $4, $5, $6)
+            "#,
+            player_id,
+            amount,
+            balance,
+            reason as GoldReason,
+            encounter_id,
+            item.map(|item| item.key),
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        Ok(balance)
+    }
+
+    /// The last `limit` changes of the gold of `player_id`, newest first
+    ///
+    /// # Errors
+    ///
+    /// Fails if the database cannot be queried
+    pub async fn recent(pool: &PgPool, player_id: i32, limit: i64) -> Result<Vec<Self>> {
+        let entries = sqlx::query_as!(
+            Self,
+            r#"
+SELECT
+    id,
+    player_id,
+    amount,
+    balance,
+    reason as "reason: GoldReason",
+    encounter_id,
+    item,
+    created_at
+FROM gold_ledger
+WHERE player_id = $1
+ORDER BY created_at DESC, id DESC
+LIMIT $2
+            "#,
+            player_id,
+            limit
+        )
+        .fetch_all(pool)
+        .await?;
+
+        Ok(entries)
+    }
+}
+
+impl fmt::Display for GoldReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Encounter => "encounter",
+            Self::Purchase => "purchase",
+            Self::Sale => "sale",
+            Self::Trade => "trade",
+        })
+    }
+}
+
+impl fmt::Display for LedgerEntry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:+} gold ({}", self.amount, self.reason)?;
+
+        if let Some(id) = self.encounter_id {
+            write!(f, " #{}", id)?;
+        }
+        if let Some(item) = &self.item {
+            write!(f, " of {}", item)?;
+        }
+
+        write!(
+            f,
+            ") = {} at {}",
+            self.balance,
+            self.created_at.format("%F %R UTC")
+        )
+    }
+}
@@ -1,3 +1,21 @@
+// the query macros of sqlx bind their arguments to underscore prefixed names
+// and unwrap internally
+#![allow(clippy::used_underscore_binding, clippy::missing_panics_doc)]
+
+mod creation;
+mod encounter;
+mod item;
+mod ledger;
 mod player;
+mod roll_log;
+mod shop;
+mod trade;
 
+pub use creation::PendingCreation;
+pub use encounter::Encounter;
+pub use item::OwnedItem;
+pub use ledger::{GoldReason, LedgerEntry};
 pub use player::Player;
+pub use roll_log::LoggedRoll;
+pub use shop::Shop;
+pub use trade::Trade;
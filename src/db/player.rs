@@ -1,7 +1,16 @@
-use crate::character::{CharacterStats, Class, Race};
-use anyhow::Result;
-use chrono::{Duration, Utc};
-use sqlx::PgPool;
+use super::{GoldReason, LedgerEntry, LoggedRoll, OwnedItem};
+use crate::{
+    character::{
+        level::{self, ATTRIBUTE_POINTS_PER_LEVEL},
+        Ability, Buffs, CharacterStats, Class, Level, Race,
+    },
+    fight::{self, StatusKind},
+    item::Item,
+};
+use anyhow::{bail, ensure, Result};
+use chrono::{DateTime, Duration, Utc};
+use sqlx::{Done, PgPool, Postgres, Transaction};
+use std::{cmp, convert::TryFrom};
 
 pub struct Player<'a> {
     // user id
@@ -11,51 +20,315 @@ pub struct Player<'a> {
 }
 
 impl<'a> Player<'a> {
+    #[must_use]
     pub fn new(pool: &'a PgPool, id: i32) -> Self {
         Self { id, pool }
     }
 
-    pub async fn insert(&self) -> Result<()> {
+    /// Register the player with the `race` and `class` of their future
+    /// character. The character is created later with
+    /// [`Player::create_character`].
+    ///
+    /// # Errors
+    ///
+    /// Fails if the player is already registered
+    pub async fn insert(&self, race: Race, class: Class) -> Result<()> {
         sqlx::query!(
             r#"
-INSERT INTO player
-(
-    id,
-    strength,
-    dexterity,
-    constitution,
-    intelligence,
-    wisdom,
-    charisma,
-    luck,
-    has_character,
-    race,
-    class
-)
-VALUES (
-    $1,
-    $2,
-    $3,
-    $4,
-    $5,
-    $6,
-    $7,
-    $8,
-    true,
-    $9,
-    $10
-)
+INSERT INTO player (id, race, class)
+VALUES ($1, $2, $3)
+            "#,
+            self.id,
+            race as Race,
+            class as Class,
+        )
+        .execute(self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Give the player a character with `stats` and the starting equipment of
+    /// the class and forget any pending scores
+    ///
+    /// # Errors
+    ///
+    /// Fails if the player is not registered
+    pub async fn create_character(&self, stats: &CharacterStats) -> Result<()> {
+        let mut tx = self.pool.begin().await?;
+
+        sqlx::query!(
+            r#"
+UPDATE player
+SET
+    strength = $2,
+    dexterity = $3,
+    constitution = $4,
+    intelligence = $5,
+    wisdom = $6,
+    charisma = $7,
+    luck = $8,
+    has_character = true,
+    race = $9,
+    class = $10
+WHERE id = $1
+            "#,
+            self.id,
+            *stats.strength,
+            *stats.dexterity,
+            *stats.constitution,
+            *stats.intelligence,
+            *stats.wisdom,
+            *stats.charisma,
+            *stats.luck,
+            stats.race as Race,
+            stats.class as Class,
+        )
+        .execute(&mut tx)
+        .await?;
+
+        for item in stats.class.starting_equipment() {
+            OwnedItem::grant(&mut tx, self.id, item, true).await?;
+        }
+
+        sqlx::query!(
+            r#"
+DELETE FROM character_creation
+WHERE player_id = $1
+            "#,
+            self.id
+        )
+        .execute(&mut tx)
+        .await?;
+
+        tx.commit().await?;
+
+        Ok(())
+    }
+
+    /// Add `experience` points and update the level. Every level gained
+    /// grants attribute points. Returns the level before and after.
+    ///
+    /// # Errors
+    ///
+    /// Fails if the experience would overflow
+    pub async fn add_experience(&self, experience: u32) -> Result<(Level, Level)> {
+        let mut tx = self.pool.begin().await?;
+
+        let rec = sqlx::query!(
+            r#"
+UPDATE player
+SET experience = experience + $2
+WHERE id = $1
+RETURNING experience, level
+            "#,
+            self.id,
+            i32::try_from(experience)?,
+        )
+        .fetch_one(&mut tx)
+        .await?;
+
+        let old = Level::from(rec.level);
+        let new = Level::from_experience(u32::try_from(rec.experience)?);
+
+        if new != old {
+            sqlx::query!(
+                r#"
+UPDATE player
+SET
+    level = $2,
+    attribute_points = attribute_points + $3
+WHERE id = $1
+                "#,
+                self.id,
+                i16::from(new.get()),
+                i16::from(new.get() - old.get()) * i16::try_from(ATTRIBUTE_POINTS_PER_LEVEL)?,
+            )
+            .execute(&mut tx)
+            .await?;
+        }
+
+        tx.commit().await?;
+
+        Ok((old, new))
+    }
+
+    /// The attribute points that can be spent with [`Player::train`]
+    ///
+    /// # Errors
+    ///
+    /// Fails if the player is not registered
+    pub async fn attribute_points(&self) -> Result<u16> {
+        let rec = sqlx::query!(
+            r#"
+SELECT attribute_points
+FROM player
+WHERE id = $1
+            "#,
+            self.id
+        )
+        .fetch_one(self.pool)
+        .await?;
+
+        Ok(u16::try_from(rec.attribute_points)?)
+    }
+
+    /// Spend `points` attribute points on `ability`. Returns the new stats
+    /// and the points left.
+    ///
+    /// # Errors
+    ///
+    /// Fails if the points are not available or `ability` cannot be trained
+    /// that far, see [`level::train`]
+    pub async fn train(&self, ability: Ability, points: u16) -> Result<(CharacterStats, u16)> {
+        let mut tx = self.pool.begin().await?;
+
+        // lock the row so that points cannot be spent twice
+        let rec = sqlx::query!(
+            r#"
+SELECT attribute_points
+FROM player
+WHERE id = $1
+FOR UPDATE
+            "#,
+            self.id
+        )
+        .fetch_one(&mut tx)
+        .await?;
+        let available = u16::try_from(rec.attribute_points)?;
+
+        let mut stats = self.get_stats().await?;
+        level::train(&mut stats, ability, points, available)?;
+
+        sqlx::query!(
+            r#"
+UPDATE player
+SET
+    strength = $2,
+    dexterity = $3,
+    constitution = $4,
+    intelligence = $5,
+    wisdom = $6,
+    charisma = $7,
+    attribute_points = attribute_points - $8
+WHERE id = $1
             "#,
             self.id,
-            1,
-            1,
-            1,
-            1,
-            1,
-            1,
-            1,
-            Race::default() as Race,
-            Class::default() as Class,
+            *stats.strength,
+            *stats.dexterity,
+            *stats.constitution,
+            *stats.intelligence,
+            *stats.wisdom,
+            *stats.charisma,
+            i16::try_from(points)?,
+        )
+        .execute(&mut tx)
+        .await?;
+
+        tx.commit().await?;
+
+        Ok((stats, available - points))
+    }
+
+    /// The experience points collected so far
+    ///
+    /// # Errors
+    ///
+    /// Fails if the player is not registered
+    pub async fn experience(&self) -> Result<u32> {
+        let rec = sqlx::query!(
+            r#"
+SELECT experience
+FROM player
+WHERE id = $1
+            "#,
+            self.id
+        )
+        .fetch_one(self.pool)
+        .await?;
+
+        Ok(u32::try_from(rec.experience)?)
+    }
+
+    /// The race and class chosen on registration
+    ///
+    /// # Errors
+    ///
+    /// Fails if the player is not registered
+    pub async fn race_and_class(&self) -> Result<(Race, Class)> {
+        let rec = sqlx::query!(
+            r#"
+SELECT race as "race: Race", class as "class: Class"
+FROM player
+WHERE id = $1
+            "#,
+            self.id
+        )
+        .fetch_one(self.pool)
+        .await?;
+
+        Ok((rec.race.unwrap_or_default(), rec.class.unwrap_or_default()))
+    }
+
+    /// Whether the player created their character
+    ///
+    /// # Errors
+    ///
+    /// Fails if the player is not registered
+    pub async fn has_character(&self) -> Result<bool> {
+        let rec = sqlx::query!(
+            r#"
+SELECT has_character
+FROM player
+WHERE id = $1
+            "#,
+            self.id
+        )
+        .fetch_one(self.pool)
+        .await?;
+
+        Ok(rec.has_character)
+    }
+
+    /// The player last seen with the login `name`
+    ///
+    /// # Errors
+    ///
+    /// Fails if the database cannot be queried
+    pub async fn find_by_name(pool: &'a PgPool, name: &str) -> Result<Option<Player<'a>>> {
+        let rec = sqlx::query!(
+            r#"
+SELECT id
+FROM player
+WHERE name = $1
+            "#,
+            name.trim_start_matches('@').to_ascii_lowercase()
+        )
+        .fetch_optional(pool)
+        .await?;
+
+        Ok(rec.map(|rec| Self::new(pool, rec.id)))
+    }
+
+    #[must_use]
+    pub fn id(&self) -> i32 {
+        self.id
+    }
+
+    /// Remember the current login of the player
+    ///
+    /// # Errors
+    ///
+    /// Fails if the database cannot be queried
+    pub async fn set_name(&self, name: &str) -> Result<()> {
+        sqlx::query!(
+            r#"
+UPDATE player
+SET name = $2
+WHERE id = $1
+            "#,
+            self.id,
+            name.to_ascii_lowercase()
         )
         .execute(self.pool)
         .await?;
@@ -63,6 +336,69 @@ VALUES (
         Ok(())
     }
 
+    /// The last `limit` rolls of the player, newest first
+    ///
+    /// # Errors
+    ///
+    /// Fails if the database cannot be queried
+    pub async fn recent_rolls(&self, limit: i64) -> Result<Vec<LoggedRoll>> {
+        LoggedRoll::recent(self.pool, self.id, limit).await
+    }
+
+    /// The last `limit` changes of the gold of the player, newest first
+    ///
+    /// # Errors
+    ///
+    /// Fails if the database cannot be queried
+    pub async fn recent_ledger(&self, limit: i64) -> Result<Vec<LedgerEntry>> {
+        LedgerEntry::recent(self.pool, self.id, limit).await
+    }
+
+    /// The gold the player owns
+    ///
+    /// # Errors
+    ///
+    /// Fails if the player is not registered
+    pub async fn gold(&self) -> Result<i32> {
+        let rec = sqlx::query!(
+            r#"
+SELECT gold
+FROM player
+WHERE id = $1
+            "#,
+            self.id
+        )
+        .fetch_one(self.pool)
+        .await?;
+
+        Ok(rec.gold)
+    }
+
+    /// Change the gold by `amount` and record it in the ledger. Returns the
+    /// new balance.
+    ///
+    /// # Errors
+    ///
+    /// Fails if the player cannot afford to pay `-amount`
+    pub async fn add_gold(
+        &self,
+        amount: i32,
+        reason: GoldReason,
+        encounter_id: Option<i32>,
+    ) -> Result<i32> {
+        let mut tx = self.pool.begin().await?;
+        let balance =
+            LedgerEntry::record(&mut tx, self.id, amount, reason, encounter_id, None).await?;
+        tx.commit().await?;
+
+        Ok(balance)
+    }
+
+    /// Whether the player is registered
+    ///
+    /// # Errors
+    ///
+    /// Fails if the database cannot be queried
     pub async fn exists(&self) -> Result<bool> {
         let rec = sqlx::query!(
             r#"
@@ -81,6 +417,11 @@ AS "exists"
         Ok(rec.exists.unwrap_or(false))
     }
 
+    /// Remove the player with their character, items and history
+    ///
+    /// # Errors
+    ///
+    /// Fails if the database cannot be queried
     pub async fn delete(&self) -> Result<()> {
         sqlx::query!(
             r#"
@@ -95,6 +436,12 @@ WHERE id = $1
         Ok(())
     }
 
+    /// The time until the player can enter the dungeon again, `None` if they
+    /// can enter now or have no character
+    ///
+    /// # Errors
+    ///
+    /// Fails if the player is not registered
     pub async fn can_enter(&self) -> Result<Option<Duration>> {
         let rec = sqlx::query!(
             r#"
@@ -124,6 +471,11 @@ WHERE id = $1
         }
     }
 
+    /// The stats of the character with their equipment and buffs
+    ///
+    /// # Errors
+    ///
+    /// Fails if the player is not registered
     pub async fn get_stats(&self) -> Result<CharacterStats> {
         let rec = sqlx::query!(
             r#"
@@ -134,7 +486,10 @@ SELECT
     intelligence as "intelligence!",
     wisdom  as "wisdom!",
     charisma as "charisma!",
-    luck as "luck!"
+    luck as "luck!",
+    level,
+    race as "race: Race",
+    class as "class: Class"
 FROM player
 WHERE id = $1
             "#,
@@ -143,16 +498,372 @@ WHERE id = $1
         .fetch_one(self.pool)
         .await?;
 
-        todo!()
+        Ok(CharacterStats {
+            strength: rec.strength.into(),
+            dexterity: rec.dexterity.into(),
+            constitution: rec.constitution.into(),
+            intelligence: rec.intelligence.into(),
+            wisdom: rec.wisdom.into(),
+            charisma: rec.charisma.into(),
+            luck: rec.luck.into(),
+            race: rec.race.unwrap_or_default(),
+            class: rec.class.unwrap_or_default(),
+            level: rec.level.into(),
+            equipment: OwnedItem::equipment(self.pool, self.id).await?,
+            buffs: self.buffs().await?,
+        })
+    }
+
+    /// The sum of the active buffs
+    ///
+    /// # Errors
+    ///
+    /// Fails if the database cannot be queried
+    pub async fn buffs(&self) -> Result<Buffs> {
+        let recs = sqlx::query!(
+            r#"
+SELECT ability as "ability: Ability", bonus
+FROM buff
+WHERE player_id = $1
+            "#,
+            self.id
+        )
+        .fetch_all(self.pool)
+        .await?;
+
+        let mut buffs = Buffs::default();
+        for rec in recs {
+            buffs.add(rec.ability, rec.bonus);
+        }
+
+        Ok(buffs)
+    }
+
+    /// The status effects with the encounters they last for, oldest first
+    ///
+    /// # Errors
+    ///
+    /// Fails if the database cannot be queried
+    pub async fn statuses(&self) -> Result<Vec<(StatusKind, u16)>> {
+        let recs = sqlx::query!(
+            r#"
+SELECT kind as "kind: StatusKind", encounters
+FROM status_effect
+WHERE player_id = $1
+ORDER BY created_at
+            "#,
+            self.id
+        )
+        .fetch_all(self.pool)
+        .await?;
+
+        recs.into_iter()
+            .map(|rec| Ok((rec.kind, u16::try_from(rec.encounters)?)))
+            .collect()
+    }
+
+    /// The current health, after resting since it was stored
+    ///
+    /// # Errors
+    ///
+    /// Fails if the player is not registered
+    pub async fn health(&self, max_health: i64) -> Result<i64> {
+        let rec = sqlx::query!(
+            r#"
+SELECT health, health_updated_at
+FROM player
+WHERE id = $1
+            "#,
+            self.id
+        )
+        .fetch_one(self.pool)
+        .await?;
+
+        Ok(rested(rec.health, rec.health_updated_at, max_health))
+    }
+
+    /// Store the health after an encounter, count down the buffs and status
+    /// effects and add the `lingering` ones for the next encounter
+    ///
+    /// # Errors
+    ///
+    /// Fails if the health or cooldown are out of range for the database
+    pub async fn end_encounter(
+        &self,
+        health: i64,
+        cooldown: Duration,
+        lingering: &[StatusKind],
+    ) -> Result<()> {
+        let mut tx = self.pool.begin().await?;
+
+        sqlx::query!(
+            r#"
+UPDATE player
+SET
+    health = $2,
+    health_updated_at = now(),
+    dungeon_cooldown = now() + make_interval(secs => $3)
+WHERE id = $1
+            "#,
+            self.id,
+            i32::try_from(health)?,
+            f64::from(i32::try_from(cooldown.num_seconds())?),
+        )
+        .execute(&mut tx)
+        .await?;
+
+        sqlx::query!(
+            r#"
+DELETE FROM buff
+WHERE player_id = $1 AND encounters <= 1
+            "#,
+            self.id
+        )
+        .execute(&mut tx)
+        .await?;
+
+        sqlx::query!(
+            r#"
+UPDATE buff
+SET encounters = encounters - 1
+WHERE player_id = $1
+            "#,
+            self.id
+        )
+        .execute(&mut tx)
+        .await?;
+
+        sqlx::query!(
+            r#"
+DELETE FROM status_effect
+WHERE player_id = $1 AND encounters <= 1
+            "#,
+            self.id
+        )
+        .execute(&mut tx)
+        .await?;
+
+        sqlx::query!(
+            r#"
+UPDATE status_effect
+SET encounters = encounters - 1
+WHERE player_id = $1
+            "#,
+            self.id
+        )
+        .execute(&mut tx)
+        .await?;
+
+        for kind in lingering {
+            add_status(&mut tx, self.id, *kind, 1).await?;
+        }
+
+        tx.commit().await?;
+
+        Ok(())
+    }
+
+    /// Use up `item` to restore `healed` health. Returns the new health.
+    ///
+    /// # Errors
+    ///
+    /// Fails if the player has no `item` or is already at full health
+    pub async fn heal(&self, item: &'static Item, healed: i64, max_health: i64) -> Result<i64> {
+        let mut tx = self.pool.begin().await?;
+        OwnedItem::take(&mut tx, self.id, item, 1).await?;
+
+        let rec = sqlx::query!(
+            r#"
+SELECT health, health_updated_at
+FROM player
+WHERE id = $1
+FOR UPDATE
+            "#,
+            self.id
+        )
+        .fetch_one(&mut tx)
+        .await?;
+
+        let health = rested(rec.health, rec.health_updated_at, max_health);
+        ensure!(health < max_health, "You are already at full health");
+        let health = cmp::min(health + cmp::max(healed, 0), max_health);
+
+        sqlx::query!(
+            r#"
+UPDATE player
+SET health = $2, health_updated_at = now()
+WHERE id = $1
+            "#,
+            self.id,
+            i32::try_from(health)?,
+        )
+        .execute(&mut tx)
+        .await?;
+
+        tx.commit().await?;
+
+        Ok(health)
+    }
+
+    /// Use up `item` to add a buff for a number of `encounters`
+    ///
+    /// # Errors
+    ///
+    /// Fails if the player has no `item`
+    pub async fn buff(
+        &self,
+        item: &'static Item,
+        ability: Ability,
+        bonus: i16,
+        encounters: u16,
+    ) -> Result<()> {
+        let mut tx = self.pool.begin().await?;
+        OwnedItem::take(&mut tx, self.id, item, 1).await?;
+
+        sqlx::query!(
+            r#"
+INSERT INTO buff (player_id, ability, bonus, encounters)
+VALUES ($1, $2, $3, $4)
+            "#,
+            self.id,
+            ability as Ability,
+            bonus,
+            i16::try_from(encounters)?,
+        )
+        .execute(&mut tx)
+        .await?;
+
+        tx.commit().await?;
+
+        Ok(())
+    }
+
+    /// Use up `item` to start a status effect for a number of `encounters`.
+    /// An active effect of the same kind lasts for the longer duration.
+    ///
+    /// # Errors
+    ///
+    /// Fails if the player has no `item`
+    pub async fn start_status(
+        &self,
+        item: &'static Item,
+        kind: StatusKind,
+        encounters: u16,
+    ) -> Result<()> {
+        let mut tx = self.pool.begin().await?;
+        OwnedItem::take(&mut tx, self.id, item, 1).await?;
+        add_status(&mut tx, self.id, kind, encounters).await?;
+        tx.commit().await?;
+
+        Ok(())
+    }
+
+    /// Use up `item` to shorten the dungeon cooldown. Returns the time left.
+    ///
+    /// # Errors
+    ///
+    /// Fails if the player has no `item` or can already enter the dungeon
+    pub async fn reduce_cooldown(&self, item: &'static Item, by: Duration) -> Result<Duration> {
+        let mut tx = self.pool.begin().await?;
+        OwnedItem::take(&mut tx, self.id, item, 1).await?;
+
+        let rec = sqlx::query!(
+            r#"
+UPDATE player
+SET dungeon_cooldown = dungeon_cooldown - make_interval(secs => $2)
+WHERE id = $1 AND dungeon_cooldown > now()
+RETURNING dungeon_cooldown as "dungeon_cooldown!"
+            "#,
+            self.id,
+            f64::from(i32::try_from(by.num_seconds())?),
+        )
+        .fetch_optional(&mut tx)
+        .await?;
+
+        let left = match rec {
+            Some(rec) => cmp::max(rec.dungeon_cooldown - Utc::now(), Duration::zero()),
+            None => bail!("You can already enter the dungeon"),
+        };
+
+        tx.commit().await?;
+
+        Ok(left)
+    }
+
+    /// Use up `item` to remove every harmful buff and status effect. Returns
+    /// how many were removed.
+    ///
+    /// # Errors
+    ///
+    /// Fails if the player has no `item`
+    pub async fn cleanse(&self, item: &'static Item) -> Result<u64> {
+        let mut tx = self.pool.begin().await?;
+        OwnedItem::take(&mut tx, self.id, item, 1).await?;
+
+        let mut removed = sqlx::query!(
+            r#"
+DELETE FROM buff
+WHERE player_id = $1 AND bonus < 0
+            "#,
+            self.id
+        )
+        .execute(&mut tx)
+        .await?
+        .rows_affected();
+
+        for kind in StatusKind::ALL.iter().filter(|kind| kind.is_harmful()) {
+            removed += sqlx::query!(
+                r#"
+DELETE FROM status_effect
+WHERE player_id = $1 AND kind = $2
+                "#,
+                self.id,
+                *kind as StatusKind,
+            )
+            .execute(&mut tx)
+            .await?
+            .rows_affected();
+        }
+
+        tx.commit().await?;
+
+        Ok(removed)
+    }
+}
+
+/// Start a status effect or extend an active one to last for `encounters`
+async fn add_status(
+    tx: &mut Transaction<'_, Postgres>,
+    player_id: i32,
+    kind: StatusKind,
+    encounters: u16,
+) -> Result<()> {
+    sqlx::query!(
+        r#"
+INSERT INTO status_effect (player_id, kind, encounters)
+VALUES ($1, $2, $3)
+ON CONFLICT (player_id, kind) DO UPDATE
+SET encounters = GREATEST(status_effect.encounters, EXCLUDED.encounters)
+        "#,
+        player_id,
+        kind as StatusKind,
+        i16::try_from(encounters)?,
+    )
+    .execute(&mut *tx)
+    .await?;
+
+    Ok(())
+}
 
-        //Ok(CharacterStats {
-        //    strength: rec.strength,
-        //    dexterity: rec.dexterity,
-        //    constitution: rec.constitution,
-        //    intelligence: rec.intelligence,
-        //    wisdom: rec.wisdom,
-        //    charisma: rec.charisma,
-        //    luck: rec.luck,
-        //})
+/// The health stored at `updated_at` after resting until now. Missing health
+/// is full health.
+fn rested(health: Option<i32>, updated_at: Option<DateTime<Utc>>, max_health: i64) -> i64 {
+    match (health, updated_at) {
+        (Some(health), Some(updated_at)) => fight::rest(
+            health.into(),
+            max_health,
+            (Utc::now() - updated_at).num_minutes(),
+        ),
+        _ => max_health,
     }
 }
@@ -0,0 +1,125 @@
+use crate::PoolRoll;
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use sqlx::PgPool;
+use std::fmt;
+
+/// A roll made during a game action
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LoggedRoll {
+    pub id: i32,
+    pub player_id: i32,
+    pub encounter_id: Option<i32>,
+    /// The number and kind of dice, e.g. `4d6`
+    pub dice: String,
+    /// The modifiers in dice notation, e.g. `advkh3`
+    pub modifiers: String,
+    pub rolls: Vec<i16>,
+    pub result: i64,
+    pub created_at: DateTime<Utc>,
+}
+
+impl LoggedRoll {
+    /// Record every dice pool rolled for `player_id`, optionally during an
+    /// encounter
+    ///
+    /// # Errors
+    ///
+    /// Fails if the player or the encounter does not exist
+    pub async fn insert(
+        pool: &PgPool,
+        player_id: i32,
+        encounter_id: Option<i32>,
+        rolls: &[PoolRoll],
+    ) -> Result<()> {
+        let mut tx = pool.begin().await?;
+
+        for roll in rolls {
+            sqlx::query!(
+                r#"
+INSERT INTO roll_log (player_id, encounter_id, dice, modifiers, rolls, result)
+VALUES ($1, $2, $3, $4, $5, $6)
+                "#,
+                player_id,
+                encounter_id,
+                format!("{}{}", roll.pool.count, roll.pool.die),
+                roll.pool.modifiers.to_string(),
+                &roll.rolls[..],
+                roll.total,
+            )
+            .execute(&mut tx)
+            .await?;
+        }
+
+        tx.commit().await?;
+
+        Ok(())
+    }
+
+    /// The last `limit` rolls of `player_id`, newest first
+    ///
+    /// # Errors
+    ///
+    /// Fails if the database cannot be queried
+    pub async fn recent(pool: &PgPool, player_id: i32, limit: i64) -> Result<Vec<Self>> {
+        let rolls = sqlx::query_as!(
+            Self,
+            r#"
+SELECT id, player_id, encounter_id, dice, modifiers, rolls, result, created_at
+FROM roll_log
+WHERE player_id = $1
+ORDER BY created_at DESC, id DESC
+LIMIT $2
+            "#,
+            player_id,
+            limit
+        )
+        .fetch_all(pool)
+        .await?;
+
+        Ok(rolls)
+    }
+
+    /// Every roll of an encounter in the order they were made
+    ///
+    /// # Errors
+    ///
+    /// Fails if the database cannot be queried
+    pub async fn of_encounter(pool: &PgPool, encounter_id: i32) -> Result<Vec<Self>> {
+        let rolls = sqlx::query_as!(
+            Self,
+            r#"
+SELECT id, player_id, encounter_id, dice, modifiers, rolls, result, created_at
+FROM roll_log
+WHERE encounter_id = $1
+ORDER BY id
+            "#,
+            encounter_id
+        )
+        .fetch_all(pool)
+        .await?;
+
+        Ok(rolls)
+    }
+}
+
+impl fmt::Display for LoggedRoll {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let rolls: Vec<String> = self.rolls.iter().map(ToString::to_string).collect();
+
+        write!(
+            f,
+            "{}{} [{}] = {}",
+            self.dice,
+            self.modifiers,
+            rolls.join(", "),
+            self.result
+        )?;
+
+        if let Some(id) = self.encounter_id {
+            write!(f, " #{}", id)?;
+        }
+
+        write!(f, " at {}", self.created_at.format("%F %R UTC"))
+    }
+}
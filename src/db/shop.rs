@@ -0,0 +1,186 @@
+use super::{GoldReason, LedgerEntry, OwnedItem};
+use crate::item::{
+    shop::{self, Offer},
+    Item,
+};
+use anyhow::{bail, Result};
+use chrono::NaiveDate;
+use sqlx::{PgPool, Postgres, Transaction};
+use std::convert::TryFrom;
+
+/// The shop of a channel
+pub struct Shop<'a> {
+    channel: &'a str,
+    pool: &'a PgPool,
+}
+
+impl<'a> Shop<'a> {
+    #[must_use]
+    pub fn new(pool: &'a PgPool, channel: &'a str) -> Self {
+        Self { channel, pool }
+    }
+
+    /// What is left of the stock on `day`
+    ///
+    /// # Errors
+    ///
+    /// Fails if the shop cannot be stocked
+    pub async fn stock(&self, day: NaiveDate) -> Result<Vec<Offer>> {
+        let mut tx = self.pool.begin().await?;
+        self.open(&mut tx, day).await?;
+
+        let recs = sqlx::query!(
+            r#"
+SELECT key, quantity
+FROM shop_stock
+WHERE channel = $1 AND day = $2
+            "#,
+            self.channel,
+            day
+        )
+        .fetch_all(&mut tx)
+        .await?;
+
+        tx.commit().await?;
+
+        // keep the order of the generated stock
+        Ok(shop::daily_stock(self.channel, day)
+            .into_iter()
+            .filter_map(|offer| {
+                let rec = recs.iter().find(|rec| rec.key == offer.item.key)?;
+
+                Some(Offer {
+                    quantity: u32::try_from(rec.quantity).ok()?,
+                    ..offer
+                })
+            })
+            .collect())
+    }
+
+    /// Buy `quantity` of `item` for `price` each on `day`. The stock, the gold
+    /// and the inventory change together or not at all. Returns the new
+    /// balance.
+    ///
+    /// # Errors
+    ///
+    /// Fails if the shop does not have enough of `item`, the player cannot
+    /// afford it or carry it
+    pub async fn buy(
+        &self,
+        player_id: i32,
+        item: &'static Item,
+        quantity: u32,
+        price: u32,
+        capacity: u32,
+        day: NaiveDate,
+    ) -> Result<i32> {
+        let mut tx = self.pool.begin().await?;
+        self.open(&mut tx, day).await?;
+
+        // the row stays locked until the purchase is done
+        let rec = sqlx::query!(
+            r#"
+SELECT quantity
+FROM shop_stock
+WHERE channel = $1 AND day = $2 AND key = $3
+FOR UPDATE
+            "#,
+            self.channel,
+            day,
+            item.key
+        )
+        .fetch_optional(&mut tx)
+        .await?;
+
+        match rec.map(|rec| rec.quantity) {
+            None => bail!("The shop doesn't sell a {} today", item.name),
+            Some(0) => bail!("The {} is sold out", item.name),
+            Some(left) if i64::from(left) < i64::from(quantity) => {
+                bail!("The shop only has {}x {} left", left, item.name)
+            }
+            Some(_) => {}
+        }
+
+        sqlx::query!(
+            r#"
+UPDATE shop_stock
+SET quantity = quantity - $4
+WHERE channel = $1 AND day = $2 AND key = $3
+            "#,
+            self.channel,
+            day,
+            item.key,
+            i32::try_from(quantity)?,
+        )
+        .execute(&mut tx)
+        .await?;
+
+        let cost = i32::try_from(price * quantity)?;
+        let balance = LedgerEntry::record(
+            &mut tx,
+            player_id,
+            -cost,
+            GoldReason::Purchase,
+            None,
+            Some(item),
+        )
+        .await?;
+        OwnedItem::give(&mut tx, player_id, item, quantity, capacity).await?;
+
+        tx.commit().await?;
+
+        Ok(balance)
+    }
+
+    /// Sell up to `quantity` unequipped `item`s for `price` each. Returns how
+    /// many were sold and the new balance.
+    ///
+    /// # Errors
+    ///
+    /// Fails if the player has no unequipped `item`
+    pub async fn sell(
+        &self,
+        player_id: i32,
+        item: &'static Item,
+        quantity: u32,
+        price: u32,
+    ) -> Result<(u32, i32)> {
+        let mut tx = self.pool.begin().await?;
+
+        let sold = OwnedItem::take(&mut tx, player_id, item, quantity).await?;
+        let balance = LedgerEntry::record(
+            &mut tx,
+            player_id,
+            i32::try_from(price * sold)?,
+            GoldReason::Sale,
+            None,
+            Some(item),
+        )
+        .await?;
+
+        tx.commit().await?;
+
+        Ok((sold, balance))
+    }
+
+    /// Stock the shop for `day` unless it already is
+    async fn open(&self, tx: &mut Transaction<'_, Postgres>, day: NaiveDate) -> Result<()> {
+        for offer in shop::daily_stock(self.channel, day) {
+            sqlx::query!(
+                r#"
+INSERT INTO shop_stock (channel, day, key, quantity)
+VALUES ($1, $2, $3, $4)
+ON CONFLICT DO NOTHING
+                "#,
+                self.channel,
+                day,
+                offer.item.key,
+                i32::try_from(offer.quantity)?,
+            )
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,404 @@
+use super::{item::known, GoldReason, LedgerEntry, OwnedItem};
+use crate::trade::Bundle;
+use anyhow::{bail, ensure, Result};
+use chrono::{DateTime, Duration, Utc};
+use sqlx::{PgPool, Postgres, Transaction};
+use std::convert::TryFrom;
+
+/// A trade both players still have to accept
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Trade {
+    pub id: i32,
+    /// The player who proposed the trade
+    pub from_id: i32,
+    pub to_id: i32,
+    /// What the proposing player gives
+    pub offer: Bundle,
+    /// What the other player gives
+    pub request: Bundle,
+    pub from_accepted: bool,
+    pub to_accepted: bool,
+    pub expires_at: DateTime<Utc>,
+}
+
+impl Trade {
+    /// Propose to give `offer` to `to_id` for `request`. Both players need to
+    /// have everything they give, without equipped items, and neither may
+    /// have another pending trade. Returns the id of the trade.
+    ///
+    /// # Errors
+    ///
+    /// Fails if either player does not have what they give or already has a
+    /// pending trade
+    pub async fn propose(
+        pool: &PgPool,
+        from_id: i32,
+        to_id: i32,
+        offer: &Bundle,
+        request: &Bundle,
+        timeout: Duration,
+    ) -> Result<i32> {
+        ensure!(from_id != to_id, "You cannot trade with yourself");
+        ensure!(
+            !offer.is_empty() || !request.is_empty(),
+            "You have to trade something"
+        );
+
+        let mut tx = pool.begin().await?;
+
+        sqlx::query!(
+            r#"
+DELETE FROM trade
+WHERE expires_at <= now()
+            "#
+        )
+        .execute(&mut tx)
+        .await?;
+
+        // lock both players so that concurrent trades see each other, in the
+        // same order everywhere to avoid deadlocks
+        sqlx::query!(
+            r#"
+SELECT id
+FROM player
+WHERE id = $1 OR id = $2
+ORDER BY id
+FOR UPDATE
+            "#,
+            from_id,
+            to_id
+        )
+        .fetch_all(&mut tx)
+        .await?;
+
+        for (player_id, subject) in &[(from_id, "You"), (to_id, "They")] {
+            let rec = sqlx::query!(
+                r#"
+SELECT count(*) as "count!"
+FROM trade
+WHERE from_id = $1 OR to_id = $1
+                "#,
+                player_id
+            )
+            .fetch_one(&mut tx)
+            .await?;
+
+            ensure!(
+                rec.count == 0,
+                "{} already have a pending trade, accept or decline it first",
+                subject
+            );
+        }
+
+        ensure_available(&mut tx, from_id, offer, "You don't").await?;
+        ensure_available(&mut tx, to_id, request, "They don't").await?;
+
+        let rec = sqlx::query!(
+            r#"
+INSERT INTO trade (from_id, to_id, from_gold, to_gold, expires_at)
+VALUES ($1, $2, $3, $4, now() + make_interval(secs => $5))
+RETURNING id
+            "#,
+            from_id,
+            to_id,
+            i32::try_from(offer.gold)?,
+            i32::try_from(request.gold)?,
+            f64::from(i32::try_from(timeout.num_seconds())?),
+        )
+        .fetch_one(&mut tx)
+        .await?;
+
+        for (player_id, bundle) in &[(from_id, offer), (to_id, request)] {
+            for (item, quantity) in &bundle.items {
+                sqlx::query!(
+                    r#"
+INSERT INTO trade_item (trade_id, player_id, key, quantity)
+VALUES ($1, $2, $3, $4)
+                    "#,
+                    rec.id,
+                    player_id,
+                    item.key,
+                    i32::try_from(*quantity)?,
+                )
+                .execute(&mut tx)
+                .await?;
+            }
+        }
+
+        tx.commit().await?;
+
+        Ok(rec.id)
+    }
+
+    /// The pending trade of `player_id`
+    ///
+    /// # Errors
+    ///
+    /// Fails if the database cannot be queried
+    pub async fn pending(pool: &PgPool, player_id: i32) -> Result<Option<Self>> {
+        let mut tx = pool.begin().await?;
+
+        let rec = sqlx::query!(
+            r#"
+SELECT id
+FROM trade
+WHERE (from_id = $1 OR to_id = $1) AND expires_at > now()
+            "#,
+            player_id
+        )
+        .fetch_optional(&mut tx)
+        .await?;
+
+        let trade = match rec {
+            Some(rec) => Some(Self::load(&mut tx, rec.id).await?),
+            None => None,
+        };
+
+        tx.commit().await?;
+
+        Ok(trade)
+    }
+
+    /// Accept the trade as `player_id`. Once both players accepted, the items
+    /// and gold change hands together or not at all. `from_capacity` and
+    /// `to_capacity` are how much the players can carry. Returns whether the
+    /// trade is done.
+    ///
+    /// # Errors
+    ///
+    /// Fails if the trade expired or the players no longer have what they
+    /// give or cannot carry what they get
+    pub async fn accept(
+        &self,
+        pool: &PgPool,
+        player_id: i32,
+        from_capacity: u32,
+        to_capacity: u32,
+    ) -> Result<bool> {
+        let mut tx = pool.begin().await?;
+
+        // lock the trade so that it is done only once
+        let rec = sqlx::query!(
+            r#"
+UPDATE trade
+SET
+    from_accepted = from_accepted OR from_id = $2,
+    to_accepted = to_accepted OR to_id = $2
+WHERE id = $1 AND expires_at > now()
+RETURNING from_accepted, to_accepted
+            "#,
+            self.id,
+            player_id
+        )
+        .fetch_optional(&mut tx)
+        .await?;
+
+        let done = match rec {
+            None => bail!("The trade expired"),
+            Some(rec) => rec.from_accepted && rec.to_accepted,
+        };
+
+        if done {
+            self.exchange(&mut tx, from_capacity, to_capacity).await?;
+
+            sqlx::query!(
+                r#"
+DELETE FROM trade
+WHERE id = $1
+                "#,
+                self.id
+            )
+            .execute(&mut tx)
+            .await?;
+        }
+
+        tx.commit().await?;
+
+        Ok(done)
+    }
+
+    /// Call off the trade
+    ///
+    /// # Errors
+    ///
+    /// Fails if the database cannot be queried
+    pub async fn cancel(&self, pool: &PgPool) -> Result<()> {
+        sqlx::query!(
+            r#"
+DELETE FROM trade
+WHERE id = $1
+            "#,
+            self.id
+        )
+        .execute(pool)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn load(tx: &mut Transaction<'_, Postgres>, id: i32) -> Result<Self> {
+        let rec = sqlx::query!(
+            r#"
+SELECT from_id, to_id, from_gold, to_gold, from_accepted, to_accepted, expires_at
+FROM trade
+WHERE id = $1
+            "#,
+            id
+        )
+        .fetch_one(&mut *tx)
+        .await?;
+
+        let mut offer = Bundle {
+            gold: u32::try_from(rec.from_gold)?,
+            items: Vec::new(),
+        };
+        let mut request = Bundle {
+            gold: u32::try_from(rec.to_gold)?,
+            items: Vec::new(),
+        };
+
+        let items = sqlx::query!(
+            r#"
+SELECT player_id, key, quantity
+FROM trade_item
+WHERE trade_id = $1
+            "#,
+            id
+        )
+        .fetch_all(&mut *tx)
+        .await?;
+
+        for rec_item in items {
+            let Some(item) = known(&rec_item.key) else {
+                continue;
+            };
+            let bundle = if rec_item.player_id == rec.from_id {
+                &mut offer
+            } else {
+                &mut request
+            };
+            bundle.add(item, u32::try_from(rec_item.quantity)?)?;
+        }
+
+        Ok(Self {
+            id,
+            from_id: rec.from_id,
+            to_id: rec.to_id,
+            offer,
+            request,
+            from_accepted: rec.from_accepted,
+            to_accepted: rec.to_accepted,
+            expires_at: rec.expires_at,
+        })
+    }
+
+    async fn exchange(
+        &self,
+        tx: &mut Transaction<'_, Postgres>,
+        from_capacity: u32,
+        to_capacity: u32,
+    ) -> Result<()> {
+        let sides = [
+            (self.from_id, self.to_id, &self.offer, to_capacity),
+            (self.to_id, self.from_id, &self.request, from_capacity),
+        ];
+
+        // take everything first, so that the capacity counts what is given
+        for (giver, _, bundle, _) in &sides {
+            for (item, quantity) in &bundle.items {
+                let taken = OwnedItem::take(tx, *giver, item, *quantity).await?;
+                ensure!(
+                    taken == *quantity,
+                    "The trade failed, {}x {} are missing",
+                    quantity - taken,
+                    item.name
+                );
+            }
+
+            if bundle.gold > 0 {
+                LedgerEntry::record(
+                    tx,
+                    *giver,
+                    -i32::try_from(bundle.gold)?,
+                    GoldReason::Trade,
+                    None,
+                    None,
+                )
+                .await?;
+            }
+        }
+
+        for (_, receiver, bundle, capacity) in &sides {
+            for (item, quantity) in &bundle.items {
+                OwnedItem::give(tx, *receiver, item, *quantity, *capacity).await?;
+            }
+
+            if bundle.gold > 0 {
+                LedgerEntry::record(
+                    tx,
+                    *receiver,
+                    i32::try_from(bundle.gold)?,
+                    GoldReason::Trade,
+                    None,
+                    None,
+                )
+                .await?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Fail unless `player_id` has everything in `bundle` that is not equipped.
+/// `subject` starts the error, e.g. `You don't`.
+async fn ensure_available(
+    tx: &mut Transaction<'_, Postgres>,
+    player_id: i32,
+    bundle: &Bundle,
+    subject: &str,
+) -> Result<()> {
+    for (item, quantity) in &bundle.items {
+        let rec = sqlx::query!(
+            r#"
+SELECT coalesce(sum(quantity), 0) as "owned!"
+FROM item
+WHERE player_id = $1 AND key = $2 AND slot IS NULL
+            "#,
+            player_id,
+            item.key
+        )
+        .fetch_one(&mut *tx)
+        .await?;
+
+        ensure!(
+            rec.owned >= i64::from(*quantity),
+            "{} have {}x {} to trade, equipped items don't count",
+            subject,
+            quantity,
+            item.name
+        );
+    }
+
+    if bundle.gold > 0 {
+        let rec = sqlx::query!(
+            r#"
+SELECT gold
+FROM player
+WHERE id = $1
+            "#,
+            player_id
+        )
+        .fetch_one(&mut *tx)
+        .await?;
+
+        ensure!(
+            i64::from(rec.gold) >= i64::from(bundle.gold),
+            "{} have {} gold to trade",
+            subject,
+            bundle.gold
+        );
+    }
+
+    Ok(())
+}
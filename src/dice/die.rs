@@ -0,0 +1,222 @@
+use anyhow::{ensure, Context, Result};
+use rand::{
+    distributions::{Distribution, Uniform},
+    Rng,
+};
+use serde::{Deserialize, Serialize};
+use std::{convert::TryFrom, fmt, str::FromStr};
+
+/// A die with a number of sides only known at runtime, e.g. from a content
+/// file. Written as `d8` or `d%` in text.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Deserialize, Serialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct Die {
+    sides: u16,
+}
+
+impl Die {
+    pub const D4: Die = Die { sides: 4 };
+    pub const D6: Die = Die { sides: 6 };
+    pub const D8: Die = Die { sides: 8 };
+    pub const D10: Die = Die { sides: 10 };
+    pub const D12: Die = Die { sides: 12 };
+    pub const D20: Die = Die { sides: 20 };
+    pub const D100: Die = Die { sides: 100 };
+
+    /// # Panics
+    ///
+    /// Panics if `sides` is zero or above `i16::MAX`
+    #[must_use]
+    pub fn new(sides: u16) -> Self {
+        assert!(sides > 0, "a die needs at least one side");
+        assert!(
+            i16::try_from(sides).is_ok(),
+            "a die can have at most {} sides",
+            i16::MAX
+        );
+
+        Self { sides }
+    }
+
+    #[must_use]
+    pub fn sides(self) -> u16 {
+        self.sides
+    }
+
+    /// The minimum roll
+    #[must_use]
+    pub fn min(self) -> i16 {
+        1
+    }
+
+    /// The maximum roll
+    #[must_use]
+    // the sides are checked to fit in an `i16` on creation
+    #[allow(clippy::cast_possible_wrap)]
+    pub fn max(self) -> i16 {
+        self.sides as i16
+    }
+
+    /// The average roll
+    #[must_use]
+    pub fn avg(self) -> f32 {
+        // halfway between 1 and the sides
+        f32::from(self.sides) / 2.0 + 0.5
+    }
+
+    #[must_use]
+    pub fn distribution(self) -> Uniform<i16> {
+        Uniform::from(self.min()..=self.max())
+    }
+
+    /// Roll a single time
+    pub fn roll_once<R>(self, rng: &mut R) -> i16
+    where
+        R: Rng + ?Sized,
+    {
+        self.sample(rng)
+    }
+
+    /// Roll multiple times
+    pub fn roll<R>(self, rng: &mut R, n: usize) -> Vec<i16>
+    where
+        R: Rng + ?Sized,
+    {
+        (0..n).map(|_| self.sample(rng)).collect()
+    }
+}
+
+impl Distribution<i16> for Die {
+    fn sample<R>(&self, rng: &mut R) -> i16
+    where
+        R: Rng + ?Sized,
+    {
+        self.distribution().sample(rng)
+    }
+}
+
+impl FromStr for Die {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let sides = s
+            .strip_prefix('d')
+            .or_else(|| s.strip_prefix('D'))
+            .with_context(|| format!("`{}` is not a die", s))?;
+
+        if sides == "%" {
+            return Ok(Self::D100);
+        }
+
+        let sides = sides
+            .parse()
+            .with_context(|| format!("`{}` is not a die", s))?;
+        ensure!(sides > 0, "A die needs at least one side");
+        ensure!(
+            i16::try_from(sides).is_ok(),
+            "A die can have at most {} sides",
+            i16::MAX
+        );
+
+        Ok(Self { sides })
+    }
+}
+
+impl TryFrom<String> for Die {
+    type Error = anyhow::Error;
+
+    fn try_from(s: String) -> Result<Self> {
+        s.parse()
+    }
+}
+
+impl From<Die> for String {
+    fn from(die: Die) -> Self {
+        die.to_string()
+    }
+}
+
+impl fmt::Display for Die {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "d{}", self.sides)
+    }
+}
+
+#[cfg(test)]
+// the averages of the dice are exact in binary
+#[allow(clippy::float_cmp)]
+mod test {
+    use super::*;
+    use crate::dice::{rng::test::rng, Dice, D10, D20, D6};
+
+    #[test]
+    fn min_max_avg() {
+        assert_eq!(Die::D8.min(), 1);
+        assert_eq!(Die::D8.max(), 8);
+        assert_eq!(Die::D8.avg(), 4.5);
+        assert_eq!(Die::new(1).avg(), 1.0);
+    }
+
+    #[test]
+    fn same_as_static_dice() {
+        assert_eq!(Die::from(D6), Die::D6);
+        assert_eq!(Die::from(D10), Die::D10);
+        assert_eq!(Die::from(D20), Die::D20);
+        assert_eq!(Die::D20.max(), D20::max());
+        assert_eq!(Die::D20.avg(), D20::avg());
+    }
+
+    #[test]
+    fn roll20() {
+        assert_eq!(Die::D6.roll(&mut rng(), 20), D6::roll(&mut rng(), 20));
+    }
+
+    #[test]
+    fn in_range() {
+        let mut rng = rng();
+        let die = Die::new(12);
+
+        for _ in 0..1000 {
+            assert!((1..=12).contains(&die.roll_once(&mut rng)));
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "a die needs at least one side")]
+    fn zero_sides() {
+        let _ = Die::new(0);
+    }
+
+    #[test]
+    #[should_panic(expected = "a die can have at most 32767 sides")]
+    fn too_many_sides() {
+        let _ = Die::new(32_768);
+    }
+
+    #[test]
+    fn largest() {
+        let die = Die::new(32_767);
+
+        assert_eq!(die.max(), i16::MAX);
+        assert_eq!(die.avg(), 16_384.0);
+    }
+
+    #[test]
+    fn parse() {
+        assert_eq!("d8".parse::<Die>().unwrap(), Die::D8);
+        assert_eq!("D12".parse::<Die>().unwrap(), Die::D12);
+        assert_eq!("d%".parse::<Die>().unwrap(), Die::D100);
+        assert!("8".parse::<Die>().is_err());
+        assert!("d0".parse::<Die>().is_err());
+        assert!("d40000".parse::<Die>().is_err());
+        assert!("dx".parse::<Die>().is_err());
+    }
+
+    #[test]
+    fn serde() {
+        let dice: Vec<Die> = ron::de::from_str(r#"["d4", "d%"]"#).unwrap();
+
+        assert_eq!(dice, [Die::D4, Die::D100]);
+        assert_eq!(ron::ser::to_string(&Die::D8).unwrap(), r#""d8""#);
+    }
+}
@@ -0,0 +1,360 @@
+// distributions have at most `MAX_OUTCOMES` results, so their lengths and
+// indices convert between integers and floats without loss
+#![allow(
+    clippy::cast_possible_truncation,
+    clippy::cast_possible_wrap,
+    clippy::cast_precision_loss,
+    clippy::cast_sign_loss
+)]
+
+use super::Operator;
+use anyhow::{bail, ensure, Context, Result};
+use std::{cmp, convert::TryFrom};
+
+/// The most different results a distribution may have
+pub const MAX_OUTCOMES: usize = 10_000;
+
+/// The most steps computing a single distribution may take
+const MAX_STEPS: usize = 50_000_000;
+
+/// Fail if computing a distribution would take longer than [`MAX_STEPS`]
+pub(super) fn ensure_steps(steps: usize) -> Result<()> {
+    ensure!(
+        steps <= MAX_STEPS,
+        "These dice are too complex to compute the odds"
+    );
+
+    Ok(())
+}
+
+/// The exact probability of every result of a dice expression
+#[derive(Debug, Clone, PartialEq)]
+pub struct Pmf {
+    /// The lowest possible result
+    min: i64,
+    /// The probability of `min`, `min + 1`, ...
+    probs: Vec<f64>,
+}
+
+impl Pmf {
+    /// Always the same result
+    #[must_use]
+    pub fn constant(value: i64) -> Self {
+        Self {
+            min: value,
+            probs: vec![1.0],
+        }
+    }
+
+    /// A distribution from the probabilities of `min`, `min + 1`, ...
+    ///
+    /// # Panics
+    ///
+    /// Panics if no result is possible
+    pub(super) fn new(mut min: i64, mut probs: Vec<f64>) -> Self {
+        let first = probs
+            .iter()
+            .position(|&p| p > 0.0)
+            .expect("a distribution needs at least one possible result");
+        let last = probs.iter().rposition(|&p| p > 0.0).unwrap_or(first);
+
+        probs.truncate(last + 1);
+        probs.drain(..first);
+        min += first as i64;
+
+        Self { min, probs }
+    }
+
+    /// The lowest possible result
+    #[must_use]
+    pub fn min(&self) -> i64 {
+        self.min
+    }
+
+    /// The highest possible result
+    #[must_use]
+    pub fn max(&self) -> i64 {
+        self.min + self.probs.len() as i64 - 1
+    }
+
+    /// The exact average result
+    #[must_use]
+    pub fn mean(&self) -> f64 {
+        self.iter().map(|(value, prob)| value as f64 * prob).sum()
+    }
+
+    /// Every possible result and its probability, from lowest to highest
+    pub fn iter(&self) -> impl Iterator<Item = (i64, f64)> + '_ {
+        self.probs
+            .iter()
+            .enumerate()
+            .map(move |(i, &prob)| (self.min + i as i64, prob))
+    }
+
+    fn index(&self, value: i64) -> Option<usize> {
+        value
+            .checked_sub(self.min)
+            .and_then(|i| usize::try_from(i).ok())
+    }
+
+    /// The chance to get exactly `value`
+    #[must_use]
+    pub fn probability(&self, value: i64) -> f64 {
+        self.index(value)
+            .and_then(|i| self.probs.get(i))
+            .copied()
+            .unwrap_or(0.0)
+    }
+
+    /// The chance to get at most `value`
+    #[must_use]
+    pub fn cdf(&self, value: i64) -> f64 {
+        match self.index(value) {
+            Some(i) => self.probs.iter().take(i + 1).sum::<f64>().clamp(0.0, 1.0),
+            None => 0.0,
+        }
+    }
+
+    /// The chance to get more than `value`
+    #[must_use]
+    pub fn chance_to_exceed(&self, value: i64) -> f64 {
+        match self.index(value) {
+            Some(i) => self.probs.iter().skip(i + 1).sum::<f64>().clamp(0.0, 1.0),
+            None => 1.0,
+        }
+    }
+
+    /// The chance to get `value` or more, e.g. to hit an armor class
+    #[must_use]
+    pub fn chance_at_least(&self, value: i64) -> f64 {
+        match value.checked_sub(1) {
+            Some(value) => self.chance_to_exceed(value),
+            None => 1.0,
+        }
+    }
+
+    /// The lowest result that is reached with a chance of at least `p`, e.g.
+    /// `0.5` for the median
+    #[must_use]
+    pub fn percentile(&self, p: f64) -> i64 {
+        let mut cdf = 0.0;
+
+        for (value, prob) in self.iter() {
+            cdf += prob;
+            // allow for rounding errors when summing up
+            if cdf >= p - 1e-9 {
+                return value;
+            }
+        }
+
+        self.max()
+    }
+
+    /// The distribution of `self op other`
+    ///
+    /// # Errors
+    ///
+    /// Fails if the distribution has too many results, takes too long to
+    /// compute or divides by zero
+    pub fn combine(&self, op: Operator, other: &Self) -> Result<Self> {
+        match op {
+            Operator::Add => self.add(other),
+            Operator::Subtract => self.add(&other.negate()?),
+            Operator::Multiply => self.map2(other, i64::checked_mul),
+            Operator::Divide => {
+                ensure!(other.probability(0) == 0.0, "Cannot divide by zero");
+                self.map2(other, i64::checked_div)
+            }
+        }
+    }
+
+    fn negate(&self) -> Result<Self> {
+        let min = self
+            .max()
+            .checked_neg()
+            .context("The result is too large")?;
+        let probs = self.probs.iter().rev().copied().collect();
+
+        Ok(Self { min, probs })
+    }
+
+    /// The distribution of the sum of both results
+    fn add(&self, other: &Self) -> Result<Self> {
+        let len = self.probs.len() + other.probs.len() - 1;
+        ensure!(
+            len <= MAX_OUTCOMES,
+            "Cannot compute the odds for more than {} different results",
+            MAX_OUTCOMES
+        );
+        ensure_steps(self.probs.len() * other.probs.len())?;
+
+        let min = self
+            .min
+            .checked_add(other.min)
+            .context("The result is too large")?;
+        self.max()
+            .checked_add(other.max())
+            .context("The result is too large")?;
+
+        let mut probs = vec![0.0; len];
+        for (i, a) in self.probs.iter().enumerate() {
+            for (j, b) in other.probs.iter().enumerate() {
+                probs[i + j] += a * b;
+            }
+        }
+
+        Ok(Self::new(min, probs))
+    }
+
+    /// The distribution of `f` applied to every pair of results
+    fn map2<F>(&self, other: &Self, f: F) -> Result<Self>
+    where
+        F: Fn(i64, i64) -> Option<i64>,
+    {
+        let pairs = || {
+            self.iter()
+                .filter(|&(_, p)| p > 0.0)
+                .flat_map(move |a| other.iter().filter(|&(_, p)| p > 0.0).map(move |b| (a, b)))
+        };
+        ensure_steps(2 * self.probs.len() * other.probs.len())?;
+
+        let (mut min, mut max) = (i64::MAX, i64::MIN);
+        for ((a, _), (b, _)) in pairs() {
+            let value = f(a, b).context("The result is too large")?;
+            min = cmp::min(min, value);
+            max = cmp::max(max, value);
+        }
+
+        let len = max
+            .checked_sub(min)
+            .and_then(|len| usize::try_from(len).ok())
+            .filter(|&len| len < MAX_OUTCOMES);
+        let len = match len {
+            Some(len) => len + 1,
+            None => bail!(
+                "Cannot compute the odds for more than {} different results",
+                MAX_OUTCOMES
+            ),
+        };
+
+        let mut probs = vec![0.0; len];
+        for ((a, p), (b, q)) in pairs() {
+            // cannot fail, every value was computed above
+            let value = f(a, b).unwrap_or(min);
+            probs[(value - min) as usize] += p * q;
+        }
+
+        Ok(Self::new(min, probs))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{DicePool, Die, Expression};
+
+    fn assert_close(a: f64, b: f64) {
+        assert!((a - b).abs() < 1e-9, "{} is not {}", a, b);
+    }
+
+    fn pmf(text: &str) -> Pmf {
+        Expression::parse(text).unwrap().pmf().unwrap()
+    }
+
+    #[test]
+    fn constant() {
+        let pmf = pmf("3");
+
+        assert_eq!(pmf.min(), 3);
+        assert_eq!(pmf.max(), 3);
+        assert_close(pmf.probability(3), 1.0);
+        assert_close(pmf.probability(4), 0.0);
+    }
+
+    #[test]
+    fn two_d6() {
+        let pmf = pmf("2d6+1");
+
+        assert_eq!(pmf.min(), 3);
+        assert_eq!(pmf.max(), 13);
+        assert_close(pmf.mean(), 8.0);
+        assert_close(pmf.probability(8), 6.0 / 36.0);
+        assert_close(pmf.iter().map(|(_, p)| p).sum(), 1.0);
+    }
+
+    #[test]
+    fn cdf() {
+        let pmf = pmf("d20+3");
+
+        assert_close(pmf.cdf(3), 0.0);
+        assert_close(pmf.cdf(4), 0.05);
+        assert_close(pmf.cdf(23), 1.0);
+        assert_close(pmf.cdf(100), 1.0);
+        assert_close(pmf.chance_at_least(15), 0.45);
+        assert_close(pmf.chance_to_exceed(15), 0.4);
+        assert_close(pmf.chance_at_least(i64::MIN), 1.0);
+    }
+
+    #[test]
+    fn percentile() {
+        let pmf = pmf("d10");
+
+        assert_eq!(pmf.percentile(0.0), 1);
+        assert_eq!(pmf.percentile(0.5), 5);
+        assert_eq!(pmf.percentile(0.51), 6);
+        assert_eq!(pmf.percentile(1.0), 10);
+    }
+
+    #[test]
+    fn subtract() {
+        let pmf = pmf("d6-d6");
+
+        assert_eq!(pmf.min(), -5);
+        assert_eq!(pmf.max(), 5);
+        assert_close(pmf.mean(), 0.0);
+        assert_close(pmf.probability(-5), pmf.probability(5));
+    }
+
+    #[test]
+    fn multiply_and_divide() {
+        let pmf = pmf("2*d6");
+
+        assert_close(pmf.probability(7), 0.0);
+        assert_close(pmf.probability(12), 1.0 / 6.0);
+
+        let pmf = self::pmf("d6/2");
+        assert_eq!(pmf.min(), 0);
+        assert_close(pmf.probability(3), 1.0 / 6.0);
+        assert_close(pmf.mean(), 1.5);
+    }
+
+    #[test]
+    fn divide_by_zero() {
+        assert!(Expression::parse("d6/(d2-1)").unwrap().pmf().is_err());
+        assert!(Expression::parse("d6/(d2+1)").unwrap().pmf().is_ok());
+    }
+
+    #[test]
+    fn matches_average() {
+        for text in &["4d6kh3", "d20adv", "3d6r2!min2kl2", "10d10!", "d8*d8"] {
+            let expression = Expression::parse(text).unwrap();
+            let mean = expression.pmf().unwrap().mean();
+
+            if let Expression::Dice(pool) = expression {
+                assert_close(mean, pool.avg());
+            } else {
+                assert_close(mean, 4.5 * 4.5);
+            }
+        }
+    }
+
+    #[test]
+    fn too_complex() {
+        assert!(DicePool::new(100, Die::new(1000)).explode().pmf().is_err());
+        assert!(Expression::parse("d1000*d1000").unwrap().pmf().is_err());
+        assert!(Expression::parse("d6+9223372036854775807")
+            .unwrap()
+            .pmf()
+            .is_err());
+    }
+}
@@ -0,0 +1,594 @@
+use super::{Advantage, DicePool, Die, Keep, Modifiers, Pmf, PoolRoll};
+use anyhow::{anyhow, ensure, Context, Result};
+use pest::{
+    iterators::{Pair, Pairs},
+    Parser,
+};
+use pest_derive::Parser;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use std::{convert::TryFrom, fmt, str::FromStr};
+
+/// The most dice a single expression may roll
+pub const MAX_DICE: u32 = 100;
+
+/// The most sides a single die may have
+pub const MAX_SIDES: u16 = 1000;
+
+#[derive(Parser)]
+#[grammar = "dice.pest"]
+struct ExpressionParser;
+
+/// A dice expression in standard notation like `3d6+2`, `4d6kh3` or `d%`
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(try_from = "String", into = "String")]
+pub enum Expression {
+    Number(i64),
+    Dice(DicePool),
+    Binary(Box<Expression>, Operator, Box<Expression>),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Operator {
+    Add,
+    Subtract,
+    Multiply,
+    Divide,
+}
+
+/// The result of rolling an [`Expression`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Roll {
+    pub total: i64,
+    pub pools: Vec<PoolRoll>,
+    breakdown: String,
+}
+
+impl Expression {
+    /// Parse a dice expression, e.g. `2d6+3`
+    ///
+    /// # Errors
+    ///
+    /// Fails if `text` is not a valid expression
+    pub fn parse(text: &str) -> Result<Self> {
+        let mut pairs = ExpressionParser::parse(Rule::expression, text)
+            .ok()
+            .with_context(|| format!("`{}` is not a valid dice expression", text))?;
+        let expression = Self::process(pairs.next().context("Missing dice expression")?)?;
+
+        ensure!(
+            expression.dice_count() <= MAX_DICE,
+            "Cannot roll more than {} dice at once",
+            MAX_DICE
+        );
+
+        Ok(expression)
+    }
+
+    fn process(pair: Pair<'_, Rule>) -> Result<Self> {
+        match pair.as_rule() {
+            Rule::sum | Rule::product => {
+                let mut pairs = pair.into_inner();
+                let mut lhs = Self::process(pairs.next().context("Missing operand")?)?;
+
+                while let Some(op) = pairs.next() {
+                    let op = match op.as_rule() {
+                        Rule::add => Operator::Add,
+                        Rule::subtract => Operator::Subtract,
+                        Rule::multiply => Operator::Multiply,
+                        Rule::divide => Operator::Divide,
+                        _ => unreachable!(),
+                    };
+                    let rhs = Self::process(pairs.next().context("Missing operand")?)?;
+
+                    lhs = Self::Binary(Box::new(lhs), op, Box::new(rhs));
+                }
+
+                Ok(lhs)
+            }
+            Rule::number => {
+                Ok(Self::Number(pair.as_str().parse().with_context(|| {
+                    format!("{} is too large", pair.as_str())
+                })?))
+            }
+            Rule::dice => Ok(Self::Dice(DicePool::process(pair)?)),
+            _ => unreachable!(),
+        }
+    }
+
+    /// `pool` plus a flat bonus, e.g. `d20+3`. A bonus of `0` is left out.
+    #[must_use]
+    pub fn with_bonus(pool: DicePool, bonus: i64) -> Self {
+        let dice = Self::Dice(pool);
+
+        match bonus {
+            0 => dice,
+            n if n < 0 => Self::Binary(
+                Box::new(dice),
+                Operator::Subtract,
+                Box::new(Self::Number(-n)),
+            ),
+            n => Self::Binary(Box::new(dice), Operator::Add, Box::new(Self::Number(n))),
+        }
+    }
+
+    /// The number of dice rolled by this expression
+    #[must_use]
+    pub fn dice_count(&self) -> u32 {
+        match self {
+            Self::Number(_) => 0,
+            Self::Dice(pool) => u32::from(pool.count),
+            Self::Binary(lhs, _, rhs) => lhs.dice_count().saturating_add(rhs.dice_count()),
+        }
+    }
+
+    /// The exact probability of every result
+    ///
+    /// # Errors
+    ///
+    /// Fails if the distribution has too many results or takes too long to
+    /// compute
+    pub fn pmf(&self) -> Result<Pmf> {
+        match self {
+            Self::Number(n) => Ok(Pmf::constant(*n)),
+            Self::Dice(pool) => pool.pmf(),
+            Self::Binary(lhs, op, rhs) => lhs.pmf()?.combine(*op, &rhs.pmf()?),
+        }
+    }
+
+    /// Roll all dice and evaluate the expression
+    ///
+    /// # Errors
+    ///
+    /// Fails if a pool is invalid, the expression divides by zero or the
+    /// total overflows
+    pub fn roll<R>(&self, rng: &mut R) -> Result<Roll>
+    where
+        R: Rng + ?Sized,
+    {
+        let mut pools = Vec::new();
+        let (total, breakdown) = self.evaluate(rng, &mut pools)?;
+
+        Ok(Roll {
+            total,
+            pools,
+            breakdown,
+        })
+    }
+
+    fn evaluate<R>(&self, rng: &mut R, pools: &mut Vec<PoolRoll>) -> Result<(i64, String)>
+    where
+        R: Rng + ?Sized,
+    {
+        match self {
+            Self::Number(n) => Ok((*n, n.to_string())),
+            Self::Dice(pool) => {
+                let roll = pool.roll(rng);
+                let result = (roll.total, roll.to_string());
+                pools.push(roll);
+                Ok(result)
+            }
+            Self::Binary(lhs, op, rhs) => {
+                let (a, lhs_text) = lhs.evaluate(rng, pools)?;
+                let (b, rhs_text) = rhs.evaluate(rng, pools)?;
+
+                let total = match op {
+                    Operator::Add => a.checked_add(b),
+                    Operator::Subtract => a.checked_sub(b),
+                    Operator::Multiply => a.checked_mul(b),
+                    Operator::Divide => {
+                        ensure!(b != 0, "Cannot divide by zero");
+                        a.checked_div(b)
+                    }
+                }
+                .context("The result is too large")?;
+
+                Ok((
+                    total,
+                    format!(
+                        "{} {} {}",
+                        parenthesize(lhs, *op, false, lhs_text),
+                        op,
+                        parenthesize(rhs, *op, true, rhs_text)
+                    ),
+                ))
+            }
+        }
+    }
+
+    fn precedence(&self) -> u8 {
+        match self {
+            Self::Binary(_, op, _) => op.precedence(),
+            _ => u8::MAX,
+        }
+    }
+}
+
+/// Wrap the text of an operand in parentheses if it binds weaker than its
+/// operator
+fn parenthesize(operand: &Expression, op: Operator, is_rhs: bool, text: String) -> String {
+    let precedence = operand.precedence();
+
+    if precedence < op.precedence()
+        || (is_rhs && precedence == op.precedence() && !op.is_associative())
+    {
+        format!("({})", text)
+    } else {
+        text
+    }
+}
+
+impl FromStr for Expression {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        Self::parse(s)
+    }
+}
+
+impl TryFrom<String> for Expression {
+    type Error = anyhow::Error;
+
+    fn try_from(s: String) -> Result<Self> {
+        s.parse()
+    }
+}
+
+impl From<Expression> for String {
+    fn from(expression: Expression) -> Self {
+        expression.to_string()
+    }
+}
+
+impl fmt::Display for Expression {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Number(n) => write!(f, "{}", n),
+            Self::Dice(pool) => write!(f, "{}", pool),
+            Self::Binary(lhs, op, rhs) => {
+                let lhs = parenthesize(lhs, *op, false, lhs.to_string());
+                let rhs = parenthesize(rhs, *op, true, rhs.to_string());
+
+                write!(f, "{}{}{}", lhs, op, rhs)
+            }
+        }
+    }
+}
+
+impl Operator {
+    fn precedence(self) -> u8 {
+        match self {
+            Self::Add | Self::Subtract => 1,
+            Self::Multiply | Self::Divide => 2,
+        }
+    }
+
+    fn is_associative(self) -> bool {
+        matches!(self, Self::Add | Self::Multiply)
+    }
+}
+
+impl fmt::Display for Operator {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Add => "+",
+            Self::Subtract => "-",
+            Self::Multiply => "*",
+            Self::Divide => "/",
+        })
+    }
+}
+
+impl DicePool {
+    fn process(pair: Pair<'_, Rule>) -> Result<Self> {
+        let mut count = 1;
+        let mut die = Die::D6;
+        let mut modifiers = Modifiers::default();
+
+        for pair in pair.into_inner() {
+            let rule = pair.as_rule();
+            let twice = || anyhow!("`{}` can only be used once per dice", pair.as_str());
+
+            match rule {
+                Rule::count => {
+                    count = pair
+                        .as_str()
+                        .parse()
+                        .with_context(|| format!("Cannot roll {} dice", pair.as_str()))?;
+                }
+                Rule::sides if pair.as_str() == "%" => die = Die::D100,
+                Rule::sides => {
+                    let sides = pair.as_str().parse().unwrap_or(u16::MAX);
+                    ensure!(
+                        (1..=MAX_SIDES).contains(&sides),
+                        "Dice must have between 1 and {} sides",
+                        MAX_SIDES
+                    );
+                    die = Die::new(sides);
+                }
+                Rule::advantage | Rule::disadvantage => {
+                    ensure!(modifiers.advantage.is_none(), twice());
+                    modifiers.advantage = Some(if rule == Rule::advantage {
+                        Advantage::Advantage
+                    } else {
+                        Advantage::Disadvantage
+                    });
+                }
+                Rule::reroll => {
+                    ensure!(modifiers.reroll_below.is_none(), twice());
+                    modifiers.reroll_below = Some(parse_number(pair.into_inner())?);
+                }
+                Rule::explode => {
+                    ensure!(!modifiers.explode, twice());
+                    modifiers.explode = true;
+                }
+                Rule::minimum => {
+                    ensure!(modifiers.minimum.is_none(), twice());
+                    modifiers.minimum = Some(parse_number(pair.into_inner())?);
+                }
+                Rule::keep => {
+                    ensure!(modifiers.keep.is_none(), twice());
+                    modifiers.keep = Some(Keep::process(pair)?);
+                }
+                _ => unreachable!(),
+            }
+        }
+
+        let pool = Self {
+            count,
+            die,
+            modifiers,
+        };
+        pool.validate()?;
+
+        Ok(pool)
+    }
+}
+
+fn parse_number<T>(mut pairs: Pairs<'_, Rule>) -> Result<T>
+where
+    T: FromStr,
+{
+    let number = pairs.next().context("Missing number")?.as_str();
+
+    number
+        .parse()
+        .ok()
+        .with_context(|| format!("{} is too large", number))
+}
+
+impl Keep {
+    fn process(pair: Pair<'_, Rule>) -> Result<Self> {
+        let mut pairs = pair.into_inner();
+        let kind = pairs.next().context("Missing keep kind")?.as_rule();
+        let n = parse_number(pairs)?;
+
+        Ok(match kind {
+            Rule::keep_highest => Self::Highest(n),
+            Rule::keep_lowest => Self::Lowest(n),
+            _ => unreachable!(),
+        })
+    }
+}
+
+impl Roll {
+    /// The rolled dice and the total, shortened to at most `max_len`
+    /// characters by leaving out the individual dice if necessary
+    #[must_use]
+    pub fn summary(&self, max_len: usize) -> String {
+        let summary = self.to_string();
+
+        if summary.chars().count() <= max_len {
+            summary
+        } else {
+            self.total.to_string()
+        }
+    }
+}
+
+impl fmt::Display for Roll {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.breakdown == self.total.to_string() {
+            write!(f, "{}", self.total)
+        } else {
+            write!(f, "{} = {}", self.breakdown, self.total)
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::dice::rng::test::rng;
+
+    fn pool(count: u16, sides: u16, keep: Option<Keep>) -> Expression {
+        Expression::Dice(DicePool {
+            count,
+            die: Die::new(sides),
+            modifiers: Modifiers {
+                keep,
+                ..Modifiers::default()
+            },
+        })
+    }
+
+    mod parse {
+        use super::*;
+
+        #[test]
+        fn single_die() {
+            assert_eq!(Expression::parse("d20").unwrap(), pool(1, 20, None));
+        }
+
+        #[test]
+        fn percentile() {
+            assert_eq!(Expression::parse("d%").unwrap(), pool(1, 100, None));
+        }
+
+        #[test]
+        fn keep() {
+            assert_eq!(
+                Expression::parse("4d6kh3").unwrap(),
+                pool(4, 6, Some(Keep::Highest(3)))
+            );
+            assert_eq!(
+                Expression::parse("2D20KL1").unwrap(),
+                pool(2, 20, Some(Keep::Lowest(1)))
+            );
+        }
+
+        #[test]
+        fn precedence() {
+            assert_eq!(
+                Expression::parse("3d6 + 2 * 4").unwrap(),
+                Expression::Binary(
+                    Box::new(pool(3, 6, None)),
+                    Operator::Add,
+                    Box::new(Expression::Binary(
+                        Box::new(Expression::Number(2)),
+                        Operator::Multiply,
+                        Box::new(Expression::Number(4))
+                    ))
+                )
+            );
+        }
+
+        #[test]
+        fn display() {
+            for text in &[
+                "3d6+2",
+                "4d6kh3",
+                "2d20kl1-1",
+                "(d8+2)*2",
+                "10-(2-1)",
+                "d100",
+            ] {
+                assert_eq!(Expression::parse(text).unwrap().to_string(), *text);
+            }
+
+            assert_eq!(Expression::parse("d%").unwrap().to_string(), "d100");
+            assert_eq!(Expression::parse("((2))+(3)").unwrap().to_string(), "2+3");
+        }
+
+        #[test]
+        fn with_bonus() {
+            let d20 = DicePool::new(1, Die::D20);
+
+            assert_eq!(Expression::with_bonus(d20, 3).to_string(), "d20+3");
+            assert_eq!(Expression::with_bonus(d20, -2).to_string(), "d20-2");
+            assert_eq!(Expression::with_bonus(d20, 0), Expression::Dice(d20));
+        }
+
+        #[test]
+        fn invalid() {
+            for text in &[
+                "", "d", "3d", "d0", "0d6", "2d6kh3", "4d6kh0", "d6+", "d1001",
+            ] {
+                assert!(
+                    Expression::parse(text).is_err(),
+                    "{:?} should not parse",
+                    text
+                );
+            }
+        }
+
+        #[test]
+        fn serde() {
+            let expression: Expression = ron::de::from_str(r#""2d6 + 1""#).unwrap();
+
+            assert_eq!(expression, Expression::parse("2d6+1").unwrap());
+            assert_eq!(ron::ser::to_string(&expression).unwrap(), r#""2d6+1""#);
+            assert!(ron::de::from_str::<Expression>(r#""2d""#).is_err());
+        }
+
+        #[test]
+        fn too_many_dice() {
+            assert!(Expression::parse("100d6").is_ok());
+            assert!(Expression::parse("50d6+51d6").is_err());
+            assert!(Expression::parse("99999d6").is_err());
+        }
+    }
+
+    mod roll {
+        use super::*;
+
+        #[test]
+        fn in_range() {
+            let expression = Expression::parse("3d6+2").unwrap();
+            let mut rng = rng();
+
+            for _ in 0..1000 {
+                let total = expression.roll(&mut rng).unwrap().total;
+                assert!((5..=20).contains(&total));
+            }
+        }
+
+        #[test]
+        fn keeps_highest() {
+            let roll = Expression::parse("4d6kh3")
+                .unwrap()
+                .roll(&mut rng())
+                .unwrap();
+            let pool = &roll.pools[0];
+
+            let mut rolls = pool.rolls.clone();
+            rolls.sort_unstable();
+            let expected: i16 = rolls[1..].iter().sum();
+
+            assert_eq!(pool.kept.iter().filter(|&&kept| kept).count(), 3);
+            assert_eq!(roll.total, i64::from(expected));
+        }
+
+        #[test]
+        fn keeps_lowest() {
+            let roll = Expression::parse("2d20kl1")
+                .unwrap()
+                .roll(&mut rng())
+                .unwrap();
+            let pool = &roll.pools[0];
+
+            assert_eq!(roll.total, i64::from(*pool.rolls.iter().min().unwrap()));
+        }
+
+        #[test]
+        fn arithmetic() {
+            let roll = Expression::parse("(1+2)*3-8/4")
+                .unwrap()
+                .roll(&mut rng())
+                .unwrap();
+
+            assert_eq!(roll.total, 7);
+            assert_eq!(roll.to_string(), "(1 + 2) * 3 - 8 / 4 = 7");
+        }
+
+        #[test]
+        fn division_by_zero() {
+            assert!(Expression::parse("d6/0").unwrap().roll(&mut rng()).is_err());
+        }
+
+        #[test]
+        fn breakdown() {
+            let roll = Expression::parse("2d6+1")
+                .unwrap()
+                .roll(&mut rng())
+                .unwrap();
+            let rolls = &roll.pools[0].rolls;
+
+            assert_eq!(
+                roll.to_string(),
+                format!("[{}, {}] + 1 = {}", rolls[0], rolls[1], roll.total)
+            );
+        }
+
+        #[test]
+        fn summary_is_shortened() {
+            let roll = Expression::parse("100d6")
+                .unwrap()
+                .roll(&mut rng())
+                .unwrap();
+
+            assert_eq!(roll.summary(50), roll.total.to_string());
+            assert!(roll.summary(1000).starts_with('['));
+        }
+    }
+}
@@ -1,26 +1,22 @@
+mod die;
+mod dist;
+mod expr;
+mod pool;
+pub(crate) mod rng;
+
+pub use self::{
+    die::Die,
+    dist::Pmf,
+    expr::{Expression, Operator, Roll},
+    pool::{Advantage, DicePool, Keep, Modifiers, PoolRoll},
+    rng::{Commitment, GameRng, Seed},
+};
+
 use rand::{
     distributions::{Distribution, Uniform},
-    RngCore,
+    Rng,
 };
 
-#[cfg(debug_assertions)]
-#[allow(dead_code)]
-fn rng() -> Box<dyn RngCore> {
-    use rand::SeedableRng;
-
-    Box::new(rand_chacha::ChaCha20Rng::from_seed([
-        0x46, 0x65, 0x65, 0x6c, 0x73, 0x44, 0x61, 0x6e, 0x6b, 0x4d, 0x61, 0x6e, 0x4b, 0x61, 0x70,
-        0x70, 0x61, 0x31, 0x32, 0x33, 0x46, 0x65, 0x65, 0x6c, 0x73, 0x44, 0x61, 0x6e, 0x6b, 0x4d,
-        0x61, 0x6e,
-    ]))
-}
-
-#[cfg(not(debug_assertions))]
-#[allow(dead_code)]
-fn rng() -> Box<dyn RngCore> {
-    Box::new(rand::thread_rng())
-}
-
 pub trait Dice<D>
 where
     D: Distribution<i16>,
@@ -37,19 +33,21 @@ where
     fn distribution() -> D;
 
     /// Roll a single time
-    fn roll_once() -> i16 {
-        Self::distribution().sample(&mut rng())
+    fn roll_once<R>(rng: &mut R) -> i16
+    where
+        R: Rng + ?Sized,
+    {
+        Self::distribution().sample(rng)
     }
 
     /// Roll multiple times
-    fn roll(n: usize) -> Vec<i16> {
+    fn roll<R>(rng: &mut R, n: usize) -> Vec<i16>
+    where
+        R: Rng + ?Sized,
+    {
         let dist = Self::distribution();
-        let mut rng = rng();
 
-        vec![0; n]
-            .into_iter()
-            .map(|_| dist.sample(&mut rng))
-            .collect()
+        vec![0; n].into_iter().map(|_| dist.sample(rng)).collect()
     }
 }
 
@@ -73,35 +71,33 @@ macro_rules! impl_dice {
                 Uniform::from($min..=$max)
             }
         }
+
+        impl From<$dice> for Die {
+            fn from(_: $dice) -> Self {
+                Die::new($max as u16)
+            }
+        }
     };
 }
 
-impl_dice!(D6,  1i16, 6i16);
+impl_dice!(D6, 1i16, 6i16);
 impl_dice!(D10, 1i16, 10i16);
 impl_dice!(D20, 1i16, 20i16);
 
-#[cfg(all(test, debug_assertions))]
+#[cfg(test)]
+// the averages of the dice are exact in binary
+#[allow(clippy::float_cmp)]
 mod test {
-    use super::*;
-
-    #[test]
-    fn is_rng_seeded() {
-        let mut rng = rng();
-        assert_eq!(rng.next_u64(), 15084443005315021354);
-        assert_eq!(rng.next_u64(), 13672135499988464561);
-        assert_eq!(rng.next_u64(), 4380429139279809442);
-    }
-
-
+    use super::{rng::test::rng, *};
     mod d6 {
         use super::*;
 
         #[test]
         fn roll20() {
             assert_eq!(
-                D6::roll(20),
+                D6::roll(&mut rng(), 20),
                 vec![1, 5, 4, 5, 6, 2, 3, 6, 4, 4, 6, 1, 1, 2, 2, 5, 2, 4, 4, 3]
-            )
+            );
         }
 
         #[test]
@@ -126,9 +122,9 @@ mod test {
         #[test]
         fn roll20() {
             assert_eq!(
-                D10::roll(20),
+                D10::roll(&mut rng(), 20),
                 vec![1, 9, 6, 8, 10, 3, 5, 9, 7, 7, 9, 1, 1, 3, 3, 8, 3, 6, 7, 4]
-            )
+            );
         }
 
         #[test]
@@ -153,9 +149,9 @@ mod test {
         #[test]
         fn roll20() {
             assert_eq!(
-                D20::roll(20),
+                D20::roll(&mut rng(), 20),
                 [2, 17, 12, 15, 20, 5, 9, 17, 14, 13, 17, 1, 1, 6, 6, 15, 6, 12, 14, 8]
-            )
+            );
         }
 
         #[test]
@@ -0,0 +1,681 @@
+// dice have at most `u16::MAX` sides and distributions at most
+// `MAX_OUTCOMES` results, so lengths, totals and probabilities convert
+// between integers and floats without loss
+#![allow(
+    clippy::cast_possible_truncation,
+    clippy::cast_possible_wrap,
+    clippy::cast_precision_loss,
+    clippy::cast_sign_loss
+)]
+
+use super::{
+    dist::{self, Pmf, MAX_OUTCOMES},
+    Die, Operator,
+};
+use anyhow::{bail, ensure, Result};
+use rand::{distributions::Distribution, Rng};
+use std::{cmp, fmt};
+
+/// How often a single die may explode
+pub const MAX_EXPLOSIONS: usize = 10;
+
+/// A number of dice with the same amount of sides rolled together
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DicePool {
+    pub count: u16,
+    pub die: Die,
+    pub modifiers: Modifiers,
+}
+
+/// Changes to how the dice of a pool are rolled.
+///
+/// Every die is rolled on its own in this order:
+///
+/// 1. with advantage (disadvantage) the die is rolled twice and the higher
+///    (lower) result is used
+/// 2. a result below `reroll_below` is rolled again once, keeping the new
+///    result
+/// 3. an exploding die showing its highest face adds another roll of the die,
+///    which can explode again up to [`MAX_EXPLOSIONS`] times
+/// 4. the result is raised to `minimum`
+///
+/// Afterwards `keep` decides which dice count towards the total.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Modifiers {
+    pub advantage: Option<Advantage>,
+    pub reroll_below: Option<i16>,
+    pub explode: bool,
+    pub minimum: Option<i16>,
+    pub keep: Option<Keep>,
+}
+
+impl Modifiers {
+    /// Roll every die once and keep all of them
+    pub const NONE: Modifiers = Modifiers {
+        advantage: None,
+        reroll_below: None,
+        explode: false,
+        minimum: None,
+        keep: None,
+    };
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Advantage {
+    Advantage,
+    Disadvantage,
+}
+
+/// Which dice of a pool count towards its total
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Keep {
+    Highest(u16),
+    Lowest(u16),
+}
+
+/// The individual dice rolled for a [`DicePool`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PoolRoll {
+    pub pool: DicePool,
+    pub rolls: Vec<i16>,
+    /// Whether the roll at the same index counts towards the total
+    pub kept: Vec<bool>,
+    pub total: i64,
+}
+
+impl DicePool {
+    #[must_use]
+    pub const fn new(count: u16, die: Die) -> Self {
+        Self {
+            count,
+            die,
+            modifiers: Modifiers::NONE,
+        }
+    }
+
+    #[must_use]
+    pub fn advantage(mut self) -> Self {
+        self.modifiers.advantage = Some(Advantage::Advantage);
+        self
+    }
+
+    #[must_use]
+    pub fn disadvantage(mut self) -> Self {
+        self.modifiers.advantage = Some(Advantage::Disadvantage);
+        self
+    }
+
+    #[must_use]
+    pub fn reroll_below(mut self, n: i16) -> Self {
+        self.modifiers.reroll_below = Some(n);
+        self
+    }
+
+    #[must_use]
+    pub fn explode(mut self) -> Self {
+        self.modifiers.explode = true;
+        self
+    }
+
+    #[must_use]
+    pub fn minimum(mut self, n: i16) -> Self {
+        self.modifiers.minimum = Some(n);
+        self
+    }
+
+    #[must_use]
+    pub fn keep_highest(mut self, n: u16) -> Self {
+        self.modifiers.keep = Some(Keep::Highest(n));
+        self
+    }
+
+    #[must_use]
+    pub fn keep_lowest(mut self, n: u16) -> Self {
+        self.modifiers.keep = Some(Keep::Lowest(n));
+        self
+    }
+
+    /// Check that the pool can be rolled
+    ///
+    /// # Errors
+    ///
+    /// Fails if the pool has no dice, cannot explode or keeps more dice than
+    /// it has
+    pub fn validate(&self) -> Result<()> {
+        ensure!(self.count > 0, "Cannot roll zero dice");
+        ensure!(
+            !self.modifiers.explode || self.die.sides() > 1,
+            "A {} cannot explode",
+            self.die
+        );
+
+        match self.modifiers.keep {
+            Some(Keep::Highest(n) | Keep::Lowest(n)) if n == 0 || n > self.count => {
+                bail!("Cannot keep {} of {} dice", n, self.count)
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// The number of dice counting towards the total
+    fn kept_count(&self) -> u16 {
+        match self.modifiers.keep {
+            Some(Keep::Highest(n) | Keep::Lowest(n)) => n,
+            None => self.count,
+        }
+    }
+
+    pub fn roll<R>(&self, rng: &mut R) -> PoolRoll
+    where
+        R: Rng + ?Sized,
+    {
+        let rolls: Vec<i16> = (0..self.count).map(|_| self.roll_die(rng)).collect();
+
+        let mut kept = vec![true; rolls.len()];
+        if let Some(keep) = self.modifiers.keep {
+            let mut order: Vec<usize> = (0..rolls.len()).collect();
+            order.sort_by_key(|&i| rolls[i]);
+
+            let dropped = match keep {
+                Keep::Highest(n) => &order[..rolls.len() - usize::from(n)],
+                Keep::Lowest(n) => &order[usize::from(n)..],
+            };
+
+            for &i in dropped {
+                kept[i] = false;
+            }
+        }
+
+        let total = rolls
+            .iter()
+            .zip(&kept)
+            .filter(|(_, &kept)| kept)
+            .map(|(&roll, _)| i64::from(roll))
+            .sum();
+
+        PoolRoll {
+            pool: *self,
+            rolls,
+            kept,
+            total,
+        }
+    }
+
+    /// Roll a single die with all modifiers except `keep`
+    fn roll_die<R>(&self, rng: &mut R) -> i16
+    where
+        R: Rng + ?Sized,
+    {
+        let modifiers = &self.modifiers;
+        let roll = |rng: &mut R| match modifiers.advantage {
+            Some(Advantage::Advantage) => cmp::max(self.die.sample(rng), self.die.sample(rng)),
+            Some(Advantage::Disadvantage) => cmp::min(self.die.sample(rng), self.die.sample(rng)),
+            None => self.die.sample(rng),
+        };
+
+        let mut result = roll(rng);
+
+        if let Some(n) = modifiers.reroll_below {
+            if result < n {
+                result = roll(rng);
+            }
+        }
+
+        if modifiers.explode {
+            let mut last = result;
+            let mut explosions = 0;
+
+            while last == self.die.max() && explosions < MAX_EXPLOSIONS {
+                last = self.die.sample(rng);
+                result += last;
+                explosions += 1;
+            }
+        }
+
+        if let Some(n) = modifiers.minimum {
+            result = cmp::max(result, n);
+        }
+
+        result
+    }
+
+    /// The probability of every result of a single die, indexed by the result
+    fn die_probabilities(&self) -> Vec<f64> {
+        let sides = usize::from(self.die.sides());
+        let p = 1.0 / sides as f64;
+
+        let mut probs = vec![0.0; sides + 1];
+        for (value, prob) in probs.iter_mut().enumerate().skip(1) {
+            // the chance of rolling exactly `value` on the better or worse of two dice
+            let (v, s) = (value as f64, sides as f64);
+            *prob = match self.modifiers.advantage {
+                Some(Advantage::Advantage) => (v * v - (v - 1.0) * (v - 1.0)) * p * p,
+                Some(Advantage::Disadvantage) => {
+                    ((s - v + 1.0) * (s - v + 1.0) - (s - v) * (s - v)) * p * p
+                }
+                None => p,
+            };
+        }
+
+        if let Some(n) = self.modifiers.reroll_below {
+            let first = probs.clone();
+            let low: f64 = first
+                .iter()
+                .enumerate()
+                .filter(|&(value, _)| (value as i64) < i64::from(n))
+                .map(|(_, prob)| prob)
+                .sum();
+
+            for (value, prob) in probs.iter_mut().enumerate() {
+                let kept = if (value as i64) < i64::from(n) {
+                    0.0
+                } else {
+                    first[value]
+                };
+                *prob = kept + low * first[value];
+            }
+        }
+
+        if self.modifiers.explode {
+            // the sum of all extra rolls once the die exploded
+            let mut extra = vec![0.0; sides + 1];
+            for prob in extra.iter_mut().skip(1) {
+                *prob = p;
+            }
+            for _ in 1..MAX_EXPLOSIONS {
+                let mut next = vec![0.0; extra.len() + sides];
+                for prob in next.iter_mut().take(sides).skip(1) {
+                    *prob = p;
+                }
+                for (value, prob) in extra.iter().enumerate() {
+                    next[sides + value] += p * prob;
+                }
+                extra = next;
+            }
+
+            let exploded = probs[sides];
+            probs[sides] = 0.0;
+            probs.resize(sides + extra.len(), 0.0);
+            for (value, prob) in extra.iter().enumerate() {
+                probs[sides + value] += exploded * prob;
+            }
+        }
+
+        if let Some(n) = self.modifiers.minimum {
+            let n = cmp::max(n, 0) as usize;
+            if probs.len() <= n {
+                probs.resize(n + 1, 0.0);
+            }
+
+            let below: f64 = probs[..n].iter().sum();
+            for prob in &mut probs[..n] {
+                *prob = 0.0;
+            }
+            probs[n] += below;
+        }
+
+        probs
+    }
+
+    /// The lowest possible total
+    #[must_use]
+    pub fn min(&self) -> i64 {
+        let probs = self.die_probabilities();
+        let min = probs.iter().position(|&p| p > 0.0).unwrap_or(0);
+
+        min as i64 * i64::from(self.kept_count())
+    }
+
+    /// The highest possible total
+    #[must_use]
+    pub fn max(&self) -> i64 {
+        let probs = self.die_probabilities();
+        let max = probs.iter().rposition(|&p| p > 0.0).unwrap_or(0);
+
+        max as i64 * i64::from(self.kept_count())
+    }
+
+    /// The exact average total
+    #[must_use]
+    pub fn avg(&self) -> f64 {
+        let probs = self.die_probabilities();
+
+        match self.modifiers.keep {
+            None => f64::from(self.count) * mean(&probs),
+            Some(keep) => {
+                let means = order_statistic_means(&probs, usize::from(self.count));
+                let n = usize::from(self.kept_count());
+
+                match keep {
+                    Keep::Highest(_) => means[means.len() - n..].iter().sum(),
+                    Keep::Lowest(_) => means[..n].iter().sum(),
+                }
+            }
+        }
+    }
+
+    /// The exact probability of every total
+    ///
+    /// # Errors
+    ///
+    /// Fails if the distribution has too many results or takes too long to
+    /// compute
+    pub fn pmf(&self) -> Result<Pmf> {
+        let probs = self.die_probabilities();
+
+        match self.modifiers.keep {
+            None => {
+                let die = Pmf::new(0, probs);
+                (0..self.count)
+                    .try_fold(Pmf::constant(0), |sum, _| sum.combine(Operator::Add, &die))
+            }
+            Some(keep) => {
+                let totals = kept_total_probabilities(
+                    &probs,
+                    usize::from(self.count),
+                    usize::from(self.kept_count()),
+                    matches!(keep, Keep::Highest(_)),
+                )?;
+
+                Ok(Pmf::new(0, totals))
+            }
+        }
+    }
+}
+
+/// The probability of every total of the `kept` highest (or lowest) of `n`
+/// dice with the given probabilities, indexed by the total
+fn kept_total_probabilities(
+    probs: &[f64],
+    n: usize,
+    kept: usize,
+    highest: bool,
+) -> Result<Vec<f64>> {
+    let mut values: Vec<usize> = (0..probs.len()).filter(|&v| probs[v] > 0.0).collect();
+    if highest {
+        values.reverse();
+    }
+
+    let len = kept * values.iter().max().copied().unwrap_or(0) + 1;
+    ensure!(
+        len <= MAX_OUTCOMES,
+        "Cannot compute the odds for more than {} different results",
+        MAX_OUTCOMES
+    );
+    dist::ensure_steps(values.len() * (n + 1) * (n + 1) * len)?;
+
+    // binomial[m][c] is m choose c
+    let mut binomial = vec![vec![1.0; n + 1]; n + 1];
+    for m in 1..=n {
+        for c in 1..m {
+            binomial[m][c] = binomial[m - 1][c - 1] + binomial[m - 1][c];
+        }
+    }
+
+    // Assign the dice from the best value to the worst. `totals[j][s]` is the
+    // chance that the first `j` dice are assigned and the kept ones among them
+    // add up to `s`.
+    let mut totals = vec![vec![0.0; len]; n + 1];
+    totals[0][0] = 1.0;
+
+    for value in values {
+        let p = probs[value];
+        let mut next = vec![vec![0.0; len]; n + 1];
+
+        for (j, row) in totals.iter().enumerate() {
+            for (s, &prob) in row.iter().enumerate().filter(|&(_, &prob)| prob > 0.0) {
+                for c in 0..=n - j {
+                    let counted = cmp::min(c, kept.saturating_sub(j));
+                    next[j + c][s + counted * value] +=
+                        prob * binomial[n - j][c] * p.powi(c as i32);
+                }
+            }
+        }
+
+        totals = next;
+    }
+
+    Ok(totals.swap_remove(n))
+}
+
+fn mean(probs: &[f64]) -> f64 {
+    probs
+        .iter()
+        .enumerate()
+        .map(|(value, prob)| value as f64 * prob)
+        .sum()
+}
+
+/// The expected value of the lowest, second lowest, ..., highest of `n` dice
+/// with the given probabilities
+fn order_statistic_means(probs: &[f64], n: usize) -> Vec<f64> {
+    // binomial coefficients for n
+    let mut binomial = vec![1.0; n + 1];
+    for i in 1..=n {
+        binomial[i] = binomial[i - 1] * (n - i + 1) as f64 / i as f64;
+    }
+
+    let mut means = vec![0.0; n];
+    let mut previous = vec![0.0; n];
+    let mut cdf = 0.0;
+
+    for (value, prob) in probs.iter().enumerate() {
+        cdf += prob;
+        let cdf = if cdf > 1.0 { 1.0 } else { cdf };
+
+        // chance that at least i of the n dice are at most `value`
+        let mut at_least = vec![0.0; n + 2];
+        for i in (0..=n).rev() {
+            let exactly = binomial[i] * cdf.powi(i as i32) * (1.0 - cdf).powi((n - i) as i32);
+            at_least[i] = at_least[i + 1] + exactly;
+        }
+
+        // the j-th lowest die is at most `value` if at least j + 1 dice are
+        for (j, mean) in means.iter_mut().enumerate() {
+            *mean += value as f64 * (at_least[j + 1] - previous[j]);
+        }
+        previous.copy_from_slice(&at_least[1..=n]);
+    }
+
+    means
+}
+
+impl fmt::Display for DicePool {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.count != 1 {
+            write!(f, "{}", self.count)?;
+        }
+
+        write!(f, "{}{}", self.die, self.modifiers)
+    }
+}
+
+impl fmt::Display for Modifiers {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.advantage {
+            Some(Advantage::Advantage) => write!(f, "adv")?,
+            Some(Advantage::Disadvantage) => write!(f, "dis")?,
+            None => {}
+        }
+
+        if let Some(n) = self.reroll_below {
+            write!(f, "r{}", n)?;
+        }
+
+        if self.explode {
+            write!(f, "!")?;
+        }
+
+        if let Some(n) = self.minimum {
+            write!(f, "min{}", n)?;
+        }
+
+        match self.keep {
+            Some(Keep::Highest(n)) => write!(f, "kh{}", n),
+            Some(Keep::Lowest(n)) => write!(f, "kl{}", n),
+            None => Ok(()),
+        }
+    }
+}
+
+impl fmt::Display for PoolRoll {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let rolls: Vec<String> = self
+            .rolls
+            .iter()
+            .zip(&self.kept)
+            .map(|(roll, &kept)| {
+                if kept {
+                    roll.to_string()
+                } else {
+                    format!("({})", roll)
+                }
+            })
+            .collect();
+
+        write!(f, "[{}]", rolls.join(", "))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::dice::rng::test::rng;
+
+    fn assert_close(a: f64, b: f64) {
+        assert!((a - b).abs() < 1e-9, "{} is not {}", a, b);
+    }
+
+    /// Roll the pool many times and compare the average to the exact one
+    fn assert_sampled_avg(pool: DicePool) {
+        let mut rng = rng();
+        let n = 20_000;
+        let sum: i64 = (0..n).map(|_| pool.roll(&mut rng).total).sum();
+        let sampled = sum as f64 / f64::from(n);
+
+        assert!(
+            (sampled - pool.avg()).abs() < 0.1,
+            "{}: sampled {} but expected {}",
+            pool,
+            sampled,
+            pool.avg()
+        );
+    }
+
+    #[test]
+    fn plain() {
+        let pool = DicePool::new(3, Die::D6);
+
+        assert_eq!(pool.min(), 3);
+        assert_eq!(pool.max(), 18);
+        assert_close(pool.avg(), 10.5);
+    }
+
+    #[test]
+    fn advantage() {
+        let pool = DicePool::new(1, Die::D20).advantage();
+
+        assert_eq!(pool.min(), 1);
+        assert_eq!(pool.max(), 20);
+        assert_close(pool.avg(), 13.825);
+        assert_close(pool.avg(), DicePool::new(2, Die::D20).keep_highest(1).avg());
+        assert_sampled_avg(pool);
+    }
+
+    #[test]
+    fn disadvantage() {
+        let pool = DicePool::new(1, Die::D20).disadvantage();
+
+        assert_close(pool.avg(), 7.175);
+        assert_close(pool.avg(), DicePool::new(2, Die::D20).keep_lowest(1).avg());
+        assert_sampled_avg(pool);
+    }
+
+    #[test]
+    fn reroll_below() {
+        let pool = DicePool::new(1, Die::D6).reroll_below(2);
+
+        assert_eq!(pool.min(), 1);
+        assert_close(pool.avg(), 3.5 / 6.0 + 20.0 / 6.0);
+        assert_sampled_avg(pool);
+    }
+
+    #[test]
+    fn explode() {
+        let pool = DicePool::new(1, Die::D6).explode();
+
+        assert_eq!(pool.min(), 1);
+        assert_eq!(pool.max(), 6 * (MAX_EXPLOSIONS as i64 + 1));
+        // 3.5 * (1 + 1/6 + 1/6^2 + ...) with the last explosion cut off
+        let expected: f64 = (0..=MAX_EXPLOSIONS as i32)
+            .map(|i| 3.5 / 6f64.powi(i))
+            .sum();
+        assert_close(pool.avg(), expected);
+        assert_sampled_avg(pool);
+    }
+
+    #[test]
+    fn minimum() {
+        let pool = DicePool::new(2, Die::D6).minimum(3);
+
+        assert_eq!(pool.min(), 6);
+        assert_eq!(pool.max(), 12);
+        assert_close(pool.avg(), 8.0);
+        assert_sampled_avg(pool);
+    }
+
+    #[test]
+    fn keep_highest() {
+        let pool = DicePool::new(4, Die::D6).keep_highest(3);
+
+        assert_eq!(pool.min(), 3);
+        assert_eq!(pool.max(), 18);
+        assert_close(pool.avg(), 15869.0 / 1296.0);
+        assert_sampled_avg(pool);
+    }
+
+    #[test]
+    fn combined() {
+        let pool = DicePool::new(4, Die::D6)
+            .advantage()
+            .reroll_below(2)
+            .explode()
+            .minimum(2)
+            .keep_lowest(2);
+
+        assert_eq!(pool.min(), 4);
+        assert_eq!(pool.to_string(), "4d6advr2!min2kl2");
+        assert_eq!(pool.modifiers.to_string(), "advr2!min2kl2");
+        assert_sampled_avg(pool);
+    }
+
+    #[test]
+    fn pmf() {
+        let pmf = DicePool::new(4, Die::D6).keep_highest(3).pmf().unwrap();
+
+        assert_eq!(pmf.min(), 3);
+        assert_eq!(pmf.max(), 18);
+        // three or four sixes
+        assert_close(pmf.probability(18), 21.0 / 1296.0);
+        assert_close(pmf.probability(3), 1.0 / 1296.0);
+
+        let pmf = DicePool::new(1, Die::D20).advantage().pmf().unwrap();
+        assert_close(pmf.probability(20), 39.0 / 400.0);
+        assert_close(pmf.mean(), 13.825);
+
+        let pmf = DicePool::new(2, Die::D6).minimum(3).pmf().unwrap();
+        assert_close(pmf.probability(6), 9.0 / 36.0);
+    }
+
+    #[test]
+    fn validate() {
+        assert!(DicePool::new(2, Die::D6).keep_highest(2).validate().is_ok());
+        assert!(DicePool::new(0, Die::D6).validate().is_err());
+        assert!(DicePool::new(2, Die::D6)
+            .keep_highest(3)
+            .validate()
+            .is_err());
+        assert!(DicePool::new(2, Die::D6).keep_lowest(0).validate().is_err());
+        assert!(DicePool::new(1, Die::new(1)).explode().validate().is_err());
+    }
+}
@@ -0,0 +1,242 @@
+use anyhow::{Context, Result};
+use rand::{Error, RngCore, SeedableRng};
+use rand_chacha::ChaCha20Rng;
+use sha2::{Digest, Sha256};
+use std::{convert::TryInto, fmt};
+
+/// The seed of a [`GameRng`]
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Seed([u8; 32]);
+
+impl Seed {
+    /// A new random seed
+    #[must_use]
+    pub fn random() -> Self {
+        let mut seed = [0; 32];
+        rand::thread_rng().fill_bytes(&mut seed);
+
+        Self(seed)
+    }
+
+    /// A seed from bytes stored earlier
+    ///
+    /// # Errors
+    ///
+    /// Fails unless there are exactly 32 bytes
+    pub fn from_slice(bytes: &[u8]) -> Result<Self> {
+        Ok(Self(
+            bytes.try_into().context("A seed must be 32 bytes long")?,
+        ))
+    }
+
+    #[must_use]
+    pub fn as_bytes(&self) -> &[u8; 32] {
+        &self.0
+    }
+
+    /// The hash of the seed, which can be published before rolling without
+    /// giving away the rolls
+    #[must_use]
+    pub fn commitment(&self) -> Commitment {
+        Commitment(Sha256::digest(&self.0).into())
+    }
+}
+
+/// The SHA-256 hash of a [`Seed`]
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Commitment([u8; 32]);
+
+impl Commitment {
+    /// A commitment from bytes stored earlier
+    ///
+    /// # Errors
+    ///
+    /// Fails unless there are exactly 32 bytes
+    pub fn from_slice(bytes: &[u8]) -> Result<Self> {
+        Ok(Self(
+            bytes
+                .try_into()
+                .context("A commitment must be 32 bytes long")?,
+        ))
+    }
+
+    #[must_use]
+    pub fn as_bytes(&self) -> &[u8; 32] {
+        &self.0
+    }
+
+    /// Whether this is the commitment of `seed`
+    #[must_use]
+    pub fn matches(&self, seed: &Seed) -> bool {
+        seed.commitment() == *self
+    }
+}
+
+impl fmt::Debug for Commitment {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Commitment({})", self)
+    }
+}
+
+impl fmt::Display for Commitment {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write_hex(f, &self.0)
+    }
+}
+
+fn write_hex(f: &mut fmt::Formatter<'_>, bytes: &[u8]) -> fmt::Result {
+    for byte in bytes {
+        write!(f, "{:02x}", byte)?;
+    }
+
+    Ok(())
+}
+
+impl From<[u8; 32]> for Seed {
+    fn from(seed: [u8; 32]) -> Self {
+        Self(seed)
+    }
+}
+
+impl fmt::Debug for Seed {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Seed({})", self)
+    }
+}
+
+impl fmt::Display for Seed {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write_hex(f, &self.0)
+    }
+}
+
+/// The random number generator for all game randomness.
+///
+/// Every encounter gets its own generator. The same seed always produces the
+/// same rolls, so any encounter can be replayed from its seed.
+#[derive(Debug, Clone)]
+pub struct GameRng {
+    seed: Seed,
+    inner: ChaCha20Rng,
+}
+
+impl GameRng {
+    /// A generator with a new random seed
+    #[must_use]
+    pub fn new() -> Self {
+        Self::from_seed(Seed::random())
+    }
+
+    #[must_use]
+    pub fn from_seed(seed: Seed) -> Self {
+        Self {
+            seed,
+            inner: ChaCha20Rng::from_seed(seed.0),
+        }
+    }
+
+    #[must_use]
+    pub fn seed(&self) -> Seed {
+        self.seed
+    }
+}
+
+impl Default for GameRng {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl RngCore for GameRng {
+    fn next_u32(&mut self) -> u32 {
+        self.inner.next_u32()
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        self.inner.next_u64()
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        self.inner.fill_bytes(dest);
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), Error> {
+        self.inner.try_fill_bytes(dest)
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod test {
+    use super::*;
+
+    /// The seed used by all tests so the expected rolls never change
+    pub const SEED: [u8; 32] = [
+        0x46, 0x65, 0x65, 0x6c, 0x73, 0x44, 0x61, 0x6e, 0x6b, 0x4d, 0x61, 0x6e, 0x4b, 0x61, 0x70,
+        0x70, 0x61, 0x31, 0x32, 0x33, 0x46, 0x65, 0x65, 0x6c, 0x73, 0x44, 0x61, 0x6e, 0x6b, 0x4d,
+        0x61, 0x6e,
+    ];
+
+    pub fn rng() -> GameRng {
+        GameRng::from_seed(SEED.into())
+    }
+
+    /// The `n`th of many fixed seeds, for tests that need several encounters
+    pub fn seed(n: u8) -> Seed {
+        let mut seed = SEED;
+        seed[0] ^= n;
+        seed.into()
+    }
+
+    #[test]
+    fn is_rng_seeded() {
+        let mut rng = rng();
+        assert_eq!(rng.next_u64(), 15_084_443_005_315_021_354);
+        assert_eq!(rng.next_u64(), 13_672_135_499_988_464_561);
+        assert_eq!(rng.next_u64(), 4_380_429_139_279_809_442);
+    }
+
+    #[test]
+    fn replay() {
+        let mut rng = GameRng::new();
+        let mut replay = GameRng::from_seed(rng.seed());
+
+        for _ in 0..100 {
+            assert_eq!(rng.next_u64(), replay.next_u64());
+        }
+    }
+
+    #[test]
+    fn new_seeds_differ() {
+        assert_ne!(GameRng::new().seed(), GameRng::new().seed());
+    }
+
+    #[test]
+    fn commitment() {
+        let seed = Seed::from(SEED);
+        let commitment = seed.commitment();
+
+        // sha256 of the seed bytes
+        assert_eq!(
+            commitment.to_string(),
+            "b6da8fd302cd08131014126bc89f7047103e0308b8ba88f5526ab0f7665103b5"
+        );
+        assert!(commitment.matches(&seed));
+        assert!(!commitment.matches(&Seed::random()));
+        assert_eq!(
+            Commitment::from_slice(commitment.as_bytes()).unwrap(),
+            commitment
+        );
+    }
+
+    #[test]
+    fn seed_roundtrip() {
+        let seed = Seed::random();
+
+        assert_eq!(Seed::from_slice(seed.as_bytes()).unwrap(), seed);
+        assert!(Seed::from_slice(&[0; 31]).is_err());
+        assert_eq!(
+            Seed::from(SEED).to_string(),
+            "4665656c7344616e6b4d616e4b617070613132334665656c7344616e6b4d616e"
+        );
+    }
+}
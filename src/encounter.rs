@@ -0,0 +1,493 @@
+use crate::{
+    character::{CharacterStats, Level},
+    fight::{self, Combatant, FightResult, Monster, Outcome, MONSTERS},
+    item::{Item, LootEntry, LOOT},
+    Commitment, DicePool, Die, Expression, GameRng, PoolRoll, Roll, Seed,
+};
+use anyhow::Result;
+use std::{convert::TryFrom, fmt};
+
+/// The gold of a treasure compared to the usual gold of a fight
+const TREASURE_MULTIPLIER: u32 = 3;
+
+/// How far the challenge rating can be from the level of the character for a
+/// medium reward
+const REWARD_TOLERANCE: u8 = 1;
+
+/// Everything that happens after a player enters the dungeon.
+///
+/// Every roll of an encounter comes from its own [`GameRng`] and is recorded,
+/// so that it can be checked with [`verify`] once the seed is revealed.
+/// Replaying an encounter with the same seed and stats rolls exactly the same
+/// numbers.
+#[derive(Debug, Clone)]
+pub struct Encounter {
+    stats: CharacterStats,
+    rng: GameRng,
+    rolls: Vec<RecordedRoll>,
+    dice: Vec<PoolRoll>,
+}
+
+/// A roll made during an encounter
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RecordedRoll {
+    pub expression: Expression,
+    pub total: i64,
+}
+
+/// How big the reward of an encounter is, decided by the challenge rating
+/// compared to the level of the character
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RewardLevel {
+    Small,
+    Medium,
+    Big,
+}
+
+/// The result of checking the rolls of an encounter against its seed
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Verification {
+    /// The seed matches the commitment and produces every roll
+    Valid { rolls: usize },
+    /// The seed is not the one that was committed to
+    WrongSeed,
+    /// The roll at `index` does not follow from the seed
+    Mismatch {
+        index: usize,
+        expression: Expression,
+        recorded: i64,
+        replayed: i64,
+    },
+}
+
+impl Encounter {
+    /// A new encounter with a random seed
+    #[must_use]
+    pub fn new(stats: CharacterStats) -> Self {
+        Self::from_seed(stats, Seed::random())
+    }
+
+    /// Replay an encounter
+    #[must_use]
+    pub fn from_seed(stats: CharacterStats, seed: Seed) -> Self {
+        Self {
+            stats,
+            rng: GameRng::from_seed(seed),
+            rolls: Vec::new(),
+            dice: Vec::new(),
+        }
+    }
+
+    #[must_use]
+    pub fn seed(&self) -> Seed {
+        self.rng.seed()
+    }
+
+    /// The hash of the seed to publish before rolling
+    #[must_use]
+    pub fn commitment(&self) -> Commitment {
+        self.seed().commitment()
+    }
+
+    #[must_use]
+    pub fn stats(&self) -> &CharacterStats {
+        &self.stats
+    }
+
+    /// Roll `expression` with the generator of this encounter and record it
+    ///
+    /// # Errors
+    ///
+    /// Fails if `expression` cannot be rolled
+    pub fn roll(&mut self, expression: &Expression) -> Result<Roll> {
+        let roll = expression.roll(&mut self.rng)?;
+
+        self.rolls.push(RecordedRoll {
+            expression: expression.clone(),
+            total: roll.total,
+        });
+        self.dice.extend(roll.pools.iter().cloned());
+
+        Ok(roll)
+    }
+
+    /// Roll which monster the character meets
+    ///
+    /// # Errors
+    ///
+    /// Fails if there are more monsters than sides a die can have
+    pub fn choose_monster(&mut self) -> Result<&'static Monster> {
+        let die = Die::new(u16::try_from(MONSTERS.len())?);
+        let index = self.roll(&Expression::Dice(DicePool::new(1, die)))?.total - 1;
+
+        Ok(&MONSTERS[usize::try_from(index)?])
+    }
+
+    /// Let `character` fight `monster`
+    ///
+    /// # Errors
+    ///
+    /// Fails if a roll of the fight fails
+    pub fn fight(&mut self, character: Combatant, monster: &Monster) -> Result<Outcome> {
+        let mut outcome = fight::run(self, character, monster.combatant())?;
+
+        let reward = RewardLevel::new(monster.challenge_rating, self.stats.level);
+        outcome.experience = reward.experience(outcome.result);
+        if outcome.result == FightResult::Won {
+            outcome.gold = self.gold(reward)?;
+
+            let chance = self
+                .roll(&Expression::Dice(DicePool::new(1, Die::D100)))?
+                .total;
+            if chance <= self.stats.treasure_chance() {
+                outcome.treasure = true;
+                outcome.gold *= TREASURE_MULTIPLIER;
+            }
+        }
+
+        Ok(outcome)
+    }
+
+    /// Roll the gold the character finds
+    ///
+    /// # Errors
+    ///
+    /// Fails if the gold dice cannot be rolled
+    pub fn gold(&mut self, reward: RewardLevel) -> Result<u32> {
+        let gold = self.roll(&Expression::Dice(reward.gold()))?.total;
+
+        Ok(u32::try_from(gold)?)
+    }
+
+    /// Roll whether the character finds an item after a won fight and which.
+    /// Lucky characters find items more often and draw twice, keeping the
+    /// rarer item.
+    ///
+    /// # Errors
+    ///
+    /// Fails if the loot table cannot be rolled
+    pub fn loot(&mut self, reward: RewardLevel) -> Result<Option<&'static Item>> {
+        let chance = self
+            .roll(&Expression::Dice(DicePool::new(1, Die::D100)))?
+            .total;
+        if chance > reward.loot_chance() + self.stats.loot_chance_bonus() {
+            return Ok(None);
+        }
+
+        let mut entry = self.draw_loot()?;
+        if self.stats.lucky_loot() {
+            let second = self.draw_loot()?;
+            if second.weight < entry.weight {
+                entry = second;
+            }
+        }
+
+        Ok(Item::by_key(entry.key))
+    }
+
+    /// Roll an entry of the loot table
+    fn draw_loot(&mut self) -> Result<&'static LootEntry> {
+        let total: u16 = LOOT.iter().map(|entry| entry.weight).sum();
+        let mut roll = self
+            .roll(&Expression::Dice(DicePool::new(1, Die::new(total))))?
+            .total;
+
+        for entry in &LOOT {
+            roll -= i64::from(entry.weight);
+            if roll <= 0 {
+                return Ok(entry);
+            }
+        }
+
+        Ok(&LOOT[LOOT.len() - 1])
+    }
+
+    /// Every roll made so far, in order
+    #[must_use]
+    pub fn rolls(&self) -> &[RecordedRoll] {
+        &self.rolls
+    }
+
+    /// Every single dice pool rolled so far, in order
+    #[must_use]
+    pub fn dice(&self) -> &[PoolRoll] {
+        &self.dice
+    }
+}
+
+/// Check that `seed` is the one committed to and recompute every recorded
+/// roll from it
+///
+/// # Errors
+///
+/// Fails if a recorded expression cannot be rolled
+pub fn verify(commitment: &Commitment, seed: Seed, rolls: &[RecordedRoll]) -> Result<Verification> {
+    if !commitment.matches(&seed) {
+        return Ok(Verification::WrongSeed);
+    }
+
+    let mut rng = GameRng::from_seed(seed);
+
+    for (index, roll) in rolls.iter().enumerate() {
+        let replayed = roll.expression.roll(&mut rng)?.total;
+
+        if replayed != roll.total {
+            return Ok(Verification::Mismatch {
+                index,
+                expression: roll.expression.clone(),
+                recorded: roll.total,
+                replayed,
+            });
+        }
+    }
+
+    Ok(Verification::Valid { rolls: rolls.len() })
+}
+
+impl RewardLevel {
+    #[must_use]
+    pub fn new(challenge_rating: u8, level: Level) -> Self {
+        let level = level.get();
+        if challenge_rating.saturating_add(REWARD_TOLERANCE) < level {
+            Self::Small
+        } else if challenge_rating > level.saturating_add(REWARD_TOLERANCE) {
+            Self::Big
+        } else {
+            Self::Medium
+        }
+    }
+
+    /// The chance in percent to find an item after winning
+    #[must_use]
+    pub fn loot_chance(self) -> i64 {
+        match self {
+            Self::Small => 25,
+            Self::Medium => 40,
+            Self::Big => 60,
+        }
+    }
+
+    /// The dice for the gold found after winning
+    #[must_use]
+    pub fn gold(self) -> DicePool {
+        match self {
+            Self::Small => DicePool::new(2, Die::D6),
+            Self::Medium => DicePool::new(4, Die::D6),
+            Self::Big => DicePool::new(8, Die::D6),
+        }
+    }
+
+    /// The experience for a fight with `result`. Fleeing gives a quarter and
+    /// losing a tenth of the experience for winning.
+    #[must_use]
+    pub fn experience(self, result: FightResult) -> u32 {
+        let experience = match self {
+            Self::Small => 50,
+            Self::Medium => 100,
+            Self::Big => 200,
+        };
+
+        match result {
+            FightResult::Won => experience,
+            FightResult::Fled => experience / 4,
+            FightResult::Lost => experience / 10,
+        }
+    }
+}
+
+impl Verification {
+    #[must_use]
+    pub fn is_valid(&self) -> bool {
+        matches!(self, Self::Valid { .. })
+    }
+}
+
+impl fmt::Display for Verification {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Valid { rolls } => {
+                write!(f, "the seed matches and all {} rolls check out", rolls)
+            }
+            Self::WrongSeed => write!(f, "the seed does not match the published hash"),
+            Self::Mismatch {
+                index,
+                expression,
+                recorded,
+                replayed,
+            } => write!(
+                f,
+                "roll #{} ({}) was {} but the seed gives {}",
+                index + 1,
+                expression,
+                recorded,
+                replayed
+            ),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::dice::rng::test::seed;
+
+    fn encounter() -> Encounter {
+        let mut encounter = Encounter::new(CharacterStats::default());
+
+        for text in &["d20+3", "2d6", "4d6kh3", "d100"] {
+            encounter.roll(&Expression::parse(text).unwrap()).unwrap();
+        }
+
+        encounter
+    }
+
+    #[test]
+    fn valid() {
+        let encounter = encounter();
+        let verification =
+            verify(&encounter.commitment(), encounter.seed(), encounter.rolls()).unwrap();
+
+        assert_eq!(verification, Verification::Valid { rolls: 4 });
+        assert_eq!(encounter.dice().len(), 4);
+    }
+
+    #[test]
+    fn wrong_seed() {
+        let encounter = encounter();
+        let verification =
+            verify(&encounter.commitment(), Seed::random(), encounter.rolls()).unwrap();
+
+        assert_eq!(verification, Verification::WrongSeed);
+    }
+
+    #[test]
+    fn tampered_roll() {
+        let encounter = encounter();
+        let mut rolls = encounter.rolls().to_vec();
+        rolls[1].total += 1;
+
+        let verification = verify(&encounter.commitment(), encounter.seed(), &rolls).unwrap();
+
+        assert!(!verification.is_valid());
+        assert_eq!(
+            verification,
+            Verification::Mismatch {
+                index: 1,
+                expression: Expression::parse("2d6").unwrap(),
+                recorded: rolls[1].total,
+                replayed: rolls[1].total - 1,
+            }
+        );
+    }
+
+    #[test]
+    fn reward_level() {
+        assert_eq!(RewardLevel::new(1, Level::from(3)), RewardLevel::Small);
+        assert_eq!(RewardLevel::new(2, Level::from(2)), RewardLevel::Medium);
+        assert_eq!(RewardLevel::new(2, Level::from(1)), RewardLevel::Medium);
+        assert_eq!(RewardLevel::new(3, Level::from(1)), RewardLevel::Big);
+
+        let level = Level::from(5);
+        assert_eq!(RewardLevel::new(3, level), RewardLevel::Small);
+        assert_eq!(RewardLevel::new(4, level), RewardLevel::Medium);
+        assert_eq!(RewardLevel::new(5, level), RewardLevel::Medium);
+        assert_eq!(RewardLevel::new(6, level), RewardLevel::Medium);
+        assert_eq!(RewardLevel::new(7, level), RewardLevel::Big);
+
+        assert_eq!(RewardLevel::Medium.experience(FightResult::Won), 100);
+        assert_eq!(RewardLevel::Medium.experience(FightResult::Fled), 25);
+        assert_eq!(RewardLevel::Big.experience(FightResult::Lost), 20);
+
+        assert_eq!(RewardLevel::Small.gold().to_string(), "2d6");
+        assert_eq!(RewardLevel::Big.gold().to_string(), "8d6");
+    }
+
+    #[test]
+    fn gold() {
+        let mut encounter = Encounter::from_seed(CharacterStats::default(), seed(0));
+        let gold = encounter.gold(RewardLevel::Medium).unwrap();
+
+        assert_eq!(gold, 15);
+        assert_eq!(encounter.rolls().len(), 1);
+    }
+
+    #[test]
+    fn loot() {
+        let found: Vec<_> = (0..10)
+            .map(|n| {
+                let mut encounter = Encounter::from_seed(CharacterStats::default(), seed(n));
+                let item = encounter.loot(RewardLevel::Big).unwrap();
+
+                let verification =
+                    verify(&encounter.commitment(), encounter.seed(), encounter.rolls()).unwrap();
+                assert!(verification.is_valid());
+
+                item.map(|item| item.key)
+            })
+            .collect();
+
+        assert_eq!(
+            found,
+            [
+                Some("warhammer"),
+                Some("four_leaf_clover"),
+                Some("potion_of_healing"),
+                Some("torch"),
+                None,
+                Some("warhammer"),
+                None,
+                None,
+                Some("gemstone"),
+                Some("antidote"),
+            ]
+        );
+    }
+
+    #[test]
+    fn lucky_loot() {
+        let stats = CharacterStats {
+            luck: 4.into(),
+            ..CharacterStats::default()
+        };
+
+        let found: Vec<_> = (0..10)
+            .map(|n| {
+                let mut encounter = Encounter::from_seed(stats, seed(n));
+                let lucky_item = encounter.loot(RewardLevel::Big).unwrap();
+                let mut encounter = Encounter::from_seed(CharacterStats::default(), seed(n));
+                let item = encounter.loot(RewardLevel::Big).unwrap();
+
+                // the same rolls, but luck raises the chance and keeps the
+                // rarer of two items
+                if let Some(item) = item {
+                    let weight = |item: &Item| {
+                        LOOT.iter()
+                            .find(|entry| entry.key == item.key)
+                            .unwrap()
+                            .weight
+                    };
+                    assert!(weight(lucky_item.unwrap()) <= weight(item));
+                }
+
+                lucky_item.map(|item| item.key)
+            })
+            .collect();
+
+        // seed 6 finds an item only with luck
+        assert_eq!(
+            found,
+            [
+                Some("warhammer"),
+                Some("four_leaf_clover"),
+                Some("scroll_of_haste"),
+                Some("ring_of_protection"),
+                None,
+                Some("warhammer"),
+                Some("elixir_of_agility"),
+                None,
+                Some("scroll_of_haste"),
+                Some("holy_water"),
+            ]
+        );
+    }
+}
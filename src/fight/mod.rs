@@ -0,0 +1,1000 @@
+//! Fights between a character and a monster, resolved round by round with the
+//! rolls of an [`Encounter`].
+
+mod monster;
+mod status;
+
+pub use monster::{Monster, MONSTERS};
+pub use status::{Status, StatusKind};
+
+use crate::{
+    character::{Ability, CharacterStats, ClassFeature, Power, PowerEffect, Spell, Strike},
+    encounter::Encounter,
+    item::{Effect, Item},
+    DicePool, Die, Expression, Operator, Roll,
+};
+use anyhow::Result;
+use std::{cmp, fmt};
+
+/// Fights that take longer end with the character fleeing
+pub const MAX_ROUNDS: u32 = 20;
+
+/// The damage the arcane ward absorbs
+const ARCANE_WARD: i64 = 5;
+
+/// Hits inflict the status of the attacker when this die rolls its maximum
+const ON_HIT_CHANCE: Die = Die::D4;
+
+/// Characters regain all their health over a long rest of this many minutes
+pub const FULL_REST_MINUTES: i64 = 8 * 60;
+
+/// Someone taking part in a fight
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Combatant {
+    pub name: String,
+    pub health: i64,
+    pub max_health: i64,
+    /// Attacks need to roll more than this to hit
+    pub armor_class: i16,
+    pub initiative: Expression,
+    pub attack_die: DicePool,
+    pub attack_bonus: i16,
+    /// The lowest natural roll of the attack die that is a critical hit
+    pub crit_threshold: i16,
+    pub damage: DicePool,
+    pub crit_damage: DicePool,
+    pub damage_bonus: i16,
+    pub feature: Option<ClassFeature>,
+    /// Healing items to use in this order
+    pub potions: Vec<&'static Item>,
+    /// Use a potion instead of attacking when the health drops below this
+    pub heal_below: i64,
+    pub statuses: Vec<Status>,
+    /// The status hits inflict now and then, with the rounds it lasts
+    pub on_hit: Option<(StatusKind, u32)>,
+    /// Active abilities, used whenever they are ready
+    pub powers: Vec<&'static Power>,
+    /// The saving throws against spells, by ability
+    pub saving_throws: Vec<Expression>,
+    pub mana: i64,
+    pub spells: Vec<&'static Spell>,
+    /// Saving throws against the spells need to reach this
+    pub spell_save_dc: i16,
+    /// The chance in percent to reroll a failed attack or saving throw
+    pub reroll_chance: i64,
+}
+
+/// How a fight ended for the character
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FightResult {
+    Won,
+    Lost,
+    Fled,
+}
+
+/// Everything worth telling about a finished fight
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Outcome {
+    pub result: FightResult,
+    pub opponent: String,
+    pub rounds: u32,
+    /// The health the character has left
+    pub health: i64,
+    pub max_health: i64,
+    pub critical_hits: u32,
+    pub experience: u32,
+    pub gold: u32,
+    /// Whether the gold comes from a treasure
+    pub treasure: bool,
+    /// The items the character used up
+    pub consumed: Vec<&'static Item>,
+    /// The names of the powers and spells the character used, in order
+    pub actions: Vec<&'static str>,
+    /// The status effects that carry over to the next encounter
+    pub lingering: Vec<StatusKind>,
+}
+
+/// The state of a combatant during a fight
+struct Fighter {
+    combatant: Combatant,
+    acted_first: bool,
+    feature_used: bool,
+    ward: i64,
+    consumed: Vec<&'static Item>,
+    /// The turns until each power is ready again
+    cooldowns: Vec<u32>,
+    actions: Vec<&'static str>,
+}
+
+impl Combatant {
+    /// The character with `stats` ready to fight
+    #[must_use]
+    pub fn from_stats(name: &str, stats: &CharacterStats) -> Self {
+        // the health of a character is far below the range of an `i64`
+        #[allow(clippy::cast_possible_truncation)]
+        let max_health = stats.max_health().round() as i64;
+
+        Self {
+            name: name.to_string(),
+            health: max_health,
+            max_health,
+            armor_class: stats.armor_class(),
+            initiative: stats.initiative_roll(),
+            attack_die: stats.attack_die(),
+            attack_bonus: stats.attack_bonus(),
+            crit_threshold: stats.crit_threshold(),
+            damage: stats.damage_dice(),
+            crit_damage: stats.crit_damage_dice(),
+            damage_bonus: stats.primary_attribute().modifier(),
+            feature: Some(stats.class.feature()),
+            potions: Vec::new(),
+            heal_below: 0,
+            statuses: Vec::new(),
+            on_hit: None,
+            powers: stats.class.unlocked_powers(stats.level),
+            saving_throws: Ability::ALL
+                .iter()
+                .map(|&ability| stats.saving_throw_roll(ability))
+                .collect(),
+            mana: stats.max_mana(),
+            spells: stats.class.unlocked_spells(stats.level),
+            spell_save_dc: stats.spell_save_dc().unwrap_or_default(),
+            reroll_chance: stats.reroll_chance(),
+        }
+    }
+
+    /// The roll for a saving throw against `ability`
+    #[must_use]
+    pub fn saving_throw(&self, ability: Ability) -> &Expression {
+        &self.saving_throws[ability as usize]
+    }
+}
+
+impl Fighter {
+    fn new(combatant: Combatant) -> Self {
+        let ward = match combatant.feature {
+            Some(ClassFeature::ArcaneWard) => ARCANE_WARD,
+            _ => 0,
+        };
+
+        Self {
+            cooldowns: vec![0; combatant.powers.len()],
+            combatant,
+            acted_first: false,
+            feature_used: false,
+            ward,
+            consumed: Vec::new(),
+            actions: Vec::new(),
+        }
+    }
+
+    fn is_down(&self) -> bool {
+        self.combatant.health <= 0
+    }
+
+    fn take_damage(&mut self, damage: i64) {
+        let absorbed = cmp::min(self.ward, damage);
+        self.ward -= absorbed;
+        self.combatant.health -= damage - absorbed;
+    }
+
+    fn heal(&mut self, health: i64) {
+        self.combatant.health = cmp::min(
+            self.combatant.health + cmp::max(health, 0),
+            self.combatant.max_health,
+        );
+    }
+
+    fn has_status(&self, kind: StatusKind) -> bool {
+        self.combatant
+            .statuses
+            .iter()
+            .any(|status| status.kind == kind)
+    }
+
+    /// Let the status effects act at the start of a turn and count them down.
+    /// Returns whether the fighter loses the turn.
+    fn start_turn(&mut self, encounter: &mut Encounter) -> Result<bool> {
+        let kinds: Vec<_> = self
+            .combatant
+            .statuses
+            .iter()
+            .map(|status| status.kind)
+            .collect();
+
+        for kind in kinds {
+            if let Some(dice) = kind.damage() {
+                let damage = encounter.roll(&Expression::Dice(dice))?.total;
+                self.take_damage(damage);
+            }
+            if let Some(dice) = kind.healing() {
+                let healed = encounter.roll(&Expression::Dice(dice))?.total;
+                self.heal(healed);
+            }
+        }
+
+        let stunned = self.has_status(StatusKind::Stun);
+        for status in &mut self.combatant.statuses {
+            if let Some(rounds) = &mut status.rounds {
+                *rounds = rounds.saturating_sub(1);
+            }
+        }
+        self.combatant
+            .statuses
+            .retain(|status| status.rounds != Some(0));
+
+        for cooldown in &mut self.cooldowns {
+            *cooldown = cooldown.saturating_sub(1);
+        }
+
+        Ok(stunned)
+    }
+
+    /// The index of the power to use this turn, the strongest ready one that
+    /// makes sense now
+    fn ready_power(&self) -> Option<usize> {
+        let combatant = &self.combatant;
+
+        (0..combatant.powers.len()).rev().find(|&index| {
+            self.cooldowns[index] == 0
+                && match combatant.powers[index].effect {
+                    PowerEffect::Heal(_) => combatant.health * 2 < combatant.max_health,
+                    PowerEffect::Status(kind, _) => !self.has_status(kind),
+                    PowerEffect::Strike(_) | PowerEffect::Blast(..) => true,
+                }
+        })
+    }
+
+    /// The strongest spell against `defender` there is enough mana for
+    fn ready_spell(&self, defender: &Self) -> Option<&'static Spell> {
+        self.combatant.spells.iter().rev().copied().find(|spell| {
+            i64::from(spell.mana) <= self.combatant.mana
+                && spell
+                    .inflicts
+                    .iter()
+                    .all(|&(kind, _)| !defender.has_status(kind))
+        })
+    }
+}
+
+/// The result once either side is down
+fn decided(character: &Fighter, monster: &Fighter) -> Option<FightResult> {
+    if character.is_down() {
+        Some(FightResult::Lost)
+    } else if monster.is_down() {
+        Some(FightResult::Won)
+    } else {
+        None
+    }
+}
+
+/// Fight `monster` until one side is down or [`MAX_ROUNDS`] have passed.
+/// `character` takes the first turn if the initiatives are tied.
+///
+/// # Errors
+///
+/// Fails if a roll of a combatant cannot be made
+pub fn run(encounter: &mut Encounter, character: Combatant, monster: Combatant) -> Result<Outcome> {
+    let opponent = monster.name.clone();
+    let mut character = Fighter::new(character);
+    let mut monster = Fighter::new(monster);
+    let mut critical_hits = 0;
+
+    let character_initiative = encounter.roll(&character.combatant.initiative)?.total;
+    let monster_initiative = encounter.roll(&monster.combatant.initiative)?.total;
+    let character_first = character_initiative >= monster_initiative;
+    character.acted_first = character_first;
+    monster.acted_first = !character_first;
+
+    let mut rounds = 0;
+    let result = loop {
+        if rounds == MAX_ROUNDS {
+            break FightResult::Fled;
+        }
+        rounds += 1;
+
+        // status effects can take down the fighter whose turn it is
+        if character_first {
+            critical_hits += turn(encounter, &mut character, &mut monster, rounds)?;
+            if let Some(result) = decided(&character, &monster) {
+                break result;
+            }
+            turn(encounter, &mut monster, &mut character, rounds)?;
+            if let Some(result) = decided(&character, &monster) {
+                break result;
+            }
+        } else {
+            turn(encounter, &mut monster, &mut character, rounds)?;
+            if let Some(result) = decided(&character, &monster) {
+                break result;
+            }
+            critical_hits += turn(encounter, &mut character, &mut monster, rounds)?;
+            if let Some(result) = decided(&character, &monster) {
+                break result;
+            }
+        }
+    };
+
+    // effects from the encounter itself linger, the stored ones are counted
+    // down after it
+    let lingering = character
+        .combatant
+        .statuses
+        .iter()
+        .filter(|status| status.rounds.is_some() && status.kind.lingers())
+        .map(|status| status.kind)
+        .collect();
+
+    Ok(Outcome {
+        result,
+        opponent,
+        rounds,
+        health: cmp::max(character.combatant.health, 0),
+        max_health: character.combatant.max_health,
+        critical_hits,
+        experience: 0,
+        gold: 0,
+        treasure: false,
+        consumed: character.consumed,
+        actions: character.actions,
+        lingering,
+    })
+}
+
+/// Let `attacker` act once. Returns the number of critical hits.
+fn turn(
+    encounter: &mut Encounter,
+    attacker: &mut Fighter,
+    defender: &mut Fighter,
+    round: u32,
+) -> Result<u32> {
+    if attacker.start_turn(encounter)? || attacker.is_down() {
+        return Ok(0);
+    }
+
+    let combatant = &attacker.combatant;
+
+    if combatant.feature == Some(ClassFeature::HealingPrayer)
+        && !attacker.feature_used
+        && combatant.health * 2 < combatant.max_health
+    {
+        let healing = Expression::with_bonus(
+            DicePool::new(1, Die::D8),
+            cmp::max(combatant.damage_bonus, 0).into(),
+        );
+        let healed = encounter.roll(&healing)?.total;
+
+        attacker.feature_used = true;
+        attacker.heal(healed);
+        return Ok(0);
+    }
+
+    if combatant.health < combatant.heal_below && !combatant.potions.is_empty() {
+        let potion = attacker.combatant.potions.remove(0);
+        if let Some(healing) = potion.effect.and_then(Effect::healing) {
+            let healed = encounter.roll(&healing)?.total;
+            attacker.heal(healed);
+        }
+
+        attacker.consumed.push(potion);
+        return Ok(0);
+    }
+
+    let mut attacks = match combatant.feature {
+        Some(ClassFeature::ActionSurge) if round == 1 => 2,
+        _ => 1,
+    };
+
+    let mut strike = None;
+    if let Some(index) = attacker.ready_power() {
+        match use_power(encounter, attacker, defender, index)? {
+            Some(power_strike) => {
+                attacks *= power_strike.attacks;
+                strike = Some(power_strike);
+            }
+            None => return Ok(0),
+        }
+    } else if let Some(spell) = attacker.ready_spell(defender) {
+        cast(encounter, attacker, defender, spell)?;
+        return Ok(0);
+    }
+
+    let mut critical_hits = 0;
+    for _ in 0..attacks {
+        critical_hits += attack(encounter, attacker, defender, strike.as_ref())?;
+        if defender.is_down() {
+            break;
+        }
+    }
+
+    Ok(critical_hits)
+}
+
+/// Let `attacker` use the power at `index`. Returns the changes to the
+/// attacks of the turn if the power attacks.
+fn use_power(
+    encounter: &mut Encounter,
+    attacker: &mut Fighter,
+    defender: &mut Fighter,
+    index: usize,
+) -> Result<Option<Strike>> {
+    let power = attacker.combatant.powers[index];
+    attacker.cooldowns[index] = power.cooldown;
+    attacker.actions.push(power.name);
+
+    match power.effect {
+        PowerEffect::Strike(strike) => return Ok(Some(strike)),
+        PowerEffect::Blast(dice, bonus) => {
+            let damage = encounter
+                .roll(&Expression::with_bonus(dice, bonus.into()))?
+                .total;
+            defender.take_damage(cmp::max(damage, 1));
+        }
+        PowerEffect::Heal(dice) => {
+            let healing =
+                Expression::with_bonus(dice, cmp::max(attacker.combatant.damage_bonus, 0).into());
+            let healed = encounter.roll(&healing)?.total;
+            attacker.heal(healed);
+        }
+        PowerEffect::Status(kind, rounds) => attacker.combatant.statuses.push(Status {
+            kind,
+            rounds: Some(rounds),
+        }),
+    }
+
+    Ok(None)
+}
+
+/// Let `attacker` cast `spell` on `defender`, who rolls a saving throw against
+/// it
+fn cast(
+    encounter: &mut Encounter,
+    attacker: &mut Fighter,
+    defender: &mut Fighter,
+    spell: &'static Spell,
+) -> Result<()> {
+    attacker.combatant.mana -= i64::from(spell.mana);
+    attacker.actions.push(spell.name);
+
+    let dc = attacker.combatant.spell_save_dc.into();
+    let save = check(
+        encounter,
+        defender.combatant.saving_throw(spell.save),
+        defender.combatant.reroll_chance,
+        |save| save.total >= dc,
+    )?;
+    let saved = save.total >= dc;
+
+    if let Some(dice) = spell.damage {
+        let damage = encounter.roll(&Expression::Dice(dice))?.total;
+        let damage = match (saved, spell.half_on_save) {
+            (false, _) => damage,
+            (true, true) => damage / 2,
+            (true, false) => 0,
+        };
+        defender.take_damage(damage);
+    }
+
+    if let Some((kind, rounds)) = spell.inflicts {
+        if !saved && !defender.has_status(kind) {
+            defender.combatant.statuses.push(Status {
+                kind,
+                rounds: Some(rounds),
+            });
+        }
+    }
+
+    Ok(())
+}
+
+/// Roll a check that passes if `passes` holds. Luck gives a failed check a
+/// second chance of `reroll_chance` percent.
+fn check<F>(
+    encounter: &mut Encounter,
+    roll: &Expression,
+    reroll_chance: i64,
+    passes: F,
+) -> Result<Roll>
+where
+    F: Fn(&Roll) -> bool,
+{
+    let first = encounter.roll(roll)?;
+    if passes(&first) || reroll_chance <= 0 {
+        return Ok(first);
+    }
+
+    let chance = encounter.roll(&Expression::Dice(DicePool::new(1, Die::D100)))?;
+    if chance.total <= reroll_chance {
+        encounter.roll(roll)
+    } else {
+        Ok(first)
+    }
+}
+
+/// The health after resting for `minutes`. A rest of [`FULL_REST_MINUTES`]
+/// restores all health.
+#[must_use]
+pub fn rest(health: i64, max_health: i64, minutes: i64) -> i64 {
+    let regained = max_health * cmp::max(minutes, 0) / FULL_REST_MINUTES;
+
+    cmp::min(health.saturating_add(regained), max_health)
+}
+
+/// Roll one attack against `defender`, changed by the `strike` of a power.
+/// Returns `1` for a critical hit.
+fn attack(
+    encounter: &mut Encounter,
+    attacker: &mut Fighter,
+    defender: &mut Fighter,
+    strike: Option<&Strike>,
+) -> Result<u32> {
+    let combatant = &attacker.combatant;
+    let on_hit = combatant.on_hit;
+
+    let attack_die = match strike {
+        Some(strike) if strike.advantage => combatant.attack_die.advantage(),
+        _ => combatant.attack_die,
+    };
+    let to_hit = combatant.attack_bonus + strike.map_or(0, |strike| strike.to_hit);
+    let mut attack = Expression::with_bonus(attack_die, to_hit.into());
+    for status in &combatant.statuses {
+        if let Some((op, dice)) = status.kind.attack_modifier() {
+            attack = Expression::Binary(Box::new(attack), op, Box::new(Expression::Dice(dice)));
+        }
+    }
+    // returns whether the roll is a critical hit and whether it hits
+    let judge = |roll: &Roll| {
+        let natural = roll.pools.first().map_or(0, |pool| pool.total);
+        let critical = natural >= combatant.crit_threshold.into();
+        // a natural 1 always misses
+        let hit = critical
+            || (natural > combatant.attack_die.min()
+                && roll.total > defender.combatant.armor_class.into());
+
+        (critical, hit)
+    };
+
+    let roll = check(encounter, &attack, combatant.reroll_chance, |roll| {
+        judge(roll).1
+    })?;
+    let (critical, hit) = judge(&roll);
+    if !hit {
+        return Ok(0);
+    }
+
+    let dice = if critical {
+        combatant.crit_damage
+    } else {
+        combatant.damage
+    };
+    let mut damage = Expression::with_bonus(dice, combatant.damage_bonus.into());
+
+    let extra = match combatant.feature {
+        Some(ClassFeature::SneakAttack) if attacker.acted_first && !attacker.feature_used => {
+            attacker.feature_used = true;
+            Some(DicePool::new(2, Die::D6))
+        }
+        Some(ClassFeature::HuntersMark) => Some(DicePool::new(1, Die::D4)),
+        _ => None,
+    };
+    for extra in extra
+        .into_iter()
+        .chain(strike.and_then(|strike| strike.extra_damage))
+    {
+        damage = Expression::Binary(
+            Box::new(damage),
+            Operator::Add,
+            Box::new(Expression::Dice(extra)),
+        );
+    }
+
+    // every hit deals at least one damage
+    let damage = cmp::max(encounter.roll(&damage)?.total, 1);
+    defender.take_damage(damage);
+
+    // the status of a power is inflicted on every hit
+    if let Some((kind, rounds)) = strike.and_then(|strike| strike.inflicts) {
+        if !defender.has_status(kind) {
+            defender.combatant.statuses.push(Status {
+                kind,
+                rounds: Some(rounds),
+            });
+        }
+    } else if let Some((kind, rounds)) = on_hit {
+        let chance = encounter.roll(&Expression::Dice(DicePool::new(1, ON_HIT_CHANCE)))?;
+        if chance.total == ON_HIT_CHANCE.max().into() && !defender.has_status(kind) {
+            defender.combatant.statuses.push(Status {
+                kind,
+                rounds: Some(rounds),
+            });
+        }
+    }
+
+    Ok(critical.into())
+}
+
+impl fmt::Display for Outcome {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.result {
+            FightResult::Won => write!(
+                f,
+                "You defeated a {} in {} rounds with {}/{} health left",
+                self.opponent, self.rounds, self.health, self.max_health
+            )?,
+            FightResult::Lost => write!(
+                f,
+                "A {} defeated you in {} rounds",
+                self.opponent, self.rounds
+            )?,
+            FightResult::Fled => write!(
+                f,
+                "You fled from a {} after {} rounds with {}/{} health left",
+                self.opponent, self.rounds, self.health, self.max_health
+            )?,
+        }
+
+        match self.critical_hits {
+            0 => {}
+            1 => write!(f, " (1 critical hit)")?,
+            n => write!(f, " ({} critical hits)", n)?,
+        }
+
+        let mut used = Vec::new();
+        for (index, name) in self.actions.iter().enumerate() {
+            if self.actions[..index].contains(name) {
+                continue;
+            }
+            match self.actions.iter().filter(|used| *used == name).count() {
+                1 => used.push(name.to_string()),
+                n => used.push(format!("{} ({}x)", name, n)),
+            }
+        }
+        match self.consumed.as_slice() {
+            [] => {}
+            [item] => used.push(format!("a {}", item.name)),
+            items => used.extend(items.iter().map(|item| item.name.to_string())),
+        }
+        if !used.is_empty() {
+            write!(f, ", used {}", used.join(", "))?;
+        }
+
+        let mut earned = Vec::new();
+        if self.experience > 0 {
+            earned.push(format!("{} XP", self.experience));
+        }
+        if self.treasure {
+            earned.push(format!("{} gold from a hidden treasure", self.gold));
+        } else if self.gold > 0 {
+            earned.push(format!("{} gold", self.gold));
+        }
+        if !earned.is_empty() {
+            write!(f, " and earned {}", earned.join(" and "))?;
+        }
+
+        if !self.lingering.is_empty() {
+            let lingering: Vec<_> = self.lingering.iter().map(ToString::to_string).collect();
+            write!(f, ". You are still {}", lingering.join(" and "))?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        character::{Class, Equipment, Race, SPELLS},
+        dice::rng::test::seed,
+        encounter,
+    };
+
+    fn stats(class: Class) -> CharacterStats {
+        let mut equipment = Equipment::default();
+        for item in class.starting_equipment() {
+            equipment.equip(item);
+        }
+
+        CharacterStats {
+            strength: 4.into(),
+            dexterity: 4.into(),
+            constitution: 2.into(),
+            intelligence: 4.into(),
+            wisdom: 4.into(),
+            class,
+            race: Race::Human,
+            equipment,
+            ..CharacterStats::default()
+        }
+    }
+
+    #[test]
+    fn replay() {
+        let outcomes: Vec<_> = (0..)
+            .zip(&Class::ALL)
+            .map(|(n, class)| {
+                let mut first = Encounter::from_seed(stats(*class), seed(n));
+                let mut second = Encounter::from_seed(stats(*class), seed(n));
+
+                let character = Combatant::from_stats("tester", first.stats());
+                let outcome = first.fight(character.clone(), &MONSTERS[2]).unwrap();
+                assert_eq!(second.fight(character, &MONSTERS[2]).unwrap(), outcome);
+
+                let verification =
+                    encounter::verify(&first.commitment(), seed(n), first.rolls()).unwrap();
+                assert!(verification.is_valid());
+
+                (outcome.result, outcome.rounds, outcome.health)
+            })
+            .collect();
+
+        // the result, rounds and health left of every class
+        assert_eq!(
+            outcomes,
+            [
+                (FightResult::Won, 1, 22),
+                (FightResult::Won, 3, 12),
+                (FightResult::Won, 1, 14),
+                (FightResult::Won, 8, 4),
+                (FightResult::Won, 2, 20),
+            ]
+        );
+    }
+
+    #[test]
+    fn characters_usually_win() {
+        let outcomes: Vec<_> = (0..20)
+            .map(|n| {
+                let mut encounter = Encounter::from_seed(stats(Class::Fighter), seed(n));
+                let character = Combatant::from_stats("tester", encounter.stats());
+                encounter.fight(character, &MONSTERS[2]).unwrap()
+            })
+            .collect();
+
+        assert!(outcomes
+            .iter()
+            .all(|outcome| outcome.result == FightResult::Won));
+        assert_eq!(
+            outcomes[0],
+            Outcome {
+                result: FightResult::Won,
+                opponent: String::from("goblin"),
+                rounds: 1,
+                health: 22,
+                max_health: 22,
+                critical_hits: 0,
+                experience: 100,
+                gold: 17,
+                treasure: false,
+                consumed: Vec::new(),
+                actions: vec!["Power Attack"],
+                lingering: Vec::new(),
+            }
+        );
+    }
+
+    #[test]
+    fn arcane_ward() {
+        let mut wizard = Fighter::new(Combatant::from_stats("tester", &stats(Class::Wizard)));
+        let health = wizard.combatant.health;
+
+        wizard.take_damage(3);
+        assert_eq!(wizard.combatant.health, health);
+        wizard.take_damage(4);
+        assert_eq!(wizard.combatant.health, health - 2);
+    }
+
+    #[test]
+    fn potions() {
+        let potion = Item::by_key("potion_of_healing").unwrap();
+        let mut character = Combatant::from_stats("tester", &stats(Class::Fighter));
+        character.health = 1;
+        character.heal_below = 5;
+        character.potions = vec![potion, potion];
+        let mut character = Fighter::new(character);
+        let mut monster = Fighter::new(MONSTERS[0].combatant());
+
+        let mut encounter = Encounter::from_seed(stats(Class::Fighter), seed(0));
+        turn(&mut encounter, &mut character, &mut monster, 1).unwrap();
+        assert_eq!(character.consumed, [potion]);
+        assert_eq!(character.combatant.potions, [potion]);
+        assert_eq!(character.combatant.health, 8);
+        assert_eq!(monster.combatant.health, monster.combatant.max_health);
+
+        // healthy characters attack
+        turn(&mut encounter, &mut character, &mut monster, 2).unwrap();
+        assert_eq!(character.consumed.len(), 1);
+    }
+
+    #[test]
+    fn statuses() {
+        let mut encounter = Encounter::from_seed(stats(Class::Fighter), seed(0));
+        let mut character = Combatant::from_stats("tester", encounter.stats());
+        character.statuses = vec![
+            Status {
+                kind: StatusKind::Poison,
+                rounds: Some(2),
+            },
+            Status {
+                kind: StatusKind::Stun,
+                rounds: Some(1),
+            },
+            Status {
+                kind: StatusKind::Cursed,
+                rounds: None,
+            },
+        ];
+        let mut character = Fighter::new(character);
+        let mut monster = Fighter::new(MONSTERS[0].combatant());
+
+        // stunned characters only suffer
+        let health = character.combatant.health;
+        turn(&mut encounter, &mut character, &mut monster, 1).unwrap();
+        assert_eq!(character.combatant.health, health - 1);
+        assert_eq!(monster.combatant.health, monster.combatant.max_health);
+        assert_eq!(
+            character.combatant.statuses,
+            [
+                Status {
+                    kind: StatusKind::Poison,
+                    rounds: Some(1),
+                },
+                Status {
+                    kind: StatusKind::Cursed,
+                    rounds: None,
+                },
+            ]
+        );
+
+        turn(&mut encounter, &mut character, &mut monster, 2).unwrap();
+        assert_eq!(character.combatant.statuses.len(), 1);
+
+        let mut regenerating = Fighter::new(MONSTERS[5].combatant());
+        regenerating.combatant.health = 1;
+        regenerating.combatant.statuses = vec![Status {
+            kind: StatusKind::Regeneration,
+            rounds: None,
+        }];
+        regenerating.start_turn(&mut encounter).unwrap();
+        assert_eq!(regenerating.combatant.health, 5);
+    }
+
+    #[test]
+    fn powers() {
+        let mut encounter = Encounter::from_seed(stats(Class::Wizard), seed(0));
+        let mut character = Fighter::new(Combatant::from_stats("tester", encounter.stats()));
+        let mut monster = Fighter::new(MONSTERS[5].combatant());
+        assert_eq!(character.cooldowns, [0]);
+
+        // magic missile always hits
+        turn(&mut encounter, &mut character, &mut monster, 1).unwrap();
+        assert_eq!(monster.combatant.health, monster.combatant.max_health - 7);
+        assert_eq!(character.actions, ["Magic Missile"]);
+        assert_eq!(character.cooldowns, [3]);
+
+        // spells fill the cooldown
+        turn(&mut encounter, &mut character, &mut monster, 2).unwrap();
+        assert_eq!(character.cooldowns, [2]);
+        assert_eq!(character.actions, ["Magic Missile", "Burning Hands"]);
+        assert_eq!(character.combatant.mana, 4);
+
+        // healing powers wait until they are needed
+        let mut stats = stats(Class::Cleric);
+        stats.level = 3.into();
+        let mut cleric = Fighter::new(Combatant::from_stats("tester", &stats));
+        cleric.cooldowns[0] = 1;
+        assert_eq!(cleric.ready_power(), None);
+        cleric.combatant.health = 1;
+        assert_eq!(cleric.ready_power(), Some(1));
+    }
+
+    #[test]
+    fn spells() {
+        let mut stats = stats(Class::Cleric);
+        stats.level = 3.into();
+        let mut cleric = Fighter::new(Combatant::from_stats("tester", &stats));
+        let mut monster = Fighter::new(MONSTERS[5].combatant());
+        assert_eq!(cleric.combatant.mana, 10);
+        assert_eq!(cleric.combatant.spell_save_dc, 12);
+
+        assert_eq!(cleric.ready_spell(&monster).unwrap().name, "Bane");
+        monster.combatant.statuses.push(Status {
+            kind: StatusKind::Cursed,
+            rounds: Some(2),
+        });
+        assert_eq!(cleric.ready_spell(&monster).unwrap().name, "Sacred Flame");
+        cleric.combatant.mana = 0;
+        assert_eq!(cleric.ready_spell(&monster), None);
+
+        let mut encounter = Encounter::from_seed(stats, seed(0));
+        let mut caster = Fighter::new(Combatant::from_stats("tester", encounter.stats()));
+        cast(&mut encounter, &mut caster, &mut monster, &SPELLS[0]).unwrap();
+        assert_eq!(monster.combatant.health, monster.combatant.max_health - 14);
+        assert_eq!(encounter.rolls().len(), 2);
+    }
+
+    #[test]
+    fn lucky_saves() {
+        let stats = stats(Class::Wizard);
+        let mut monster = Fighter::new(MONSTERS[0].combatant());
+        monster.combatant.reroll_chance = 100;
+
+        // the save cannot pass, so the monster always rolls it twice
+        let mut encounter = Encounter::from_seed(stats, seed(0));
+        let mut caster = Fighter::new(Combatant::from_stats("tester", encounter.stats()));
+        caster.combatant.spell_save_dc = 30;
+        cast(&mut encounter, &mut caster, &mut monster, &SPELLS[0]).unwrap();
+        assert_eq!(encounter.rolls().len(), 4);
+
+        monster.combatant.reroll_chance = 0;
+        let mut encounter = Encounter::from_seed(stats, seed(0));
+        cast(&mut encounter, &mut caster, &mut monster, &SPELLS[0]).unwrap();
+        assert_eq!(encounter.rolls().len(), 2);
+    }
+
+    #[test]
+    fn resting() {
+        assert_eq!(rest(0, 40, 0), 0);
+        assert_eq!(rest(0, 40, FULL_REST_MINUTES / 4), 10);
+        assert_eq!(rest(30, 40, FULL_REST_MINUTES / 2), 40);
+        assert_eq!(rest(10, 40, -5), 10);
+    }
+
+    #[test]
+    fn summary() {
+        let outcome = Outcome {
+            result: FightResult::Won,
+            opponent: String::from("goblin"),
+            rounds: 3,
+            health: 12,
+            max_health: 22,
+            critical_hits: 1,
+            experience: 100,
+            gold: 0,
+            treasure: false,
+            consumed: Vec::new(),
+            actions: Vec::new(),
+            lingering: Vec::new(),
+        };
+
+        assert_eq!(
+            outcome.to_string(),
+            "You defeated a goblin in 3 rounds with 12/22 health left (1 critical hit) and earned 100 XP"
+        );
+
+        let outcome = Outcome {
+            gold: 14,
+            consumed: vec![Item::by_key("potion_of_healing").unwrap()],
+            ..outcome
+        };
+        assert_eq!(
+            outcome.to_string(),
+            "You defeated a goblin in 3 rounds with 12/22 health left (1 critical hit), used a potion of healing and earned 100 XP and 14 gold"
+        );
+
+        let outcome = Outcome {
+            actions: vec!["Power Attack", "Second Wind", "Power Attack"],
+            ..outcome
+        };
+        assert_eq!(
+            outcome.to_string(),
+            "You defeated a goblin in 3 rounds with 12/22 health left (1 critical hit), used Power Attack (2x), Second Wind, a potion of healing and earned 100 XP and 14 gold"
+        );
+
+        let outcome = Outcome {
+            treasure: true,
+            actions: Vec::new(),
+            consumed: Vec::new(),
+            ..outcome
+        };
+        assert!(outcome
+            .to_string()
+            .ends_with("earned 100 XP and 14 gold from a hidden treasure"));
+
+        let outcome = Outcome {
+            lingering: vec![StatusKind::Poison],
+            ..outcome
+        };
+        assert!(outcome.to_string().ends_with(". You are still poisoned"));
+    }
+}
@@ -0,0 +1,135 @@
+use super::{Combatant, StatusKind};
+use crate::{character::Ability, DicePool, Die, Expression};
+
+/// An opponent in the dungeon
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Monster {
+    pub name: &'static str,
+    pub challenge_rating: u8,
+    pub armor_class: i16,
+    pub max_health: i64,
+    pub initiative_bonus: i16,
+    pub attack_bonus: i16,
+    pub damage: DicePool,
+    pub damage_bonus: i16,
+    /// The status hits inflict now and then, with the rounds it lasts
+    pub on_hit: Option<(StatusKind, u32)>,
+    /// The bonus to every saving throw
+    pub save_bonus: i16,
+}
+
+/// Every monster that can be met in the dungeon
+pub const MONSTERS: [Monster; 6] = [
+    Monster {
+        name: "giant rat",
+        challenge_rating: 1,
+        armor_class: 10,
+        max_health: 5,
+        initiative_bonus: 2,
+        attack_bonus: 1,
+        damage: DicePool::new(1, Die::D4),
+        damage_bonus: 0,
+        on_hit: Some((StatusKind::Poison, 3)),
+        save_bonus: 0,
+    },
+    Monster {
+        name: "kobold",
+        challenge_rating: 1,
+        armor_class: 11,
+        max_health: 5,
+        initiative_bonus: 2,
+        attack_bonus: 2,
+        damage: DicePool::new(1, Die::D4),
+        damage_bonus: 1,
+        on_hit: None,
+        save_bonus: 1,
+    },
+    Monster {
+        name: "goblin",
+        challenge_rating: 1,
+        armor_class: 11,
+        max_health: 7,
+        initiative_bonus: 2,
+        attack_bonus: 2,
+        damage: DicePool::new(1, Die::D6),
+        damage_bonus: 0,
+        on_hit: None,
+        save_bonus: 1,
+    },
+    Monster {
+        name: "wolf",
+        challenge_rating: 2,
+        armor_class: 11,
+        max_health: 9,
+        initiative_bonus: 2,
+        attack_bonus: 3,
+        damage: DicePool::new(1, Die::D6),
+        damage_bonus: 1,
+        on_hit: Some((StatusKind::Bleed, 2)),
+        save_bonus: 2,
+    },
+    Monster {
+        name: "skeleton",
+        challenge_rating: 2,
+        armor_class: 12,
+        max_health: 10,
+        initiative_bonus: 1,
+        attack_bonus: 3,
+        damage: DicePool::new(1, Die::D6),
+        damage_bonus: 1,
+        on_hit: Some((StatusKind::Cursed, 3)),
+        save_bonus: 1,
+    },
+    Monster {
+        name: "orc",
+        challenge_rating: 2,
+        armor_class: 12,
+        max_health: 12,
+        initiative_bonus: 0,
+        attack_bonus: 3,
+        damage: DicePool::new(1, Die::D8),
+        damage_bonus: 1,
+        on_hit: Some((StatusKind::Stun, 1)),
+        save_bonus: 2,
+    },
+];
+
+impl Monster {
+    /// The monster ready to fight
+    #[must_use]
+    pub fn combatant(&self) -> Combatant {
+        Combatant {
+            name: self.name.to_string(),
+            health: self.max_health,
+            max_health: self.max_health,
+            armor_class: self.armor_class,
+            initiative: Expression::with_bonus(
+                DicePool::new(1, Die::D20),
+                self.initiative_bonus.into(),
+            ),
+            attack_die: DicePool::new(1, Die::D20),
+            attack_bonus: self.attack_bonus,
+            crit_threshold: Die::D20.max(),
+            damage: self.damage,
+            crit_damage: DicePool::new(self.damage.count * 2, self.damage.die),
+            damage_bonus: self.damage_bonus,
+            feature: None,
+            potions: Vec::new(),
+            heal_below: 0,
+            statuses: Vec::new(),
+            on_hit: self.on_hit,
+            powers: Vec::new(),
+            saving_throws: vec![
+                Expression::with_bonus(
+                    DicePool::new(1, Die::D20),
+                    self.save_bonus.into()
+                );
+                Ability::ALL.len()
+            ],
+            mana: 0,
+            spells: Vec::new(),
+            spell_save_dc: 0,
+            reroll_chance: 0,
+        }
+    }
+}
@@ -0,0 +1,117 @@
+use crate::{DicePool, Die, Operator};
+use std::fmt;
+
+/// A lasting effect on a combatant that acts every round
+#[derive(sqlx::Type, Debug, Copy, Clone, PartialEq, Eq)]
+#[sqlx(rename_all = "lowercase")]
+#[sqlx(rename = "status")]
+pub enum StatusKind {
+    Poison,
+    Bleed,
+    Stun,
+    Regeneration,
+    Blessed,
+    Cursed,
+}
+
+/// A status effect during a fight
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Status {
+    pub kind: StatusKind,
+    /// The turns left, `None` if it lasts for the whole fight
+    pub rounds: Option<u32>,
+}
+
+impl StatusKind {
+    pub const ALL: [Self; 6] = [
+        Self::Poison,
+        Self::Bleed,
+        Self::Stun,
+        Self::Regeneration,
+        Self::Blessed,
+        Self::Cursed,
+    ];
+
+    /// Whether cleansing removes the effect
+    #[must_use]
+    pub fn is_harmful(self) -> bool {
+        !matches!(self, Self::Regeneration | Self::Blessed)
+    }
+
+    /// The damage taken at the start of every turn
+    #[must_use]
+    pub fn damage(self) -> Option<DicePool> {
+        match self {
+            Self::Poison => Some(DicePool::new(1, Die::D4)),
+            Self::Bleed => Some(DicePool::new(1, Die::D6)),
+            _ => None,
+        }
+    }
+
+    /// The health regained at the start of every turn
+    #[must_use]
+    pub fn healing(self) -> Option<DicePool> {
+        match self {
+            Self::Regeneration => Some(DicePool::new(1, Die::D4)),
+            _ => None,
+        }
+    }
+
+    /// The dice added to or subtracted from attack rolls
+    #[must_use]
+    pub fn attack_modifier(self) -> Option<(Operator, DicePool)> {
+        match self {
+            Self::Blessed => Some((Operator::Add, DicePool::new(1, Die::D4))),
+            Self::Cursed => Some((Operator::Subtract, DicePool::new(1, Die::D4))),
+            _ => None,
+        }
+    }
+
+    /// Whether the effect carries over to the next encounter if it is still
+    /// active at the end of a fight
+    #[must_use]
+    pub fn lingers(self) -> bool {
+        matches!(self, Self::Poison | Self::Bleed)
+    }
+}
+
+impl fmt::Display for StatusKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Poison => "poisoned",
+            Self::Bleed => "bleeding",
+            Self::Stun => "stunned",
+            Self::Regeneration => "regenerating",
+            Self::Blessed => "blessed",
+            Self::Cursed => "cursed",
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn harmful() {
+        let harmful: Vec<_> = StatusKind::ALL
+            .iter()
+            .copied()
+            .filter(|kind| kind.is_harmful())
+            .collect();
+
+        assert_eq!(
+            harmful,
+            [
+                StatusKind::Poison,
+                StatusKind::Bleed,
+                StatusKind::Stun,
+                StatusKind::Cursed
+            ]
+        );
+        assert!(StatusKind::ALL
+            .iter()
+            .filter(|kind| kind.lingers())
+            .all(|kind| kind.is_harmful()));
+    }
+}
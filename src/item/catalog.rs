@@ -0,0 +1,244 @@
+use super::{Effect, Item, Slot};
+use crate::{character::Ability, fight::StatusKind, DicePool, Die};
+
+const fn weapon(
+    key: &'static str,
+    name: &'static str,
+    damage: DicePool,
+    weight: u32,
+    price: u32,
+) -> Item {
+    Item {
+        key,
+        name,
+        slot: Some(Slot::Weapon),
+        damage: Some(damage),
+        attack_bonus: 0,
+        armor_class: 0,
+        attributes: &[],
+        weight,
+        stackable: false,
+        effect: None,
+        price,
+    }
+}
+
+const fn armor(
+    key: &'static str,
+    name: &'static str,
+    slot: Slot,
+    armor_class: i16,
+    weight: u32,
+    price: u32,
+) -> Item {
+    Item {
+        key,
+        name,
+        slot: Some(slot),
+        damage: None,
+        attack_bonus: 0,
+        armor_class,
+        attributes: &[],
+        weight,
+        stackable: false,
+        effect: None,
+        price,
+    }
+}
+
+const fn accessory(
+    key: &'static str,
+    name: &'static str,
+    armor_class: i16,
+    attributes: &'static [(Ability, i16)],
+    weight: u32,
+    price: u32,
+) -> Item {
+    Item {
+        key,
+        name,
+        slot: Some(Slot::Accessory),
+        damage: None,
+        attack_bonus: 0,
+        armor_class,
+        attributes,
+        weight,
+        stackable: false,
+        effect: None,
+        price,
+    }
+}
+
+/// Something that is only carried around
+const fn trinket(key: &'static str, name: &'static str, weight: u32, price: u32) -> Item {
+    Item {
+        key,
+        name,
+        slot: None,
+        damage: None,
+        attack_bonus: 0,
+        armor_class: 0,
+        attributes: &[],
+        weight,
+        stackable: true,
+        effect: None,
+        price,
+    }
+}
+
+/// Something that is used up
+const fn consumable(
+    key: &'static str,
+    name: &'static str,
+    effect: Effect,
+    weight: u32,
+    price: u32,
+) -> Item {
+    Item {
+        key,
+        name,
+        slot: None,
+        damage: None,
+        attack_bonus: 0,
+        armor_class: 0,
+        attributes: &[],
+        weight,
+        stackable: true,
+        effect: Some(effect),
+        price,
+    }
+}
+
+/// Every item in the game
+pub const ITEMS: [Item; 28] = [
+    weapon("dagger", "dagger", DicePool::new(1, Die::D4), 1, 2),
+    weapon(
+        "quarterstaff",
+        "quarterstaff",
+        DicePool::new(1, Die::D6),
+        4,
+        2,
+    ),
+    weapon("mace", "mace", DicePool::new(1, Die::D6), 4, 5),
+    weapon("shortsword", "shortsword", DicePool::new(1, Die::D6), 2, 10),
+    weapon("longsword", "longsword", DicePool::new(1, Die::D8), 3, 15),
+    weapon("longbow", "longbow", DicePool::new(1, Die::D8), 2, 50),
+    weapon("warhammer", "warhammer", DicePool::new(1, Die::D8), 2, 15),
+    weapon("greataxe", "greataxe", DicePool::new(1, Die::D12), 7, 30),
+    Item {
+        attack_bonus: 1,
+        ..weapon(
+            "fine_rapier",
+            "fine rapier",
+            DicePool::new(1, Die::D8),
+            2,
+            100,
+        )
+    },
+    armor("leather_armor", "leather armor", Slot::Armor, 1, 10, 10),
+    armor("scale_mail", "scale mail", Slot::Armor, 3, 45, 50),
+    armor("chain_mail", "chain mail", Slot::Armor, 4, 55, 75),
+    armor("shield", "shield", Slot::Shield, 2, 6, 10),
+    accessory(
+        "spellbook",
+        "spellbook",
+        0,
+        &[(Ability::Intelligence, 1)],
+        3,
+        50,
+    ),
+    accessory("ring_of_protection", "ring of protection", 1, &[], 0, 200),
+    accessory(
+        "amulet_of_health",
+        "amulet of health",
+        0,
+        &[(Ability::Constitution, 2)],
+        1,
+        250,
+    ),
+    accessory(
+        "gauntlets_of_ogre_power",
+        "gauntlets of ogre power",
+        0,
+        &[(Ability::Strength, 2)],
+        2,
+        250,
+    ),
+    trinket("torch", "torch", 1, 1),
+    trinket("gemstone", "gemstone", 0, 50),
+    consumable(
+        "potion_of_healing",
+        "potion of healing",
+        Effect::Heal(DicePool::new(2, Die::D4), 2),
+        1,
+        25,
+    ),
+    consumable(
+        "greater_potion_of_healing",
+        "greater potion of healing",
+        Effect::Heal(DicePool::new(4, Die::D4), 4),
+        1,
+        75,
+    ),
+    consumable(
+        "potion_of_giant_strength",
+        "potion of giant strength",
+        Effect::Buff {
+            ability: Ability::Strength,
+            bonus: 2,
+            encounters: 3,
+        },
+        1,
+        100,
+    ),
+    consumable(
+        "elixir_of_agility",
+        "elixir of agility",
+        Effect::Buff {
+            ability: Ability::Dexterity,
+            bonus: 2,
+            encounters: 3,
+        },
+        1,
+        100,
+    ),
+    consumable(
+        "four_leaf_clover",
+        "four-leaf clover",
+        Effect::Buff {
+            ability: Ability::Luck,
+            bonus: 2,
+            encounters: 3,
+        },
+        0,
+        120,
+    ),
+    consumable(
+        "potion_of_regeneration",
+        "potion of regeneration",
+        Effect::Status {
+            kind: StatusKind::Regeneration,
+            encounters: 2,
+        },
+        1,
+        80,
+    ),
+    consumable(
+        "holy_water",
+        "holy water",
+        Effect::Status {
+            kind: StatusKind::Blessed,
+            encounters: 3,
+        },
+        1,
+        40,
+    ),
+    consumable(
+        "scroll_of_haste",
+        "scroll of haste",
+        Effect::ReduceCooldown(30),
+        0,
+        60,
+    ),
+    consumable("antidote", "antidote", Effect::Cleanse, 1, 30),
+];
@@ -0,0 +1,65 @@
+use crate::{character::Ability, fight::StatusKind, DicePool, Expression};
+use std::fmt;
+
+/// What happens when a consumable item is used
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Effect {
+    /// Restore health with the dice and a bonus
+    Heal(DicePool, i16),
+    /// Raise an attribute for a number of encounters
+    Buff {
+        ability: Ability,
+        bonus: i16,
+        encounters: u16,
+    },
+    /// Start a status effect that lasts for a number of encounters
+    Status { kind: StatusKind, encounters: u16 },
+    /// Shorten the dungeon cooldown by some minutes
+    ReduceCooldown(i64),
+    /// Remove every harmful effect
+    Cleanse,
+}
+
+impl Effect {
+    /// The roll for the restored health of a healing effect
+    #[must_use]
+    pub fn healing(self) -> Option<Expression> {
+        match self {
+            Self::Heal(dice, bonus) => Some(Expression::with_bonus(dice, bonus.into())),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for Effect {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Heal(dice, bonus) => {
+                write!(
+                    f,
+                    "heals {}",
+                    Expression::with_bonus(*dice, (*bonus).into())
+                )
+            }
+            Self::Buff {
+                ability,
+                bonus,
+                encounters: 1,
+            } => write!(f, "{:+} {} for 1 encounter", bonus, ability),
+            Self::Buff {
+                ability,
+                bonus,
+                encounters,
+            } => write!(f, "{:+} {} for {} encounters", bonus, ability, encounters),
+            Self::Status {
+                kind,
+                encounters: 1,
+            } => write!(f, "{} for 1 encounter", kind),
+            Self::Status { kind, encounters } => {
+                write!(f, "{} for {} encounters", kind, encounters)
+            }
+            Self::ReduceCooldown(minutes) => write!(f, "-{} min dungeon cooldown", minutes),
+            Self::Cleanse => f.write_str("cleanses harmful effects"),
+        }
+    }
+}
@@ -0,0 +1,50 @@
+/// An item that can be found after a fight and how likely it is compared to
+/// the other entries
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LootEntry {
+    pub key: &'static str,
+    pub weight: u16,
+}
+
+const fn entry(key: &'static str, weight: u16) -> LootEntry {
+    LootEntry { key, weight }
+}
+
+/// The items found after won fights
+pub const LOOT: [LootEntry; 21] = [
+    entry("torch", 30),
+    entry("gemstone", 20),
+    entry("potion_of_healing", 20),
+    entry("dagger", 10),
+    entry("shortsword", 8),
+    entry("leather_armor", 8),
+    entry("shield", 6),
+    entry("scroll_of_haste", 6),
+    entry("antidote", 5),
+    entry("greater_potion_of_healing", 5),
+    entry("warhammer", 5),
+    entry("potion_of_giant_strength", 4),
+    entry("elixir_of_agility", 4),
+    entry("holy_water", 4),
+    entry("potion_of_regeneration", 3),
+    entry("four_leaf_clover", 2),
+    entry("greataxe", 3),
+    entry("fine_rapier", 3),
+    entry("ring_of_protection", 2),
+    entry("amulet_of_health", 1),
+    entry("gauntlets_of_ogre_power", 1),
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::item::Item;
+
+    #[test]
+    fn known_items() {
+        for entry in &LOOT {
+            assert!(Item::by_key(entry.key).is_some(), "{}", entry.key);
+            assert!(entry.weight > 0);
+        }
+    }
+}
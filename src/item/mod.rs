@@ -0,0 +1,207 @@
+//! Everything a character can carry and equip
+
+mod catalog;
+mod consumable;
+mod loot;
+pub mod shop;
+
+pub use catalog::ITEMS;
+pub use consumable::Effect;
+pub use loot::{LootEntry, LOOT};
+
+use crate::{character::Ability, DicePool};
+use anyhow::{bail, Result};
+use std::{fmt, str::FromStr};
+
+/// Where an item is worn
+#[derive(sqlx::Type, Debug, Clone, Copy, PartialEq, Eq)]
+#[sqlx(rename_all = "lowercase")]
+#[sqlx(rename = "slot")]
+pub enum Slot {
+    Weapon,
+    Armor,
+    Shield,
+    Accessory,
+}
+
+/// A kind of item. Players own instances of these, referenced by `key`.
+#[derive(Debug, PartialEq, Eq)]
+pub struct Item {
+    /// Stored in the database, never change it
+    pub key: &'static str,
+    pub name: &'static str,
+    pub slot: Option<Slot>,
+    /// The damage dice of a weapon
+    pub damage: Option<DicePool>,
+    pub attack_bonus: i16,
+    pub armor_class: i16,
+    pub attributes: &'static [(Ability, i16)],
+    /// In pounds
+    pub weight: u32,
+    /// Whether instances share one inventory entry with a quantity
+    pub stackable: bool,
+    /// What using the item does. The item is used up.
+    pub effect: Option<Effect>,
+    /// The value in gold before charisma changes it
+    pub price: u32,
+}
+
+impl Slot {
+    pub const ALL: [Slot; 4] = [Self::Weapon, Self::Armor, Self::Shield, Self::Accessory];
+}
+
+impl FromStr for Slot {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match Self::ALL
+            .iter()
+            .find(|slot| slot.to_string().eq_ignore_ascii_case(s))
+        {
+            Some(slot) => Ok(*slot),
+            None => bail!("`{}` is not a slot", s),
+        }
+    }
+}
+
+impl fmt::Display for Slot {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Weapon => "weapon",
+            Self::Armor => "armor",
+            Self::Shield => "shield",
+            Self::Accessory => "accessory",
+        })
+    }
+}
+
+impl Item {
+    /// The item stored as `key`
+    #[must_use]
+    pub fn by_key(key: &str) -> Option<&'static Item> {
+        ITEMS.iter().find(|item| item.key == key)
+    }
+
+    /// The item called `name`, ignoring case
+    #[must_use]
+    pub fn by_name(name: &str) -> Option<&'static Item> {
+        let name = name.trim();
+
+        ITEMS
+            .iter()
+            .find(|item| item.name.eq_ignore_ascii_case(name) || item.key == name)
+    }
+
+    /// The bonus of this item to `ability`
+    #[must_use]
+    pub fn attribute_bonus(&self, ability: Ability) -> i16 {
+        self.attributes
+            .iter()
+            .filter(|(bonus_ability, _)| *bonus_ability == ability)
+            .map(|(_, bonus)| bonus)
+            .sum()
+    }
+}
+
+impl fmt::Display for Item {
+    /// The name followed by what the item does, e.g. `longsword (weapon, 1d8)`
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut effects = Vec::new();
+        if let Some(slot) = self.slot {
+            effects.push(slot.to_string());
+        }
+        if let Some(damage) = self.damage {
+            effects.push(damage.to_string());
+        }
+        if self.attack_bonus != 0 {
+            effects.push(format!("{:+} to hit", self.attack_bonus));
+        }
+        if self.armor_class != 0 {
+            effects.push(format!("{:+} AC", self.armor_class));
+        }
+        for (ability, bonus) in self.attributes {
+            effects.push(format!("{:+} {}", bonus, ability));
+        }
+        if let Some(effect) = self.effect {
+            effects.push(effect.to_string());
+        }
+
+        if self.stackable {
+            effects.push(String::from("stackable"));
+        }
+
+        if effects.is_empty() {
+            f.write_str(self.name)
+        } else {
+            write!(f, "{} ({})", self.name, effects.join(", "))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn catalog() {
+        for (index, item) in ITEMS.iter().enumerate() {
+            assert!(
+                ITEMS[..index].iter().all(|other| other.key != item.key),
+                "{} is not unique",
+                item.key
+            );
+            assert_eq!(
+                item.damage.is_some(),
+                item.slot == Some(Slot::Weapon),
+                "{}",
+                item.key
+            );
+            // equipped items are always single instances
+            assert!(!(item.stackable && item.slot.is_some()), "{}", item.key);
+            // consumables are never equipped
+            assert!(
+                !(item.effect.is_some() && item.slot.is_some()),
+                "{}",
+                item.key
+            );
+        }
+    }
+
+    #[test]
+    fn lookup() {
+        assert_eq!(Item::by_name("Chain Mail").unwrap().key, "chain_mail");
+        assert_eq!(Item::by_name("chain_mail").unwrap().name, "chain mail");
+        assert_eq!(Item::by_key("longsword").unwrap().name, "longsword");
+        assert!(Item::by_name("excalibur").is_none());
+    }
+
+    #[test]
+    fn display() {
+        assert_eq!(
+            Item::by_key("longsword").unwrap().to_string(),
+            "longsword (weapon, d8)"
+        );
+        assert_eq!(
+            Item::by_key("amulet_of_health").unwrap().to_string(),
+            "amulet of health (accessory, +2 constitution)"
+        );
+        assert_eq!(
+            Item::by_key("torch").unwrap().to_string(),
+            "torch (stackable)"
+        );
+        assert_eq!(
+            Item::by_key("potion_of_healing").unwrap().to_string(),
+            "potion of healing (heals 2d4+2, stackable)"
+        );
+        assert_eq!(
+            Item::by_key("potion_of_giant_strength")
+                .unwrap()
+                .to_string(),
+            "potion of giant strength (+2 strength for 3 encounters, stackable)"
+        );
+        assert_eq!(
+            Item::by_key("holy_water").unwrap().to_string(),
+            "holy water (blessed for 3 encounters, stackable)"
+        );
+    }
+}
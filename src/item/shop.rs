@@ -0,0 +1,97 @@
+use super::{Item, ITEMS};
+use crate::{character::Attribute, GameRng, Seed};
+use chrono::NaiveDate;
+use rand::seq::SliceRandom;
+use sha2::{Digest, Sha256};
+use std::{cmp, convert::TryFrom};
+
+/// How many different items a shop sells each day
+pub const STOCK_SIZE: usize = 6;
+/// How many of a stackable item a shop has each day
+const STACK_STOCK: u32 = 5;
+/// Every point of charisma modifier changes prices by this many percent
+const CHARISMA_PERCENT: i64 = 5;
+
+/// An item in the stock of a shop
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Offer {
+    pub item: &'static Item,
+    pub quantity: u32,
+}
+
+/// The stock of the shop in `channel` on `day`. It is the same all day and
+/// differs between channels.
+#[must_use]
+pub fn daily_stock(channel: &str, day: NaiveDate) -> Vec<Offer> {
+    let hash: [u8; 32] = Sha256::digest(format!("{}/{}", channel, day).as_bytes()).into();
+    let mut rng = GameRng::from_seed(Seed::from(hash));
+
+    ITEMS
+        .choose_multiple(&mut rng, STOCK_SIZE)
+        .map(|item| Offer {
+            item,
+            quantity: if item.stackable { STACK_STOCK } else { 1 },
+        })
+        .collect()
+}
+
+/// What a character with `charisma` pays for `item`. Every point of
+/// charisma modifier is a discount of 5%, from half to one and a half times
+/// the value.
+#[must_use]
+pub fn buy_price(item: &Item, charisma: Attribute) -> u32 {
+    let percent = (100 - CHARISMA_PERCENT * i64::from(charisma.modifier())).clamp(50, 150);
+
+    cmp::max(percentage(item.price, percent), 1)
+}
+
+/// What a character with `charisma` gets for selling `item`. This is at most
+/// half the value, so selling never pays more than buying costs.
+#[must_use]
+pub fn sell_price(item: &Item, charisma: Attribute) -> u32 {
+    let percent = (40 + CHARISMA_PERCENT * i64::from(charisma.modifier())).clamp(20, 50);
+
+    percentage(item.price, percent)
+}
+
+fn percentage(price: u32, percent: i64) -> u32 {
+    u32::try_from(i64::from(price) * percent / 100).unwrap_or(u32::MAX)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn stock() {
+        let day = NaiveDate::from_ymd(2020, 12, 31);
+        let stock = daily_stock("#chronophylos", day);
+
+        assert_eq!(stock.len(), STOCK_SIZE);
+        assert_eq!(stock, daily_stock("#chronophylos", day));
+        assert_ne!(stock, daily_stock("#chronophylos", day.succ()));
+        assert_ne!(stock, daily_stock("#other", day));
+
+        for (index, offer) in stock.iter().enumerate() {
+            assert!(stock[..index].iter().all(|other| other.item != offer.item));
+        }
+    }
+
+    #[test]
+    fn prices() {
+        let longsword = Item::by_key("longsword").unwrap();
+
+        assert_eq!(buy_price(longsword, 0.into()), 15);
+        assert_eq!(buy_price(longsword, 4.into()), 13);
+        assert_eq!(buy_price(longsword, (-4).into()), 16);
+        assert_eq!(buy_price(longsword, 40.into()), 7);
+        assert_eq!(sell_price(longsword, 0.into()), 6);
+        assert_eq!(sell_price(longsword, 40.into()), 7);
+
+        for item in &ITEMS {
+            for charisma in -10..=20 {
+                assert!(sell_price(item, charisma.into()) <= buy_price(item, charisma.into()));
+            }
+        }
+    }
+}
@@ -1,12 +1,22 @@
 #![forbid(unsafe_code)]
 #![warn(clippy::pedantic)]
+// format arguments are passed positionally throughout the code base
+#![allow(clippy::uninlined_format_args)]
 
-mod character;
 mod config;
 mod dice;
 
 pub mod bot;
+pub mod character;
 pub mod db;
+pub mod encounter;
+pub mod fight;
+pub mod item;
+pub mod reload;
+pub mod trade;
 
 pub use config::Config;
-pub use dice::{Dice, D10, D20, D6};
+pub use dice::{
+    Advantage, Commitment, Dice, DicePool, Die, Expression, GameRng, Keep, Modifiers, Operator,
+    Pmf, PoolRoll, Roll, Seed, D10, D20, D6,
+};
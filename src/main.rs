@@ -1,31 +1,69 @@
 #![forbid(unsafe_code)]
 #![warn(clippy::pedantic)]
+// format arguments are passed positionally throughout the code base
+#![allow(clippy::uninlined_format_args)]
+// command handlers own their arguments like `Command::handle`
+#![allow(clippy::needless_pass_by_value)]
+// `lazy_static` keeps the bot building with toolchains before `LazyLock`
+#![allow(clippy::non_std_lazy_statics)]
 
-use anyhow::{anyhow, Context, Result};
-use chrono::{DateTime, FixedOffset, NaiveDateTime, Utc};
+use anyhow::{anyhow, ensure, Context, Result};
+use chrono::{NaiveDateTime, Utc};
 use dungeon_bot::{
     bot::{Args, Bot},
-    db::Player,
-    Config,
+    character::{creation, Ability, CharacterStats, Class, CreationMethod, Equipment, Level, Race},
+    db::{self, GoldReason, LoggedRoll, OwnedItem, PendingCreation, Player, Shop, Trade},
+    encounter::{self, Encounter, RewardLevel},
+    fight::{self, Combatant, FightResult, Status, StatusKind},
+    item::{shop, Effect, Item, Slot},
+    reload::{Reloader, DEFAULT_LOG_LEVEL},
+    trade::{Bundle, TRADE_TIMEOUT},
+    Config, Expression, GameRng,
 };
 use lazy_static::lazy_static;
 use log::LevelFilter;
 use simple_logger::SimpleLogger;
 use smol::future::FutureExt as _;
 use sqlx::{postgres::PgPoolOptions, PgPool};
+use std::convert::TryFrom;
+use std::fmt::Write as _;
+use std::path::PathBuf;
+use std::sync::Arc;
 use std::time::Duration;
 use std::time::Instant;
+use structopt::StructOpt;
 use twitchchat::PrivmsgExt as _;
 
 const PREFIX: char = '>';
 const APP_VERSION: &str = env!("CARGO_PKG_VERSION");
 const APP_NAME: &str = env!("CARGO_PKG_NAME");
 const APP_REPO: &str = env!("CARGO_PKG_REPOSITORY");
+// leave some room for the mention in replies
+const MAX_ROLL_LEN: usize = 400;
+const DEFAULT_LOGGED_ROLLS: i64 = 5;
+const MAX_LOGGED_ROLLS: i64 = 20;
+const DEFAULT_LEDGER_ENTRIES: i64 = 5;
+const MAX_LEDGER_ENTRIES: i64 = 20;
+// minutes until a character can enter the dungeon again
+const DUNGEON_COOLDOWN: i64 = 60;
 
 lazy_static! {
     static ref BOOT_TIME: Instant = Instant::now();
 }
 
+#[derive(Debug, StructOpt)]
+#[structopt(about = "A dungeon crawler for twitch chat")]
+struct Opt {
+    /// Path to the configuration file
+    #[structopt(short, long, default_value = "config.ron", parse(from_os_str))]
+    config: PathBuf,
+
+    /// Log level (off, error, warn, info, debug or trace). Overrides the
+    /// configuration file
+    #[structopt(short, long)]
+    log_level: Option<LevelFilter>,
+}
+
 fn register(args: Args, pool: &PgPool) -> Result<()> {
     let uid = args.user_id()?;
     let player = Player::new(pool, uid);
@@ -36,80 +74,1482 @@ fn register(args: Args, pool: &PgPool) -> Result<()> {
         return Ok(());
     }
 
-    smol::block_on(player.insert())?;
+    let mut race = Race::default();
+    let mut class = Class::default();
+    for argument in &args.msg.arguments {
+        if let Ok(chosen) = argument.parse::<Race>() {
+            race = chosen;
+        } else if let Ok(chosen) = argument.parse::<Class>() {
+            class = chosen;
+        } else {
+            args.writer.reply(
+                args.raw,
+                &format!(
+                    "`{}` is neither a race nor a class. Races: {}. Classes: {}. E.g. `{} register elf wizard`",
+                    argument,
+                    join(&Race::ALL),
+                    join(&Class::ALL),
+                    PREFIX
+                ),
+            )?;
+            return Ok(());
+        }
+    }
+
+    smol::block_on(player.insert(race, class))?;
+    smol::block_on(player.set_name(args.raw.name()))?;
 
     args.writer.reply(
         args.raw,
-        "I added you to my records. Your character has been created",
+        &format!(
+            "I added you to my records as {} {} {} with {} and {}. You start with {}. Create your character with `{} create`",
+            race.article(),
+            race,
+            class,
+            race.racial_trait(),
+            class.feature(),
+            class
+                .starting_equipment()
+                .iter()
+                .map(|item| item.name)
+                .collect::<Vec<_>>()
+                .join(", "),
+            PREFIX
+        ),
     )?;
 
     Ok(())
 }
 
-fn unregister(args: Args, pool: &PgPool) -> Result<()> {
+/// Items separated by commas
+fn join<T: std::fmt::Display>(items: &[T]) -> String {
+    items
+        .iter()
+        .map(ToString::to_string)
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+fn unregister(args: Args, pool: &PgPool) -> Result<()> {
+    let uid = args.user_id()?;
+    let player = Player::new(pool, uid);
+
+    if !smol::block_on(player.exists())? {
+        args.writer.reply(
+            args.raw,
+            "I cannot delete what doesn't exist: You are not in my records",
+        )?;
+
+        return Ok(());
+    }
+
+    match args.msg.arguments.first() {
+        Some(&"confirm") => {
+            smol::block_on(player.delete())?;
+            args.writer
+                .reply(args.raw, "I removed you from my records 🔥🗒")?;
+        }
+        Some(_) => args.writer.reply(
+                args.raw,
+                &format!(
+                    "Type `{} unregister confirm` if you want to unregister",
+                    PREFIX
+                ),
+            )?,
+        _ => args.writer.reply(
+                args.raw,
+                &format!(
+                    "❗ This will delete your character and all of your progress. Are you sure about that? Type `{} unregister confirm` if you want to unregister",
+                    PREFIX
+                ),
+            )?,
+    }
+
+    Ok(())
+}
+
+fn create(args: Args, pool: &PgPool, method: CreationMethod) -> Result<()> {
+    let uid = args.user_id()?;
+    let player = Player::new(pool, uid);
+
+    if !smol::block_on(player.exists())? {
+        args.writer.reply(
+            args.raw,
+            &format!("You're not registered. Register with `{} register`", PREFIX),
+        )?;
+        return Ok(());
+    }
+
+    if smol::block_on(player.has_character())? {
+        args.writer
+            .reply(args.raw, "You already have a character")?;
+        return Ok(());
+    }
+
+    let action = args.msg.arguments.first().copied();
+    let scores = || -> Result<Vec<i16>> {
+        args.msg.arguments[1..]
+            .iter()
+            .map(|score| {
+                score
+                    .parse()
+                    .with_context(|| format!("`{}` is not a score", score))
+            })
+            .collect()
+    };
+
+    let stats = match (method, action) {
+        (CreationMethod::PointBuy, Some("buy")) => {
+            scores().and_then(|scores| creation::point_buy(&scores))
+        }
+        (CreationMethod::PointBuy, _) => {
+            args.writer.reply(
+                args.raw,
+                &format!(
+                    "Buy scores from {} to {} for strength, dexterity, constitution, intelligence, wisdom and charisma with {} points, e.g. `{} create buy {}`. Every score above 13 costs two points",
+                    creation::POINT_BUY_RANGE.0,
+                    creation::POINT_BUY_RANGE.1,
+                    creation::POINT_BUY_BUDGET,
+                    PREFIX,
+                    creation::join(&creation::STANDARD_ARRAY).replace(',', ""),
+                ),
+            )?;
+            return Ok(());
+        }
+        (_, action) => match smol::block_on(PendingCreation::get(pool, uid))? {
+            None => {
+                let (pending, rolled) = new_scores(pool, uid, method, false)?;
+                args.writer
+                    .reply(args.raw, &describe_scores(&pending, method, &rolled))?;
+                return Ok(());
+            }
+            Some(pending) => match action {
+                Some("assign") => {
+                    scores().and_then(|scores| creation::assign(&pending.scores, &scores))
+                }
+                Some("accept") => creation::assign(&pending.scores, &pending.scores),
+                Some("reroll") if method == CreationMethod::Rolled && !pending.rerolled => {
+                    let (pending, rolled) = new_scores(pool, uid, method, true)?;
+                    args.writer
+                        .reply(args.raw, &describe_scores(&pending, method, &rolled))?;
+                    return Ok(());
+                }
+                Some("reroll") => Err(anyhow!("You cannot roll again")),
+                _ => {
+                    args.writer
+                        .reply(args.raw, &describe_scores(&pending, method, ""))?;
+                    return Ok(());
+                }
+            },
+        },
+    };
+
+    match stats {
+        Ok(mut stats) => {
+            let (race, class) = smol::block_on(player.race_and_class())?;
+            race.apply(&mut stats);
+            stats.class = class;
+            smol::block_on(player.create_character(&stats))?;
+            args.writer.reply(
+                args.raw,
+                &format!(
+                    "Your character has been created. Enter the dungeon with `{} enter`",
+                    PREFIX
+                ),
+            )?;
+        }
+        Err(err) => args.writer.reply(args.raw, &err.to_string())?,
+    }
+
+    Ok(())
+}
+
+/// Roll new scores or hand out the standard array and store them until the
+/// player assigns them. Returns the scores and the rolled dice.
+fn new_scores(
+    pool: &PgPool,
+    uid: i32,
+    method: CreationMethod,
+    rerolled: bool,
+) -> Result<(PendingCreation, String)> {
+    let (scores, rolled) = if method == CreationMethod::Rolled {
+        let rolls = creation::roll_scores(&mut GameRng::new());
+        smol::block_on(LoggedRoll::insert(pool, uid, None, &rolls))?;
+
+        let scores = rolls
+            .iter()
+            .map(|roll| i16::try_from(roll.total))
+            .collect::<Result<_, _>>()?;
+        let rolled = rolls
+            .iter()
+            .map(|roll| format!("{} {}", roll.total, roll))
+            .collect::<Vec<_>>()
+            .join(", ");
+
+        (scores, format!("You rolled {}. ", rolled))
+    } else {
+        (creation::STANDARD_ARRAY.to_vec(), String::new())
+    };
+
+    let pending = PendingCreation { scores, rerolled };
+    smol::block_on(pending.save(pool, uid))?;
+
+    Ok((pending, rolled))
+}
+
+fn describe_scores(pending: &PendingCreation, method: CreationMethod, rolled: &str) -> String {
+    let mut reply = format!(
+        "{}Assign {} to strength, dexterity, constitution, intelligence, wisdom and charisma with `{} create assign {}` or keep this order with `{} create accept`",
+        rolled,
+        creation::join(&pending.scores),
+        PREFIX,
+        creation::join(&pending.scores).replace(',', ""),
+        PREFIX
+    );
+
+    if method == CreationMethod::Rolled && !pending.rerolled {
+        let _ = write!(
+            reply,
+            ". You can roll again once with `{} create reroll`",
+            PREFIX
+        );
+    }
+
+    reply
+}
+
+fn enter(args: Args, pool: &PgPool, config: &Config) -> Result<()> {
+    let uid = args.user_id()?;
+    let player = Player::new(pool, uid);
+
+    if let Some(reason) = entry_denied(&player)? {
+        args.writer.reply(args.raw, &reason)?;
+
+        return Ok(());
+    }
+
+    smol::block_on(player.set_name(args.raw.name()))?;
+
+    let stats = smol::block_on(player.get_stats())?;
+    let character = prepare_character(pool, &player, args.raw.name(), &stats, config)?;
+    if character.health <= 0 {
+        args.writer.reply(
+            args.raw,
+            &format!(
+                "You are too wounded to enter the dungeon. Rest or heal with `{} use potion of healing`",
+                PREFIX
+            ),
+        )?;
+
+        return Ok(());
+    }
+
+    let mut encounter = Encounter::new(stats);
+    let record = smol::block_on(db::Encounter::insert(pool, uid, encounter.seed()))?;
+
+    if config.provably_fair() {
+        args.writer.reply(
+            args.raw,
+            &format!(
+                "You enter the dungeon (encounter #{}, seed hash {})",
+                record.id(),
+                encounter.commitment()
+            ),
+        )?;
+    }
+
+    let monster = encounter.choose_monster()?;
+    let outcome = encounter.fight(character, monster)?;
+    let loot = if outcome.result == FightResult::Won {
+        encounter.loot(RewardLevel::new(
+            monster.challenge_rating,
+            encounter.stats().level,
+        ))?
+    } else {
+        None
+    };
+
+    store_outcome(pool, &player, &record, &encounter, &outcome)?;
+
+    if config.provably_fair() {
+        let seed = smol::block_on(record.reveal())?;
+        args.writer.reply(
+            args.raw,
+            &format!(
+                "{}. Encounter #{} used seed {}, check it with `{} verify {}`",
+                outcome,
+                record.id(),
+                seed,
+                PREFIX,
+                record.id()
+            ),
+        )?;
+    } else {
+        args.writer.reply(
+            args.raw,
+            &format!("{} (encounter #{})", outcome, record.id()),
+        )?;
+    }
+
+    if let Some(item) = loot {
+        let capacity = encounter.stats().carrying_capacity();
+        match smol::block_on(OwnedItem::add(pool, uid, item, 1, capacity)) {
+            Ok(()) => args
+                .writer
+                .reply(args.raw, &format!("You found a {} 🎁", item.name))?,
+            Err(err) => args.writer.reply(
+                args.raw,
+                &format!("You found a {} but leave it behind. {}", item.name, err),
+            )?,
+        }
+    }
+
+    let (old, new) = smol::block_on(player.add_experience(outcome.experience))?;
+    if new > old {
+        args.writer.reply(
+            args.raw,
+            &format!(
+                "Level up! You are now level {} 🎉 Spend your new attribute points with `{} train`",
+                new, PREFIX
+            ),
+        )?;
+    }
+
+    Ok(())
+}
+
+/// Why `player` cannot enter the dungeon now, `None` if they can
+fn entry_denied(player: &Player) -> Result<Option<String>> {
+    if !smol::block_on(player.exists())? {
+        return Ok(Some(format!(
+            "You're not registered. Register with `{} register`",
+            PREFIX
+        )));
+    }
+
+    if !smol::block_on(player.has_character())? {
+        return Ok(Some(format!(
+            "You don't have a character. Create one with `{} create`",
+            PREFIX
+        )));
+    }
+
+    Ok(smol::block_on(player.can_enter())?.map(|cooldown| {
+        format!(
+            "You cannot enter the dungeon. Please wait for {}",
+            describe_duration(cooldown)
+        )
+    }))
+}
+
+/// The character of `player` with the health, potions and status effects
+/// they bring into the dungeon
+fn prepare_character(
+    pool: &PgPool,
+    player: &Player,
+    name: &str,
+    stats: &CharacterStats,
+    config: &Config,
+) -> Result<Combatant> {
+    let mut character = Combatant::from_stats(name, stats);
+    character.health = smol::block_on(player.health(character.max_health))?;
+    character.heal_below = character.max_health * i64::from(config.auto_heal_below()) / 100;
+    character.potions = healing_potions(&smol::block_on(OwnedItem::of_player(pool, player.id()))?);
+    character.statuses = smol::block_on(player.statuses())?
+        .into_iter()
+        .map(|(kind, _)| Status { kind, rounds: None })
+        .collect();
+
+    Ok(character)
+}
+
+/// Record the rolls of a finished encounter and apply its outcome to
+/// `player`
+fn store_outcome(
+    pool: &PgPool,
+    player: &Player,
+    record: &db::Encounter,
+    encounter: &Encounter,
+    outcome: &fight::Outcome,
+) -> Result<()> {
+    let uid = player.id();
+
+    smol::block_on(record.record_rolls(encounter.rolls()))?;
+    smol::block_on(LoggedRoll::insert(
+        pool,
+        uid,
+        Some(record.id()),
+        encounter.dice(),
+    ))?;
+
+    let mut consumed = outcome.consumed.clone();
+    consumed.sort_by_key(|item| item.key);
+    consumed.dedup();
+    for item in consumed {
+        let quantity = outcome
+            .consumed
+            .iter()
+            .filter(|used| **used == item)
+            .count();
+        smol::block_on(OwnedItem::drop(pool, uid, item, u32::try_from(quantity)?))?;
+    }
+    smol::block_on(player.end_encounter(
+        outcome.health,
+        chrono::Duration::minutes(DUNGEON_COOLDOWN),
+        &outcome.lingering,
+    ))?;
+    if outcome.gold > 0 {
+        smol::block_on(player.add_gold(
+            i32::try_from(outcome.gold)?,
+            GoldReason::Encounter,
+            Some(record.id()),
+        ))?;
+    }
+
+    Ok(())
+}
+
+fn level(args: Args, pool: &PgPool) -> Result<()> {
+    let player = Player::new(pool, args.user_id()?);
+
+    if !smol::block_on(player.exists())? || !smol::block_on(player.has_character())? {
+        args.writer.reply(
+            args.raw,
+            &format!(
+                "You don't have a character. Create one with `{} create`",
+                PREFIX
+            ),
+        )?;
+        return Ok(());
+    }
+
+    let experience = smol::block_on(player.experience())?;
+    let level = Level::from_experience(experience);
+
+    args.writer.reply(
+        args.raw,
+        &match level.next_experience() {
+            Some(next) => format!("You are level {} with {}/{} XP", level, experience, next),
+            None => format!("You are level {} with {} XP", level, experience),
+        },
+    )?;
+
+    Ok(())
+}
+
+fn powers(args: Args, pool: &PgPool) -> Result<()> {
+    let player = Player::new(pool, args.user_id()?);
+
+    if !smol::block_on(player.exists())? || !smol::block_on(player.has_character())? {
+        args.writer.reply(
+            args.raw,
+            &format!(
+                "You don't have a character. Create one with `{} create`",
+                PREFIX
+            ),
+        )?;
+        return Ok(());
+    }
+
+    let stats = smol::block_on(player.get_stats())?;
+    let powers: Vec<_> = stats
+        .class
+        .powers()
+        .map(|power| {
+            if power.level <= stats.level.get() {
+                power.to_string()
+            } else {
+                format!("{} at level {}", power, power.level)
+            }
+        })
+        .collect();
+
+    args.writer.reply(
+        args.raw,
+        &format!("Powers of a {}: {}", stats.class, powers.join(" | ")),
+    )?;
+
+    Ok(())
+}
+
+fn spells(args: Args, pool: &PgPool) -> Result<()> {
+    let player = Player::new(pool, args.user_id()?);
+
+    if !smol::block_on(player.exists())? || !smol::block_on(player.has_character())? {
+        args.writer.reply(
+            args.raw,
+            &format!(
+                "You don't have a character. Create one with `{} create`",
+                PREFIX
+            ),
+        )?;
+        return Ok(());
+    }
+
+    let stats = smol::block_on(player.get_stats())?;
+    let Some(dc) = stats.spell_save_dc() else {
+        args.writer
+            .reply(args.raw, &format!("A {} cannot cast spells", stats.class))?;
+        return Ok(());
+    };
+    let spells: Vec<_> = stats
+        .class
+        .spells()
+        .map(|spell| {
+            if spell.level <= stats.level.get() {
+                spell.to_string()
+            } else {
+                format!("{} at level {}", spell, spell.level)
+            }
+        })
+        .collect();
+
+    args.writer.reply(
+        args.raw,
+        &format!(
+            "Spells of a {} with {} mana and save DC {}: {}",
+            stats.class,
+            stats.max_mana(),
+            dc,
+            spells.join(" | ")
+        ),
+    )?;
+
+    Ok(())
+}
+
+fn train(args: Args, pool: &PgPool) -> Result<()> {
+    let player = Player::new(pool, args.user_id()?);
+
+    if !smol::block_on(player.exists())? || !smol::block_on(player.has_character())? {
+        args.writer.reply(
+            args.raw,
+            &format!(
+                "You don't have a character. Create one with `{} create`",
+                PREFIX
+            ),
+        )?;
+        return Ok(());
+    }
+
+    let ability = match args
+        .msg
+        .arguments
+        .first()
+        .map(|ability| ability.parse::<Ability>())
+    {
+        Some(Ok(ability)) => ability,
+        Some(Err(err)) => {
+            args.writer.reply(args.raw, &err.to_string())?;
+            return Ok(());
+        }
+        None => {
+            let points = smol::block_on(player.attribute_points())?;
+            args.writer.reply(
+                args.raw,
+                &format!(
+                    "You have {} attribute points. Spend them with `{} train <attribute> [points]`, e.g. `{} train str 2`",
+                    points, PREFIX, PREFIX
+                ),
+            )?;
+            return Ok(());
+        }
+    };
+
+    let points = match args.msg.arguments.get(1).map(|points| points.parse()) {
+        None => 1,
+        Some(Ok(points)) => points,
+        Some(Err(_)) => {
+            args.writer
+                .reply(args.raw, "The number of points must be a positive number")?;
+            return Ok(());
+        }
+    };
+
+    match smol::block_on(player.train(ability, points)) {
+        Ok((stats, left)) => args.writer.reply(
+            args.raw,
+            &format!(
+                "Your {} is now {}. You have {} attribute points left",
+                ability,
+                stats.attribute(ability).score(),
+                left
+            ),
+        )?,
+        Err(err) => args.writer.reply(args.raw, &err.to_string())?,
+    }
+
+    Ok(())
+}
+
+fn equip(args: Args, pool: &PgPool) -> Result<()> {
+    let player = Player::new(pool, args.user_id()?);
+
+    if !smol::block_on(player.exists())? || !smol::block_on(player.has_character())? {
+        args.writer.reply(
+            args.raw,
+            &format!(
+                "You don't have a character. Create one with `{} create`",
+                PREFIX
+            ),
+        )?;
+        return Ok(());
+    }
+
+    if args.msg.arguments.is_empty() {
+        let equipment = smol::block_on(OwnedItem::equipment(pool, player.id()))?;
+        args.writer
+            .reply(args.raw, &describe_equipment(&equipment))?;
+        return Ok(());
+    }
+
+    let name = args.msg.arguments.join(" ");
+    let Some(item) = Item::by_name(&name) else {
+        args.writer
+            .reply(args.raw, &format!("There is no item called {}", name))?;
+        return Ok(());
+    };
+
+    match smol::block_on(OwnedItem::equip(pool, player.id(), item)) {
+        Ok(Some(replaced)) => args.writer.reply(
+            args.raw,
+            &format!("You put away your {} and equip {}", replaced.name, item),
+        )?,
+        Ok(None) => args
+            .writer
+            .reply(args.raw, &format!("You equip {}", item))?,
+        Err(err) => args.writer.reply(args.raw, &err.to_string())?,
+    }
+
+    Ok(())
+}
+
+fn unequip(args: Args, pool: &PgPool) -> Result<()> {
+    let player = Player::new(pool, args.user_id()?);
+
+    if !smol::block_on(player.exists())? || !smol::block_on(player.has_character())? {
+        args.writer.reply(
+            args.raw,
+            &format!(
+                "You don't have a character. Create one with `{} create`",
+                PREFIX
+            ),
+        )?;
+        return Ok(());
+    }
+
+    // either a slot or the name of an equipped item
+    let name = args.msg.arguments.join(" ");
+    let slot = match name.parse::<Slot>() {
+        Ok(slot) => Some(slot),
+        Err(_) => Item::by_name(&name).and_then(|item| item.slot),
+    };
+
+    let Some(slot) = slot else {
+        args.writer.reply(
+            args.raw,
+            &format!(
+                "Unequip a slot ({}) or an item, e.g. `{} unequip shield`",
+                join(&Slot::ALL),
+                PREFIX
+            ),
+        )?;
+        return Ok(());
+    };
+
+    match smol::block_on(OwnedItem::unequip(pool, player.id(), slot))? {
+        Some(item) => args
+            .writer
+            .reply(args.raw, &format!("You put away your {}", item.name))?,
+        None => args
+            .writer
+            .reply(args.raw, &format!("You have nothing equipped as {}", slot))?,
+    }
+
+    Ok(())
+}
+
+fn inventory(args: Args, pool: &PgPool) -> Result<()> {
+    let player = Player::new(pool, args.user_id()?);
+
+    if !smol::block_on(player.exists())? || !smol::block_on(player.has_character())? {
+        args.writer.reply(
+            args.raw,
+            &format!(
+                "You don't have a character. Create one with `{} create`",
+                PREFIX
+            ),
+        )?;
+        return Ok(());
+    }
+
+    let stats = smol::block_on(player.get_stats())?;
+    let items = smol::block_on(OwnedItem::of_player(pool, player.id()))?;
+    let weight = items
+        .iter()
+        .try_fold(0, |total: u32, owned| total.checked_add(owned.weight()?))
+        .context("Your bag is too heavy to weigh")?;
+
+    let entries: Vec<_> = items
+        .iter()
+        .map(|owned| {
+            let mut entry = String::from(owned.item.name);
+            if owned.quantity > 1 {
+                entry = format!("{}x {}", owned.quantity, entry);
+            }
+            if owned.slot.is_some() {
+                entry.push('*');
+            }
+            entry
+        })
+        .collect();
+
+    let header = format!("🎒 {}/{} lb", weight, stats.carrying_capacity());
+    let reply = if entries.is_empty() {
+        format!("{}: empty", header)
+    } else {
+        format!(
+            "{}: {} (* equipped)",
+            header,
+            fit(&entries, MAX_ROLL_LEN - header.len())
+        )
+    };
+    args.writer.reply(args.raw, &reply)?;
+
+    Ok(())
+}
+
+fn drop_item(args: Args, pool: &PgPool) -> Result<()> {
+    let player = Player::new(pool, args.user_id()?);
+
+    if !smol::block_on(player.exists())? || !smol::block_on(player.has_character())? {
+        args.writer.reply(
+            args.raw,
+            &format!(
+                "You don't have a character. Create one with `{} create`",
+                PREFIX
+            ),
+        )?;
+        return Ok(());
+    }
+
+    let (item, quantity) = match item_and_quantity(&args.msg.arguments, "drop") {
+        Ok(Some(item)) => item,
+        Err(err) => {
+            args.writer.reply(args.raw, &err.to_string())?;
+            return Ok(());
+        }
+        Ok(None) => {
+            args.writer.reply(
+                args.raw,
+                &format!("Drop an item, e.g. `{} drop torch 3`", PREFIX),
+            )?;
+            return Ok(());
+        }
+    };
+
+    match smol::block_on(OwnedItem::drop(pool, player.id(), item, quantity)) {
+        Ok(1) => args
+            .writer
+            .reply(args.raw, &format!("You drop your {}", item.name))?,
+        Ok(dropped) => args
+            .writer
+            .reply(args.raw, &format!("You drop {}x {}", dropped, item.name))?,
+        Err(err) => args.writer.reply(args.raw, &err.to_string())?,
+    }
+
+    Ok(())
+}
+
+/// The item named by `arguments` and an optional quantity at the end, e.g.
+/// `torch 3`. Fails if the quantity is 0, `verb` names the action in the
+/// error.
+fn item_and_quantity(arguments: &[&str], verb: &str) -> Result<Option<(&'static Item, u32)>> {
+    let mut arguments = arguments.to_vec();
+    let quantity = match arguments.last().map(|last| last.parse::<u32>()) {
+        Some(Ok(quantity)) if arguments.len() > 1 => {
+            arguments.pop();
+            quantity
+        }
+        _ => 1,
+    };
+
+    let Some(item) = Item::by_name(&arguments.join(" ")) else {
+        return Ok(None);
+    };
+    ensure!(quantity > 0, "You cannot {} 0x {}", verb, item.name);
+    Ok(Some((item, quantity)))
+}
+
+fn shop(args: Args, pool: &PgPool) -> Result<()> {
+    let player = Player::new(pool, args.user_id()?);
+    let charisma = if smol::block_on(player.exists())? && smol::block_on(player.has_character())? {
+        smol::block_on(player.get_stats())?.attribute(Ability::Charisma)
+    } else {
+        0.into()
+    };
+
+    let stock =
+        smol::block_on(Shop::new(pool, args.raw.channel()).stock(Utc::today().naive_utc()))?;
+    let offers: Vec<_> = stock
+        .iter()
+        .map(|offer| match offer.quantity {
+            0 => format!("{} (sold out)", offer.item.name),
+            1 => format!(
+                "{} {}g",
+                offer.item.name,
+                shop::buy_price(offer.item, charisma)
+            ),
+            n => format!(
+                "{}x {} {}g",
+                n,
+                offer.item.name,
+                shop::buy_price(offer.item, charisma)
+            ),
+        })
+        .collect();
+
+    let footer = format!(" | buy with `{} buy <item>`", PREFIX);
+    args.writer.reply(
+        args.raw,
+        &format!("🛒 {}{}", fit(&offers, MAX_ROLL_LEN - footer.len()), footer),
+    )?;
+
+    Ok(())
+}
+
+fn buy(args: Args, pool: &PgPool) -> Result<()> {
+    let player = Player::new(pool, args.user_id()?);
+
+    if !smol::block_on(player.exists())? || !smol::block_on(player.has_character())? {
+        args.writer.reply(
+            args.raw,
+            &format!(
+                "You don't have a character. Create one with `{} create`",
+                PREFIX
+            ),
+        )?;
+        return Ok(());
+    }
+
+    let (item, quantity) = match item_and_quantity(&args.msg.arguments, "buy") {
+        Ok(Some(item)) => item,
+        Err(err) => {
+            args.writer.reply(args.raw, &err.to_string())?;
+            return Ok(());
+        }
+        Ok(None) => {
+            args.writer.reply(
+                args.raw,
+                &format!(
+                    "Buy an item from the shop, e.g. `{} buy potion of healing 2`",
+                    PREFIX
+                ),
+            )?;
+            return Ok(());
+        }
+    };
+
+    let stats = smol::block_on(player.get_stats())?;
+    let price = shop::buy_price(item, stats.attribute(Ability::Charisma));
+    let bought = smol::block_on(Shop::new(pool, args.raw.channel()).buy(
+        player.id(),
+        item,
+        quantity,
+        price,
+        stats.carrying_capacity(),
+        Utc::today().naive_utc(),
+    ));
+
+    match bought {
+        Ok(balance) => args.writer.reply(
+            args.raw,
+            &format!(
+                "You buy {}x {} for {} gold, {} gold left 💰",
+                quantity,
+                item.name,
+                price * quantity,
+                balance
+            ),
+        )?,
+        Err(err) => args.writer.reply(args.raw, &err.to_string())?,
+    }
+
+    Ok(())
+}
+
+fn sell(args: Args, pool: &PgPool) -> Result<()> {
+    let player = Player::new(pool, args.user_id()?);
+
+    if !smol::block_on(player.exists())? || !smol::block_on(player.has_character())? {
+        args.writer.reply(
+            args.raw,
+            &format!(
+                "You don't have a character. Create one with `{} create`",
+                PREFIX
+            ),
+        )?;
+        return Ok(());
+    }
+
+    let (item, quantity) = match item_and_quantity(&args.msg.arguments, "sell") {
+        Ok(Some(item)) => item,
+        Err(err) => {
+            args.writer.reply(args.raw, &err.to_string())?;
+            return Ok(());
+        }
+        Ok(None) => {
+            args.writer.reply(
+                args.raw,
+                &format!("Sell an item to the shop, e.g. `{} sell gemstone`", PREFIX),
+            )?;
+            return Ok(());
+        }
+    };
+
+    let stats = smol::block_on(player.get_stats())?;
+    let price = shop::sell_price(item, stats.attribute(Ability::Charisma));
+    let sold = smol::block_on(Shop::new(pool, args.raw.channel()).sell(
+        player.id(),
+        item,
+        quantity,
+        price,
+    ));
+
+    match sold {
+        Ok((sold, balance)) => args.writer.reply(
+            args.raw,
+            &format!(
+                "You sell {}x {} for {} gold, you have {} gold now 💰",
+                sold,
+                item.name,
+                price * sold,
+                balance
+            ),
+        )?,
+        Err(err) => args.writer.reply(args.raw, &err.to_string())?,
+    }
+
+    Ok(())
+}
+
+fn trade(args: Args, pool: &PgPool) -> Result<()> {
+    let uid = args.user_id()?;
+    let player = Player::new(pool, uid);
+
+    if !smol::block_on(player.exists())? || !smol::block_on(player.has_character())? {
+        args.writer.reply(
+            args.raw,
+            &format!(
+                "You don't have a character. Create one with `{} create`",
+                PREFIX
+            ),
+        )?;
+        return Ok(());
+    }
+
+    let (target, rest) = match args.msg.arguments.split_first() {
+        Some((target, rest)) if !rest.is_empty() => (target, rest),
+        _ => {
+            args.writer.reply(
+                args.raw,
+                &format!(
+                    "Trade with another player, e.g. `{} trade @name potion of healing 2, 10 gold for longsword`",
+                    PREFIX
+                ),
+            )?;
+            return Ok(());
+        }
+    };
+
+    // everything after `for` is what you want in return
+    let (offer, request) = match rest
+        .iter()
+        .position(|word| word.eq_ignore_ascii_case("for"))
+    {
+        Some(index) => (rest[..index].join(" "), rest[index + 1..].join(" ")),
+        None => (rest.join(" "), String::new()),
+    };
+    let bundles = offer
+        .parse::<Bundle>()
+        .and_then(|offer| Ok((offer, request.parse::<Bundle>()?)));
+    let (offer, request) = match bundles {
+        Ok(bundles) => bundles,
+        Err(err) => {
+            args.writer.reply(args.raw, &err.to_string())?;
+            return Ok(());
+        }
+    };
+
+    let partner = match smol::block_on(Player::find_by_name(pool, target))? {
+        Some(partner) if smol::block_on(partner.has_character())? => partner,
+        _ => {
+            args.writer
+                .reply(args.raw, &format!("{} doesn't have a character", target))?;
+            return Ok(());
+        }
+    };
+
+    smol::block_on(player.set_name(args.raw.name()))?;
+
+    let proposed = smol::block_on(Trade::propose(
+        pool,
+        uid,
+        partner.id(),
+        &offer,
+        &request,
+        chrono::Duration::minutes(TRADE_TIMEOUT),
+    ));
+    match proposed {
+        Ok(_) => args.writer.reply(
+            args.raw,
+            &format!(
+                "{} {} offers {} for {}. Both of you `{} accept` within {} minutes or `{} decline`",
+                target,
+                args.raw.name(),
+                offer,
+                request,
+                PREFIX,
+                TRADE_TIMEOUT,
+                PREFIX
+            ),
+        )?,
+        Err(err) => args.writer.reply(args.raw, &err.to_string())?,
+    }
+
+    Ok(())
+}
+
+fn accept(args: Args, pool: &PgPool) -> Result<()> {
+    let uid = args.user_id()?;
+
+    let Some(trade) = smol::block_on(Trade::pending(pool, uid))? else {
+        args.writer
+            .reply(args.raw, "You don't have a pending trade")?;
+        return Ok(());
+    };
+
+    let capacity = |id| -> Result<u32> {
+        Ok(smol::block_on(Player::new(pool, id).get_stats())?.carrying_capacity())
+    };
+    let accepted =
+        smol::block_on(trade.accept(pool, uid, capacity(trade.from_id)?, capacity(trade.to_id)?));
+
+    match accepted {
+        Ok(true) => args.writer.reply(
+            args.raw,
+            &format!("Trade done: {} for {} 🤝", trade.offer, trade.request),
+        )?,
+        Ok(false) => args
+            .writer
+            .reply(args.raw, "You accept the trade, waiting for the other side")?,
+        Err(err) => args.writer.reply(args.raw, &err.to_string())?,
+    }
+
+    Ok(())
+}
+
+fn decline(args: Args, pool: &PgPool) -> Result<()> {
+    match smol::block_on(Trade::pending(pool, args.user_id()?))? {
+        Some(trade) => {
+            smol::block_on(trade.cancel(pool))?;
+            args.writer.reply(
+                args.raw,
+                &format!("The trade of {} for {} is off", trade.offer, trade.request),
+            )?;
+        }
+        None => args
+            .writer
+            .reply(args.raw, "You don't have a pending trade")?,
+    }
+
+    Ok(())
+}
+
+fn inspect(args: Args, pool: &PgPool) -> Result<()> {
+    let name = args.msg.arguments.join(" ");
+    let item = match Item::by_name(&name) {
+        Some(item) => item,
+        None if name.is_empty() => {
+            args.writer.reply(
+                args.raw,
+                &format!("Inspect an item, e.g. `{} inspect longsword`", PREFIX),
+            )?;
+            return Ok(());
+        }
+        None => {
+            args.writer
+                .reply(args.raw, &format!("There is no item called {}", name))?;
+            return Ok(());
+        }
+    };
+
+    let player = Player::new(pool, args.user_id()?);
+    let owned: Vec<_> = if smol::block_on(player.exists())? {
+        smol::block_on(OwnedItem::of_player(pool, player.id()))?
+            .into_iter()
+            .filter(|owned| owned.item == item)
+            .collect()
+    } else {
+        Vec::new()
+    };
+    let quantity: u32 = owned.iter().map(|owned| owned.quantity).sum();
+
+    let mut reply = format!("{} | {} lb | worth {} gold", item, item.weight, item.price);
+    if quantity > 0 {
+        let _ = write!(reply, " | you have {}", quantity);
+        if owned.iter().any(|owned| owned.slot.is_some()) {
+            reply.push_str(", equipped");
+        }
+    }
+    args.writer.reply(args.raw, &reply)?;
+
+    Ok(())
+}
+
+/// Every healing item in `items`, once per item in a stack, weakest first
+fn healing_potions(items: &[OwnedItem]) -> Vec<&'static Item> {
+    let mut potions: Vec<_> = items
+        .iter()
+        .filter_map(|owned| match owned.item.effect {
+            Some(Effect::Heal(dice, bonus)) => Some((owned, dice.max() + i64::from(bonus))),
+            _ => None,
+        })
+        .flat_map(|(owned, healing)| (0..owned.quantity).map(move |_| (owned.item, healing)))
+        .collect();
+    potions.sort_by_key(|(_, healing)| *healing);
+
+    potions.into_iter().map(|(item, _)| item).collect()
+}
+
+fn use_item(args: Args, pool: &PgPool) -> Result<()> {
     let uid = args.user_id()?;
     let player = Player::new(pool, uid);
 
-    if !smol::block_on(player.exists())? {
+    if !smol::block_on(player.exists())? || !smol::block_on(player.has_character())? {
         args.writer.reply(
             args.raw,
-            "I cannot delete what doesn't exist: You are not in my records",
+            &format!(
+                "You don't have a character. Create one with `{} create`",
+                PREFIX
+            ),
         )?;
-
         return Ok(());
     }
 
-    match args.msg.arguments.get(0) {
-        Some(&"confirm") => {
-            smol::block_on(player.delete())?;
-            args.writer
-                .reply(args.raw, "I removed you from my records 🔥🗒")?
+    let name = args.msg.arguments.join(" ");
+    let Some(item) = Item::by_name(&name) else {
+        args.writer.reply(
+            args.raw,
+            &format!("Use an item, e.g. `{} use potion of healing`", PREFIX),
+        )?;
+        return Ok(());
+    };
+
+    let Some(effect) = item.effect else {
+        args.writer
+            .reply(args.raw, &format!("You cannot use a {}", item.name))?;
+        return Ok(());
+    };
+
+    let reply = match effect {
+        Effect::Heal(dice, bonus) => {
+            let max_health = max_health(&smol::block_on(player.get_stats())?);
+            let roll = Expression::with_bonus(dice, bonus.into()).roll(&mut GameRng::new())?;
+
+            smol::block_on(player.heal(item, roll.total, max_health)).and_then(|health| {
+                smol::block_on(LoggedRoll::insert(pool, uid, None, &roll.pools))?;
+
+                Ok(format!(
+                    "You use the {} and regain {} health ❤ {}/{}",
+                    item.name, roll.total, health, max_health
+                ))
+            })
         }
-        Some(_) => args.writer.reply(
-                args.raw,
-                &format!(
-                    "Type `{} unregister confirm` if you want to unregister",
-                    PREFIX
-                ),
-            )?,
-        _ => args.writer.reply(
-                args.raw,
-                &format!(
-                    "❗ This will delete your character and all of your progress. Are you sure about that? Type `{} unregister confirm` if you want to unregister",
-                    PREFIX
-                ),
-            )?,
+        Effect::Buff {
+            ability,
+            bonus,
+            encounters,
+        } => smol::block_on(player.buff(item, ability, bonus, encounters))
+            .map(|()| format!("You use the {}: {}", item.name, effect)),
+        Effect::Status { kind, encounters } => {
+            smol::block_on(player.start_status(item, kind, encounters))
+                .map(|()| format!("You use the {}: {}", item.name, effect))
+        }
+        Effect::ReduceCooldown(minutes) => smol::block_on(
+            player.reduce_cooldown(item, chrono::Duration::minutes(minutes)),
+        )
+        .map(|left| {
+            if left > chrono::Duration::zero() {
+                format!(
+                    "You use the {}, you can enter the dungeon in {}",
+                    item.name,
+                    describe_duration(left)
+                )
+            } else {
+                format!("You use the {}, you can enter the dungeon now", item.name)
+            }
+        }),
+        Effect::Cleanse => smol::block_on(player.cleanse(item)).map(|removed| match removed {
+            0 => format!("You use the {}, nothing harmful affected you", item.name),
+            1 => format!("You use the {} and remove 1 harmful effect", item.name),
+            n => format!("You use the {} and remove {} harmful effects", item.name, n),
+        }),
+    };
+
+    match reply {
+        Ok(reply) => args.writer.reply(args.raw, &reply)?,
+        Err(err) => args.writer.reply(args.raw, &err.to_string())?,
     }
 
     Ok(())
 }
 
-fn enter(args: Args, pool: &PgPool) -> Result<()> {
-    let uid = args.user_id()?;
-    let player = Player::new(pool, uid);
+fn gold(args: Args, pool: &PgPool) -> Result<()> {
+    let player = Player::new(pool, args.user_id()?);
 
     if !smol::block_on(player.exists())? {
         args.writer.reply(
             args.raw,
             &format!("You're not registered. Register with `{} register`", PREFIX),
         )?;
-
         return Ok(());
     }
 
-    if let Some(cooldown) = smol::block_on(player.can_enter())? {
+    let gold = smol::block_on(player.gold())?;
+    args.writer
+        .reply(args.raw, &format!("💰 You have {} gold", gold))?;
+
+    Ok(())
+}
+
+fn health(args: Args, pool: &PgPool) -> Result<()> {
+    let player = Player::new(pool, args.user_id()?);
+
+    if !smol::block_on(player.exists())? || !smol::block_on(player.has_character())? {
         args.writer.reply(
             args.raw,
-            &format!("You cannot enter the dungeon. Please wait for {}", cooldown),
+            &format!(
+                "You don't have a character. Create one with `{} create`",
+                PREFIX
+            ),
         )?;
+        return Ok(());
+    }
+
+    let max_health = max_health(&smol::block_on(player.get_stats())?);
+    let health = smol::block_on(player.health(max_health))?;
+
+    let reply = if health < max_health {
+        // round up to the next minute of rest
+        let minutes =
+            ((max_health - health) * fight::FULL_REST_MINUTES + max_health - 1) / max_health;
+        format!(
+            "❤ {}/{} health, fully rested in {}",
+            health,
+            max_health,
+            describe_duration(chrono::Duration::minutes(minutes))
+        )
+    } else {
+        format!("❤ {}/{} health", health, max_health)
+    };
+    args.writer.reply(args.raw, &reply)?;
+
+    Ok(())
+}
 
+fn stats(args: Args, pool: &PgPool) -> Result<()> {
+    let player = Player::new(pool, args.user_id()?);
+
+    if !smol::block_on(player.exists())? || !smol::block_on(player.has_character())? {
+        args.writer.reply(
+            args.raw,
+            &format!(
+                "You don't have a character. Create one with `{} create`",
+                PREFIX
+            ),
+        )?;
         return Ok(());
     }
 
     let stats = smol::block_on(player.get_stats())?;
+    let max_health = max_health(&stats);
+    let health = smol::block_on(player.health(max_health))?;
+    let statuses = smol::block_on(player.statuses())?;
+
+    args.writer.reply(
+        args.raw,
+        &character_card(&stats, health, max_health, &statuses),
+    )?;
+
+    Ok(())
+}
+
+/// The character at a glance, e.g. `Level 3 elf wizard | ❤ 12/22 | AC 13 |
+/// STR 10 DEX 14 (+2) ... | ✨ 10 mana, save DC 12 | 🍀 crit on 20, 0% reroll
+/// on failed checks, +0% loot, 2% treasure | blessed for 2 encounters`
+fn character_card(
+    stats: &CharacterStats,
+    health: i64,
+    max_health: i64,
+    statuses: &[(StatusKind, u16)],
+) -> String {
+    let attributes: Vec<_> = Ability::ALL
+        .iter()
+        .map(|&ability| {
+            let name = ability.to_string()[..3].to_ascii_uppercase();
+            let score = stats.attribute(ability).score();
+            match stats.buffs.get(ability) {
+                0 => format!("{} {}", name, score),
+                buff => format!("{} {} ({:+})", name, score, buff),
+            }
+        })
+        .collect();
+
+    let mut card = format!(
+        "Level {} {} {} | ❤ {}/{} | AC {} | {}",
+        stats.level,
+        stats.race,
+        stats.class,
+        health,
+        max_health,
+        stats.armor_class(),
+        attributes.join(" ")
+    );
+
+    if let Some(dc) = stats.spell_save_dc() {
+        let _ = write!(card, " | ✨ {} mana, save DC {}", stats.max_mana(), dc);
+    }
+
+    let crit = match stats.crit_threshold() {
+        20 => String::from("crit on 20"),
+        threshold => format!("crit on {}-20", threshold),
+    };
+    let _ = write!(
+        card,
+        " | 🍀 {}, {}% reroll on failed checks, +{}% loot{}, {}% treasure",
+        crit,
+        stats.reroll_chance(),
+        stats.loot_chance_bonus(),
+        if stats.lucky_loot() {
+            " drawn twice"
+        } else {
+            ""
+        },
+        stats.treasure_chance()
+    );
+
+    if !statuses.is_empty() {
+        let statuses: Vec<_> = statuses
+            .iter()
+            .map(|(kind, encounters)| match encounters {
+                1 => format!("{} for 1 encounter", kind),
+                n => format!("{} for {} encounters", kind, n),
+            })
+            .collect();
+        let _ = write!(card, " | {}", statuses.join(", "));
+    }
+
+    card
+}
+
+/// The maximum health of a character, rounded to whole points
+// the health of a character is far below the range of an `i64`
+#[allow(clippy::cast_possible_truncation)]
+fn max_health(stats: &CharacterStats) -> i64 {
+    stats.max_health().round() as i64
+}
+
+/// A duration for chat, e.g. `1h 5m`, rounded up to minutes
+fn describe_duration(duration: chrono::Duration) -> String {
+    let minutes = (duration.num_seconds() + 59) / 60;
+
+    match (minutes / 60, minutes % 60) {
+        (0, minutes) => format!("{}m", minutes),
+        (hours, 0) => format!("{}h", hours),
+        (hours, minutes) => format!("{}h {}m", hours, minutes),
+    }
+}
+
+/// Join `entries` with commas, leaving out the last ones if they do not fit
+/// into `max_len`
+fn fit(entries: &[String], max_len: usize) -> String {
+    let mut text = String::new();
+    for (index, entry) in entries.iter().enumerate() {
+        let rest = format!(" and {} more", entries.len() - index);
+        if text.len() + entry.len() + 2 + rest.len() > max_len && index + 1 < entries.len() {
+            text.push_str(&rest);
+            break;
+        }
+        if index > 0 {
+            text.push_str(", ");
+        }
+        text.push_str(entry);
+    }
+
+    text
+}
+
+/// The equipped items in one line
+fn describe_equipment(equipment: &Equipment) -> String {
+    Slot::ALL
+        .iter()
+        .map(|&slot| match equipment.get(slot) {
+            Some(item) => format!("{}: {}", slot, item),
+            None => format!("{}: nothing", slot),
+        })
+        .collect::<Vec<_>>()
+        .join(" | ")
+}
+
+fn verify(args: Args, pool: &PgPool) -> Result<()> {
+    let Some(Ok(id)) = args
+        .msg
+        .arguments
+        .first()
+        .map(|id| id.trim_start_matches('#').parse())
+    else {
+        args.writer.reply(
+            args.raw,
+            &format!(
+                "Check the rolls of an encounter, e.g. `{} verify 42`",
+                PREFIX
+            ),
+        )?;
+        return Ok(());
+    };
+
+    let Some(record) = smol::block_on(db::Encounter::find(pool, id))? else {
+        args.writer
+            .reply(args.raw, &format!("There is no encounter #{}", id))?;
+        return Ok(());
+    };
+
+    let Some(seed) = smol::block_on(record.revealed_seed())? else {
+        args.writer.reply(
+            args.raw,
+            &format!("The seed of encounter #{} was not revealed", id),
+        )?;
+        return Ok(());
+    };
+
+    let commitment = smol::block_on(record.commitment())?;
+    let rolls = smol::block_on(record.rolls())?;
+    let verification = encounter::verify(&commitment, seed, &rolls)?;
 
-    //dbg!(stats.dps());
-    //dbg!(stats.max_health());
+    args.writer.reply(
+        args.raw,
+        &format!(
+            "Encounter #{}: {} {}",
+            id,
+            verification,
+            if verification.is_valid() {
+                "✅"
+            } else {
+                "❌"
+            }
+        ),
+    )?;
 
     Ok(())
 }
@@ -118,10 +1558,10 @@ fn ping(args: Args) -> Result<()> {
     let latency = args
         .raw
         .tmi_sent_ts()
-        .map(|ts| NaiveDateTime::from_timestamp(ts as i64, 0))
+        .and_then(|ts| i64::try_from(ts).ok())
+        .map(|ts| NaiveDateTime::from_timestamp(ts, 0))
         .map(|time| time.signed_duration_since(Utc::now().naive_utc()))
-        .map(|duration| duration.to_string())
-        .unwrap_or(String::from("unknown"));
+        .map_or(String::from("unknown"), |duration| duration.to_string());
 
     args.writer.reply(
         args.raw,
@@ -135,13 +1575,261 @@ fn ping(args: Args) -> Result<()> {
     Ok(())
 }
 
+fn roll(args: Args) -> Result<()> {
+    if args.msg.arguments.is_empty() {
+        args.writer.reply(
+            args.raw,
+            &format!("Roll some dice, e.g. `{} roll 4d6kh3+2`", PREFIX),
+        )?;
+        return Ok(());
+    }
+
+    let text = args.msg.arguments.join(" ");
+    let result = text
+        .parse::<Expression>()
+        .and_then(|expression| Ok((expression.roll(&mut rand::thread_rng())?, expression)));
+
+    match result {
+        Ok((roll, expression)) => {
+            // the expression counts towards the length of the reply as well
+            let prefix = format!("🎲 {}: ", expression);
+            let reply = match MAX_ROLL_LEN.checked_sub(prefix.chars().count()) {
+                Some(left) => prefix + &roll.summary(left),
+                None => format!("🎲 {}", roll.total),
+            };
+            args.writer.reply(args.raw, &reply)?;
+        }
+        Err(err) => args.writer.reply(args.raw, &err.to_string())?,
+    }
+
+    Ok(())
+}
+
+fn odds(args: Args) -> Result<()> {
+    if args.msg.arguments.is_empty() {
+        args.writer.reply(
+            args.raw,
+            &format!(
+                "Show the odds of a roll, e.g. `{0} odds 2d6+1` or `{0} odds d20+3 vs 15`",
+                PREFIX
+            ),
+        )?;
+        return Ok(());
+    }
+
+    match describe_odds(&args.msg.arguments.join(" ")) {
+        Ok(reply) => args.writer.reply(args.raw, &reply)?,
+        Err(err) => args.writer.reply(args.raw, &err.to_string())?,
+    }
+
+    Ok(())
+}
+
+/// The odds of a dice expression, optionally against a target like `d20+3 vs 15`
+fn describe_odds(text: &str) -> Result<String> {
+    let (text, target) = match text.rsplitn(2, " vs ").collect::<Vec<_>>()[..] {
+        [target, text] => (text, Some(target.trim())),
+        _ => (text, None),
+    };
+
+    let expression = text.parse::<Expression>()?;
+    let pmf = expression.pmf()?;
+
+    Ok(match target {
+        Some(target) => {
+            let target: i64 = target
+                .parse()
+                .with_context(|| format!("`{}` is not a number", target))?;
+
+            format!(
+                "🎲 {}: {:.1}% to get {} or more",
+                expression,
+                pmf.chance_at_least(target) * 100.0,
+                target
+            )
+        }
+        None => format!(
+            "🎲 {}: {} to {}, average {:.2}, median {}, 90% between {} and {}",
+            expression,
+            pmf.min(),
+            pmf.max(),
+            pmf.mean(),
+            pmf.percentile(0.5),
+            pmf.percentile(0.05),
+            pmf.percentile(0.95)
+        ),
+    })
+}
+
+fn is_owner(args: &Args, config: &Config) -> bool {
+    config
+        .owner()
+        .is_some_and(|owner| owner == args.raw.name().to_ascii_lowercase())
+}
+
+fn is_moderator(args: &Args, config: &Config) -> bool {
+    args.raw.is_moderator() || args.raw.is_broadcaster() || is_owner(args, config)
+}
+
+fn rolls(args: Args, pool: &PgPool, config: &Config) -> Result<()> {
+    if !is_moderator(&args, config) {
+        args.writer.reply(args.raw, "Only moderators can do that")?;
+        return Ok(());
+    }
+
+    let Some(target) = args.msg.arguments.first() else {
+        args.writer.reply(
+            args.raw,
+            &format!(
+                "Show recent rolls, e.g. `{0} rolls @name 5` or `{0} rolls #42` for an encounter",
+                PREFIX
+            ),
+        )?;
+        return Ok(());
+    };
+
+    let rolls = if let Some(id) = target.strip_prefix('#') {
+        let Ok(id) = id.parse() else {
+            args.writer
+                .reply(args.raw, &format!("`{}` is not an encounter", target))?;
+            return Ok(());
+        };
+        smol::block_on(LoggedRoll::of_encounter(pool, id))?
+    } else {
+        let limit = match args.msg.arguments.get(1).map(|n| n.parse::<i64>()) {
+            Some(Ok(n)) => n.clamp(1, MAX_LOGGED_ROLLS),
+            Some(Err(_)) | None => DEFAULT_LOGGED_ROLLS,
+        };
+
+        if let Some(player) = smol::block_on(Player::find_by_name(pool, target))? {
+            smol::block_on(player.recent_rolls(limit))?
+        } else {
+            args.writer
+                .reply(args.raw, &format!("I don't know {}", target))?;
+            return Ok(());
+        }
+    };
+
+    if rolls.is_empty() {
+        args.writer.reply(args.raw, "No rolls found")?;
+        return Ok(());
+    }
+
+    let mut reply = String::new();
+    for roll in rolls {
+        let roll = roll.to_string();
+        if reply.len() + roll.len() + 3 > MAX_ROLL_LEN {
+            break;
+        }
+        if !reply.is_empty() {
+            reply.push_str(" | ");
+        }
+        reply.push_str(&roll);
+    }
+
+    args.writer.reply(args.raw, &reply)?;
+
+    Ok(())
+}
+
+fn ledger(args: Args, pool: &PgPool, config: &Config) -> Result<()> {
+    if !is_moderator(&args, config) {
+        args.writer.reply(args.raw, "Only moderators can do that")?;
+        return Ok(());
+    }
+
+    let Some(target) = args.msg.arguments.first() else {
+        args.writer.reply(
+            args.raw,
+            &format!("Show recent gold changes, e.g. `{} ledger @name 5`", PREFIX),
+        )?;
+        return Ok(());
+    };
+
+    let limit = match args.msg.arguments.get(1).map(|n| n.parse::<i64>()) {
+        Some(Ok(n)) => n.clamp(1, MAX_LEDGER_ENTRIES),
+        Some(Err(_)) | None => DEFAULT_LEDGER_ENTRIES,
+    };
+
+    let entries = if let Some(player) = smol::block_on(Player::find_by_name(pool, target))? {
+        smol::block_on(player.recent_ledger(limit))?
+    } else {
+        args.writer
+            .reply(args.raw, &format!("I don't know {}", target))?;
+        return Ok(());
+    };
+
+    if entries.is_empty() {
+        args.writer.reply(args.raw, "No gold changes found")?;
+        return Ok(());
+    }
+
+    let mut reply = String::new();
+    for entry in entries {
+        let entry = entry.to_string();
+        if reply.len() + entry.len() + 3 > MAX_ROLL_LEN {
+            break;
+        }
+        if !reply.is_empty() {
+            reply.push_str(" | ");
+        }
+        reply.push_str(&entry);
+    }
+
+    args.writer.reply(args.raw, &reply)?;
+
+    Ok(())
+}
+
+fn reload(args: Args, reloader: &Reloader) -> Result<()> {
+    if !is_owner(&args, &reloader.config()) {
+        args.writer
+            .reply(args.raw, "Only the owner of the bot can do that")?;
+        return Ok(());
+    }
+
+    match reloader.reload() {
+        Ok(changes) => {
+            changes.apply(args.writer)?;
+            args.writer.reply(
+                args.raw,
+                &format!("Reloaded configuration: {}", changes.summary()),
+            )?;
+        }
+        Err(err) => args.writer.reply(
+            args.raw,
+            &format!(
+                "Could not reload configuration, keeping the old one. {:#}",
+                err
+            )
+            .replace('\n', " "),
+        )?,
+    }
+
+    Ok(())
+}
+
+// registers every command of the bot
+#[allow(clippy::too_many_lines)]
 fn main() -> Result<()> {
     // evaluate boot time
     let _ = *BOOT_TIME;
 
-    SimpleLogger::new().with_level(LevelFilter::Debug).init()?;
+    let opt = Opt::from_args();
+
+    // the level is lowered once the configuration is loaded
+    SimpleLogger::new().with_level(LevelFilter::Trace).init()?;
+
+    let config = Config::load(&opt.config)?;
+
+    log::set_max_level(
+        opt.log_level
+            .or_else(|| config.log_level())
+            .unwrap_or(DEFAULT_LOG_LEVEL),
+    );
 
-    let config = Config::load("config.ron")?;
+    let reloader =
+        Arc::new(Reloader::new(&opt.config, config.clone()).with_log_level_override(opt.log_level));
 
     let pool = smol::block_on(
         async {
@@ -171,31 +1859,134 @@ fn main() -> Result<()> {
 
             Ok(())
         })
-        .with_command("register", Vec::new(), {
+        .with_command("register", &[], {
             let pool = pool.clone();
             move |args: Args| register(args, &pool)
         })
-        .with_command("unregister", Vec::new(), {
+        .with_command("unregister", &[], {
             let pool = pool.clone();
             move |args: Args| unregister(args, &pool)
         })
-        .with_command("enter", vec!["e"], {
+        .with_command("create", &[], {
+            let pool = pool.clone();
+            let reloader = reloader.clone();
+            move |args: Args| create(args, &pool, reloader.config().creation_method())
+        })
+        .with_command("enter", &["e"], {
+            let pool = pool.clone();
+            let reloader = reloader.clone();
+            move |args: Args| enter(args, &pool, &reloader.config())
+        })
+        .with_command("rolls", &[], {
+            let pool = pool.clone();
+            let reloader = reloader.clone();
+            move |args: Args| rolls(args, &pool, &reloader.config())
+        })
+        .with_command("ledger", &[], {
+            let pool = pool.clone();
+            let reloader = reloader.clone();
+            move |args: Args| ledger(args, &pool, &reloader.config())
+        })
+        .with_command("level", &["lvl"], {
+            let pool = pool.clone();
+            move |args: Args| level(args, &pool)
+        })
+        .with_command("powers", &[], {
+            let pool = pool.clone();
+            move |args: Args| powers(args, &pool)
+        })
+        .with_command("spells", &[], {
+            let pool = pool.clone();
+            move |args: Args| spells(args, &pool)
+        })
+        .with_command("train", &[], {
+            let pool = pool.clone();
+            move |args: Args| train(args, &pool)
+        })
+        .with_command("equip", &[], {
             let pool = pool.clone();
-            move |args: Args| enter(args, &pool)
+            move |args: Args| equip(args, &pool)
         })
-        .with_command("help", vec!["commands"], |args: Args| {
+        .with_command("unequip", &[], {
+            let pool = pool.clone();
+            move |args: Args| unequip(args, &pool)
+        })
+        .with_command("inventory", &["inv", "bag"], {
+            let pool = pool.clone();
+            move |args: Args| inventory(args, &pool)
+        })
+        .with_command("drop", &[], {
+            let pool = pool.clone();
+            move |args: Args| drop_item(args, &pool)
+        })
+        .with_command("inspect", &[], {
+            let pool = pool.clone();
+            move |args: Args| inspect(args, &pool)
+        })
+        .with_command("use", &[], {
+            let pool = pool.clone();
+            move |args: Args| use_item(args, &pool)
+        })
+        .with_command("gold", &[], {
+            let pool = pool.clone();
+            move |args: Args| gold(args, &pool)
+        })
+        .with_command("stats", &["char"], {
+            let pool = pool.clone();
+            move |args: Args| stats(args, &pool)
+        })
+        .with_command("health", &["hp"], {
+            let pool = pool.clone();
+            move |args: Args| health(args, &pool)
+        })
+        .with_command("shop", &[], {
+            let pool = pool.clone();
+            move |args: Args| shop(args, &pool)
+        })
+        .with_command("buy", &[], {
+            let pool = pool.clone();
+            move |args: Args| buy(args, &pool)
+        })
+        .with_command("sell", &[], {
+            let pool = pool.clone();
+            move |args: Args| sell(args, &pool)
+        })
+        .with_command("trade", &[], {
+            let pool = pool.clone();
+            move |args: Args| trade(args, &pool)
+        })
+        .with_command("accept", &[], {
+            let pool = pool.clone();
+            move |args: Args| accept(args, &pool)
+        })
+        .with_command("decline", &[], {
+            let pool = pool.clone();
+            move |args: Args| decline(args, &pool)
+        })
+        .with_command("verify", &[], {
+            let pool = pool.clone();
+            move |args: Args| verify(args, &pool)
+        })
+        .with_command("help", &["commands"], |args: Args| {
             args.writer
                 .reply(args.raw, "this command is not yet implemented")?;
             Ok(())
         })
-        .with_command("ping", Vec::new(), ping)
-        .with_command("repo", vec!["source"], |args: Args| {
+        .with_command("ping", &[], ping)
+        .with_command("roll", &["r"], roll)
+        .with_command("odds", &[], odds)
+        .with_command("reload", &[], {
+            let reloader = reloader.clone();
+            move |args: Args| reload(args, &reloader)
+        })
+        .with_command("repo", &["source"], |args: Args| {
             args.writer.reply(
                 args.raw,
                 &format!("the source code can be found here: {}", APP_REPO),
             )?;
             Ok(())
-        });
+        })
+        .with_reloader(reloader);
 
     // run the bot in the executor
     smol::block_on(bot.run(&config.user_config()?, config.channels()))
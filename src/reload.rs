@@ -0,0 +1,361 @@
+use crate::{character::CreationMethod, Config};
+use anyhow::{Context, Result};
+use log::{debug, error, info, warn, LevelFilter};
+use notify::{DebouncedEvent, RecursiveMode, Watcher};
+use std::{
+    fs,
+    path::PathBuf,
+    sync::{mpsc, Arc, Mutex},
+    thread,
+    time::Duration,
+};
+use twitchchat::{commands, Writer};
+
+/// The log level used if neither the command line nor the config set one
+pub const DEFAULT_LOG_LEVEL: LevelFilter = LevelFilter::Debug;
+
+/// How long to wait for more file system events before reloading
+const DEBOUNCE_DELAY: Duration = Duration::from_secs(1);
+
+/// The differences between two configurations
+#[derive(Debug, Default, PartialEq)]
+pub struct Changes {
+    pub join: Vec<String>,
+    pub part: Vec<String>,
+    pub log_level: Option<LevelFilter>,
+    pub owner: Option<Option<String>>,
+    pub provably_fair: Option<bool>,
+    pub creation_method: Option<CreationMethod>,
+    /// Fields that changed but only take effect after a restart
+    pub restart_required: Vec<&'static str>,
+}
+
+impl Changes {
+    fn between(
+        old: &Config<'_>,
+        new: &Config<'_>,
+        log_level_override: Option<LevelFilter>,
+    ) -> Self {
+        let old_channels: Vec<_> = old.channels().iter().map(|c| normalize(c)).collect();
+        let new_channels: Vec<_> = new.channels().iter().map(|c| normalize(c)).collect();
+
+        let log_level = |config: &Config<'_>| {
+            log_level_override
+                .or_else(|| config.log_level())
+                .unwrap_or(DEFAULT_LOG_LEVEL)
+        };
+
+        let mut restart_required = Vec::new();
+        if !old.same_credentials(new) {
+            restart_required.push("username/token");
+        }
+        if old.database_url() != new.database_url() {
+            restart_required.push("database_url");
+        }
+
+        Self {
+            join: new_channels
+                .iter()
+                .filter(|c| !old_channels.contains(c))
+                .cloned()
+                .collect(),
+            part: old_channels
+                .iter()
+                .filter(|c| !new_channels.contains(c))
+                .cloned()
+                .collect(),
+            log_level: Some(log_level(new)).filter(|level| *level != log_level(old)),
+            owner: Some(new.owner().map(String::from))
+                .filter(|owner| owner.as_deref() != old.owner()),
+            provably_fair: Some(new.provably_fair()).filter(|fair| *fair != old.provably_fair()),
+            creation_method: Some(new.creation_method())
+                .filter(|method| *method != old.creation_method()),
+            restart_required,
+        }
+    }
+
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// Join and part channels and set the new log level
+    ///
+    /// # Errors
+    ///
+    /// Fails if a join or part message cannot be sent
+    pub fn apply(&self, writer: &mut Writer) -> Result<()> {
+        for channel in &self.join {
+            info!("joining: {}", channel);
+            writer.encode_sync(commands::join(channel))?;
+        }
+
+        for channel in &self.part {
+            info!("leaving: {}", channel);
+            writer.encode_sync(commands::part(channel))?;
+        }
+
+        if let Some(level) = self.log_level {
+            info!("setting log level to {}", level);
+            log::set_max_level(level);
+        }
+
+        for field in &self.restart_required {
+            warn!("{} changed. Restart the bot to apply it", field);
+        }
+
+        Ok(())
+    }
+
+    /// A short description of the changes that fits in a chat message
+    #[must_use]
+    pub fn summary(&self) -> String {
+        if self.is_empty() {
+            return String::from("nothing changed");
+        }
+
+        let mut parts = Vec::new();
+        if !self.join.is_empty() {
+            parts.push(format!("joined {}", self.join.join(", ")));
+        }
+        if !self.part.is_empty() {
+            parts.push(format!("left {}", self.part.join(", ")));
+        }
+        if let Some(level) = self.log_level {
+            parts.push(format!("log level {}", level));
+        }
+        if let Some(owner) = &self.owner {
+            parts.push(format!("owner {}", owner.as_deref().unwrap_or("none")));
+        }
+        if let Some(fair) = self.provably_fair {
+            parts.push(format!("provably fair {}", if fair { "on" } else { "off" }));
+        }
+        if let Some(method) = self.creation_method {
+            parts.push(format!("creation method {}", method));
+        }
+        if !self.restart_required.is_empty() {
+            parts.push(format!(
+                "restart required for {}",
+                self.restart_required.join(", ")
+            ));
+        }
+
+        parts.join("; ")
+    }
+}
+
+fn normalize(channel: &str) -> String {
+    channel.trim_start_matches('#').to_ascii_lowercase()
+}
+
+/// Keeps the current configuration and replaces it when the file changes
+pub struct Reloader {
+    path: PathBuf,
+    log_level_override: Option<LevelFilter>,
+    config: Mutex<Config<'static>>,
+}
+
+impl Reloader {
+    pub fn new<P>(path: P, config: Config<'static>) -> Self
+    where
+        P: Into<PathBuf>,
+    {
+        Self {
+            path: path.into(),
+            log_level_override: None,
+            config: Mutex::new(config),
+        }
+    }
+
+    /// Keep this log level regardless of the config file, e.g. because it was
+    /// set on the command line
+    #[must_use]
+    pub fn with_log_level_override(mut self, level: Option<LevelFilter>) -> Self {
+        self.log_level_override = level;
+        self
+    }
+
+    /// A copy of the current configuration
+    ///
+    /// # Panics
+    ///
+    /// Panics if a reload panicked while holding the configuration
+    pub fn config(&self) -> Config<'static> {
+        self.config.lock().unwrap().clone()
+    }
+
+    /// Read and validate the config file and replace the current
+    /// configuration. An invalid file leaves the current configuration
+    /// untouched, and fields that need a restart keep their running values.
+    ///
+    /// # Errors
+    ///
+    /// Fails if the config file cannot be loaded, see [`Config::load`]
+    ///
+    /// # Panics
+    ///
+    /// Panics if another reload panicked while holding the configuration
+    pub fn reload(&self) -> Result<Changes> {
+        let mut new = Config::load(&self.path)?;
+        let mut config = self.config.lock().unwrap();
+        let changes = Changes::between(&config, &new, self.log_level_override);
+        new.keep_restart_fields(&config);
+        *config = new;
+
+        Ok(changes)
+    }
+
+    /// Reload and apply the configuration every time the file changes
+    ///
+    /// # Errors
+    ///
+    /// Fails if the directory of the config file cannot be watched
+    pub fn watch(self: Arc<Self>, mut writer: Writer) -> Result<()> {
+        let path = fs::canonicalize(&self.path)
+            .with_context(|| format!("Could not watch {}", self.path.display()))?;
+        let dir = path
+            .parent()
+            .context("Config file has no parent directory")?
+            .to_path_buf();
+
+        let (sender, receiver) = mpsc::channel();
+        let mut watcher = notify::watcher(sender, DEBOUNCE_DELAY)?;
+        watcher.watch(&dir, RecursiveMode::NonRecursive)?;
+
+        info!("watching {} for changes", path.display());
+
+        thread::spawn(move || {
+            // the watcher stops when it is dropped
+            let _watcher = watcher;
+
+            for event in receiver {
+                match event {
+                    DebouncedEvent::Create(file)
+                    | DebouncedEvent::Write(file)
+                    | DebouncedEvent::Rename(_, file)
+                        if file == path =>
+                    {
+                        debug!("{} changed", path.display());
+                        match self.reload() {
+                            Ok(changes) => {
+                                info!("reloaded configuration: {}", changes.summary());
+                                if let Err(err) = changes.apply(&mut writer) {
+                                    error!("Could not apply configuration: {}", err);
+                                }
+                            }
+                            Err(err) => error!("Could not reload configuration: {:#}", err),
+                        }
+                    }
+                    DebouncedEvent::Error(err, _) => error!("Could not watch config: {}", err),
+                    _ => {}
+                }
+            }
+        });
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write as _;
+
+    const TOKEN: &str = "oauth:abcdefghijklmnopqrstuvwxyz0123";
+
+    fn write_config(path: &PathBuf, channels: &[&str], log_level: &str) {
+        write_config_with_database(path, channels, log_level, "postgres://localhost/dungeon");
+    }
+
+    fn write_config_with_database(
+        path: &PathBuf,
+        channels: &[&str],
+        log_level: &str,
+        database_url: &str,
+    ) {
+        let mut file = fs::File::create(path).unwrap();
+        write!(
+            file,
+            r#"(
+    username: "dungeon_bot",
+    token: "{}",
+    channels: {:?},
+    database_url: "{}",
+    log_level: "{}",
+)"#,
+            TOKEN, channels, database_url, log_level
+        )
+        .unwrap();
+    }
+
+    fn config_path(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!(
+            "dungeon-bot-reload-{}-{}.ron",
+            name,
+            std::process::id()
+        ))
+    }
+
+    #[test]
+    fn channels_and_log_level() {
+        let path = config_path("changes");
+        write_config(&path, &["a", "b"], "info");
+        let reloader = Reloader::new(&path, Config::load(&path).unwrap());
+
+        write_config(&path, &["b", "#C"], "warn");
+        let changes = reloader.reload().unwrap();
+        fs::remove_file(&path).unwrap();
+
+        assert_eq!(changes.join, ["c"]);
+        assert_eq!(changes.part, ["a"]);
+        assert_eq!(changes.log_level, Some(LevelFilter::Warn));
+        assert_eq!(changes.summary(), "joined c; left a; log level WARN");
+        assert_eq!(reloader.config().channels(), ["b", "#C"]);
+    }
+
+    #[test]
+    fn log_level_override() {
+        let path = config_path("override");
+        write_config(&path, &[], "info");
+        let reloader = Reloader::new(&path, Config::load(&path).unwrap())
+            .with_log_level_override(Some(LevelFilter::Trace));
+
+        write_config(&path, &[], "warn");
+        let changes = reloader.reload().unwrap();
+        fs::remove_file(&path).unwrap();
+
+        assert!(changes.is_empty());
+    }
+
+    #[test]
+    fn restart_keeps_running_values() {
+        let path = config_path("restart");
+        write_config(&path, &["a"], "info");
+        let reloader = Reloader::new(&path, Config::load(&path).unwrap());
+
+        write_config_with_database(&path, &["a"], "warn", "postgres://elsewhere/dungeon");
+        let changes = reloader.reload().unwrap();
+        fs::remove_file(&path).unwrap();
+
+        assert_eq!(changes.restart_required, ["database_url"]);
+        assert_eq!(
+            reloader.config().database_url(),
+            "postgres://localhost/dungeon"
+        );
+        assert_eq!(reloader.config().log_level(), Some(LevelFilter::Warn));
+    }
+
+    #[test]
+    fn invalid_file_keeps_config() {
+        let path = config_path("invalid");
+        write_config(&path, &["a"], "info");
+        let reloader = Reloader::new(&path, Config::load(&path).unwrap());
+
+        write_config(&path, &["a"], "loud");
+        let result = reloader.reload();
+        fs::remove_file(&path).unwrap();
+
+        assert!(result.is_err());
+        assert_eq!(reloader.config().log_level(), Some(LevelFilter::Info));
+    }
+}
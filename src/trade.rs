@@ -0,0 +1,134 @@
+//! Trades of items and gold between players
+
+use crate::item::Item;
+use anyhow::{anyhow, bail, ensure, Result};
+use std::{fmt, str::FromStr};
+
+/// Both players have to accept a trade within this many minutes
+pub const TRADE_TIMEOUT: i64 = 5;
+
+/// The gold and items one side of a trade gives
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Bundle {
+    pub gold: u32,
+    pub items: Vec<(&'static Item, u32)>,
+}
+
+impl Bundle {
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.gold == 0 && self.items.is_empty()
+    }
+
+    /// Add `quantity` of `item`, merging it with the same item
+    ///
+    /// # Errors
+    ///
+    /// Fails if the total quantity overflows
+    pub fn add(&mut self, item: &'static Item, quantity: u32) -> Result<()> {
+        match self.items.iter_mut().find(|(other, _)| *other == item) {
+            Some((_, total)) => {
+                *total = total
+                    .checked_add(quantity)
+                    .ok_or_else(|| anyhow!("You cannot trade that many {}", item.name))?;
+            }
+            None => self.items.push((item, quantity)),
+        }
+
+        Ok(())
+    }
+}
+
+impl FromStr for Bundle {
+    type Err = anyhow::Error;
+
+    /// Parse a comma separated list of items with an optional quantity and
+    /// gold, e.g. `potion of healing 2, dagger, 50 gold`
+    fn from_str(s: &str) -> Result<Self> {
+        let mut bundle = Self::default();
+
+        for entry in s
+            .split(',')
+            .map(str::trim)
+            .filter(|entry| !entry.is_empty())
+        {
+            let (name, quantity) = match entry.rsplitn(2, ' ').collect::<Vec<_>>().as_slice() {
+                [last, rest] => match last.parse::<u32>() {
+                    Ok(quantity) => (*rest, quantity),
+                    Err(_) => (entry, 1),
+                },
+                _ => (entry, 1),
+            };
+
+            if let Some(gold) = entry
+                .strip_suffix("gold")
+                .and_then(|gold| gold.trim().parse::<u32>().ok())
+            {
+                bundle.gold = bundle
+                    .gold
+                    .checked_add(gold)
+                    .ok_or_else(|| anyhow!("You cannot trade that much gold"))?;
+                continue;
+            }
+
+            ensure!(quantity > 0, "You cannot trade 0x {}", name);
+            match Item::by_name(name) {
+                Some(item) => bundle.add(item, quantity)?,
+                None => bail!("`{}` is not an item", name),
+            }
+        }
+
+        Ok(bundle)
+    }
+}
+
+impl fmt::Display for Bundle {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut entries: Vec<_> = self
+            .items
+            .iter()
+            .map(|(item, quantity)| match quantity {
+                1 => item.name.to_string(),
+                n => format!("{}x {}", n, item.name),
+            })
+            .collect();
+        if self.gold > 0 {
+            entries.push(format!("{} gold", self.gold));
+        }
+
+        if entries.is_empty() {
+            f.write_str("nothing")
+        } else {
+            f.write_str(&entries.join(", "))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse() {
+        let bundle: Bundle = "potion of healing 2, dagger, 50 gold, Potion of Healing"
+            .parse()
+            .unwrap();
+
+        assert_eq!(bundle.gold, 50);
+        assert_eq!(
+            bundle.items,
+            [
+                (Item::by_key("potion_of_healing").unwrap(), 3),
+                (Item::by_key("dagger").unwrap(), 1),
+            ]
+        );
+        assert_eq!(bundle.to_string(), "3x potion of healing, dagger, 50 gold");
+
+        assert!("".parse::<Bundle>().unwrap().is_empty());
+        assert_eq!("".parse::<Bundle>().unwrap().to_string(), "nothing");
+        assert!("excalibur".parse::<Bundle>().is_err());
+        assert!("dagger 0".parse::<Bundle>().is_err());
+        assert!("dagger 4294967295, dagger".parse::<Bundle>().is_err());
+        assert!("4294967295 gold, 1 gold".parse::<Bundle>().is_err());
+    }
+}